@@ -5,19 +5,184 @@
  * This enables unrestricted browser control without iframe limitations.
  */
 
+use headless_chrome::browser::tab::element::Element;
+use headless_chrome::protocol::cdp::Runtime;
 use headless_chrome::{Browser, LaunchOptions, Tab};
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+mod console;
+mod dialogs;
+mod error;
+mod files;
+mod forms;
+mod frames;
+mod input;
+mod intercept;
+mod links;
+mod marks;
+mod network;
+mod paging;
+mod pdf;
+mod popups;
+mod readable;
+mod screenshot;
+mod snapshot;
+mod storage;
+mod target;
+pub mod tools;
+mod wait;
+
+pub use console::ConsoleLevel;
+pub use dialogs::{DialogAnswer, DialogLog, DialogPolicy};
+pub use error::{ErrorCode, ToolError};
+pub use links::LinkFilter;
+pub use paging::PageRequest;
+pub use pdf::PdfOptions;
+pub use readable::ExtractMode;
+pub use screenshot::ScreenshotOptions;
+pub use target::{OptionalTarget, Target};
+pub use wait::WaitFor;
+
+/// How long blocking waits sleep between cancellation checks
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How a tool call ended
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolStatus {
+    #[default]
+    Ok,
+    Error,
+    Cancelled,
+    TimedOut,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToolResult {
     pub success: bool,
+    #[serde(default)]
+    pub status: ToolStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
+    pub error: Option<ToolError>,
+}
+
+impl ToolResult {
+    pub fn ok(data: serde_json::Value) -> Self {
+        Self {
+            success: true,
+            status: ToolStatus::Ok,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    pub fn err(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self::from_error(ToolError::new(code, message))
+    }
+
+    pub fn from_error(error: ToolError) -> Self {
+        let status = match error.code {
+            ErrorCode::Cancelled => ToolStatus::Cancelled,
+            _ => ToolStatus::Error,
+        };
+        Self {
+            success: false,
+            status,
+            data: None,
+            error: Some(error),
+        }
+    }
+
+    /// Failure from a headless_chrome call, classified by its error type
+    pub fn browser_err(
+        fallback: ErrorCode,
+        context: &str,
+        err: &(dyn std::error::Error + Send + Sync + 'static),
+    ) -> Self {
+        Self::from_error(ToolError::from_browser(fallback, context, err))
+    }
+
+    pub fn cancelled() -> Self {
+        Self::err(ErrorCode::Cancelled, "Cancelled")
+    }
+
+    pub fn timed_out() -> Self {
+        Self {
+            status: ToolStatus::TimedOut,
+            ..Self::err(ErrorCode::Timeout, "Deadline exceeded")
+        }
+    }
+}
+
+/// Cancellation flag and optional deadline for a single tool call
+#[derive(Debug, Default)]
+pub struct CallToken {
+    cancelled: AtomicBool,
+    deadline: Option<Instant>,
+}
+
+impl CallToken {
+    pub fn new(deadline: Option<Instant>) -> Self {
+        Self {
+            cancelled: AtomicBool::new(false),
+            deadline,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The result to bail out with if the call was cancelled or ran out of time
+    pub fn check(&self) -> Option<ToolResult> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            Some(ToolResult::cancelled())
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Some(ToolResult::timed_out())
+        } else {
+            None
+        }
+    }
+}
+
+/// Lets another thread abort the CDP call the agent is blocked on
+#[derive(Clone, Default)]
+pub struct Interrupter {
+    tab: Arc<Mutex<Option<Arc<Tab>>>>,
+}
+
+impl Interrupter {
+    fn set_tab(&self, tab: &Arc<Tab>) {
+        *self.tab.lock().unwrap_or_else(PoisonError::into_inner) = Some(tab.clone());
+    }
+
+    /// Stop any page load and, optionally, script execution in the tab the
+    /// agent last touched
+    pub fn interrupt(&self, terminate_js: bool) {
+        let tab = self
+            .tab
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(tab) = tab {
+            let _ = tab.stop_loading();
+            if terminate_js {
+                let _ = tab.call_method(Runtime::TerminateExecution(None));
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,82 +194,378 @@ pub struct PageInfo {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct Link {
-    pub text: String,
-    pub href: String,
+pub struct TabInfo {
+    pub tab_id: String,
+    pub url: String,
+    pub title: String,
+    pub loading: bool,
+    pub active: bool,
+}
+
+/// A CDP tab owned by the agent, addressed by a short ID like `t1`
+struct AgentTab {
+    id: String,
+    tab: Arc<Tab>,
+    network: network::NetworkLog,
+    console: console::ConsoleLog,
+    refs: snapshot::RefWorld,
+}
+
+impl AgentTab {
+    fn new(
+        id: String,
+        tab: Arc<Tab>,
+        interception: &intercept::Interception,
+        dialogs: &dialogs::DialogLog,
+    ) -> Self {
+        let network = network::NetworkLog::attach(&tab);
+        let console = console::ConsoleLog::attach(&tab);
+        let refs = snapshot::RefWorld::attach(&tab);
+        interception.attach(&tab);
+        dialogs.attach(&id, &tab);
+        Self {
+            id,
+            tab,
+            network,
+            console,
+            refs,
+        }
+    }
 }
 
 /// Browser automation agent using Chrome DevTools Protocol
 pub struct BrowserAgent {
     browser: Browser,
-    tab: Arc<Tab>,
+    tabs: Vec<AgentTab>,
+    active_tab: String,
+    next_tab_id: usize,
+    call: Arc<CallToken>,
+    /// When the current call began, in milliseconds since the epoch
+    call_started: f64,
+    interrupter: Interrupter,
+    interception: intercept::Interception,
+    dialogs: dialogs::DialogLog,
+    files: files::ManagedFiles,
+    /// Pages open at launch (Chrome's first blank tab), which aren't popups
+    ignored_targets: HashSet<String>,
 }
 
 impl BrowserAgent {
-    /// Create a new browser agent instance
-    pub fn new(headless: bool) -> Result<Self, String> {
+    /// Create a new browser agent instance. Without a `user_data_dir` the
+    /// browser gets a throwaway profile that is deleted when it closes.
+    pub fn new(headless: bool, user_data_dir: Option<PathBuf>) -> Result<Self, ToolError> {
         let options = LaunchOptions::default_builder()
             .headless(headless)
             .idle_browser_timeout(Duration::from_secs(300))
+            .user_data_dir(user_data_dir)
             .build()
-            .map_err(|e| format!("Failed to build launch options: {}", e))?;
+            .map_err(|e| {
+                ToolError::new(
+                    ErrorCode::LaunchFailed,
+                    format!("Failed to build launch options: {}", e),
+                )
+            })?;
+
+        let browser = Browser::new(options).map_err(|e| {
+            ToolError::from_browser(ErrorCode::LaunchFailed, "Failed to launch browser", &*e)
+        })?;
 
-        let browser = Browser::new(options)
-            .map_err(|e| format!("Failed to launch browser: {}", e))?;
+        let tab = browser.new_tab().map_err(|e| {
+            ToolError::from_browser(ErrorCode::LaunchFailed, "Failed to create tab", &*e)
+        })?;
 
-        let tab = browser
-            .new_tab()
-            .map_err(|e| format!("Failed to create tab: {}", e))?;
+        let ignored_targets = browser
+            .get_tabs()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|t| t.get_target_id().clone())
+            .filter(|id| id != tab.get_target_id())
+            .collect();
+
+        let interception = intercept::Interception::default();
+        let dialogs = dialogs::DialogLog::default();
+        Ok(Self {
+            browser,
+            tabs: vec![AgentTab::new(
+                "t1".to_string(),
+                tab,
+                &interception,
+                &dialogs,
+            )],
+            active_tab: "t1".to_string(),
+            next_tab_id: 2,
+            call: Arc::new(CallToken::default()),
+            call_started: console::now_ms(),
+            interrupter: Interrupter::default(),
+            interception,
+            dialogs,
+            files: files::ManagedFiles::new(),
+            ignored_targets,
+        })
+    }
+
+    /// Handle for aborting in-flight CDP calls from another thread
+    pub fn interrupter(&self) -> Interrupter {
+        self.interrupter.clone()
+    }
+
+    /// Set the cancellation token checked by the calls that follow
+    pub fn begin_call(&mut self, token: Arc<CallToken>) {
+        self.call = token;
+        self.call_started = console::now_ms();
+    }
+
+    /// Sleep, waking early (with the result to return) if the current call
+    /// is cancelled or reaches its deadline
+    fn pause(&self, duration: Duration) -> Option<ToolResult> {
+        let until = Instant::now() + duration;
+        loop {
+            if let Some(result) = self.call.check() {
+                return Some(result);
+            }
+            let now = Instant::now();
+            if now >= until {
+                return None;
+            }
+            std::thread::sleep(CANCEL_POLL_INTERVAL.min(until - now));
+        }
+    }
 
-        Ok(Self { browser, tab })
+    /// Look up a tab by ID, falling back to the active tab
+    fn get_tab(&self, tab_id: Option<&str>) -> Result<&Arc<Tab>, ToolResult> {
+        self.get_agent_tab(tab_id).map(|t| &t.tab)
     }
 
-    /// Navigate to a URL
-    pub fn navigate(&self, url: &str) -> ToolResult {
-        match self.tab.navigate_to(url) {
+    /// Like `get_tab`, but with the agent's per-tab state
+    fn get_agent_tab(&self, tab_id: Option<&str>) -> Result<&AgentTab, ToolResult> {
+        let id = tab_id.unwrap_or(&self.active_tab);
+        let tab = self.tabs.iter().find(|t| t.id == id).ok_or_else(|| {
+            ToolResult::err(ErrorCode::TabNotFound, format!("Tab not found: {}", id))
+        })?;
+        self.interrupter.set_tab(&tab.tab);
+        Ok(tab)
+    }
+
+    /// Open a new tab, optionally navigating it and making it the active tab
+    pub fn open_tab(&mut self, url: Option<&str>, activate: bool) -> ToolResult {
+        let tab = match self.browser.new_tab() {
+            Ok(tab) => tab,
+            Err(e) => {
+                return ToolResult::browser_err(ErrorCode::Internal, "Failed to create tab", &*e)
+            }
+        };
+
+        let id = format!("t{}", self.next_tab_id);
+        self.next_tab_id += 1;
+        self.tabs.push(AgentTab::new(
+            id.clone(),
+            tab,
+            &self.interception,
+            &self.dialogs,
+        ));
+
+        if let Some(url) = url {
+            let mut result = self.navigate(Some(&id), url, None);
+            if !result.success {
+                // Don't leave a tab open that the caller never got the ID of
+                if !self.close_tab(Some(&id)).success {
+                    match result.data.as_mut() {
+                        Some(serde_json::Value::Object(data)) => {
+                            data.insert("tab_id".to_string(), id.into());
+                        }
+                        _ => result.data = Some(serde_json::json!({ "tab_id": id })),
+                    }
+                }
+                return result;
+            }
+        }
+
+        if activate {
+            self.active_tab = id.clone();
+        }
+
+        ToolResult::ok(serde_json::json!({
+            "tab_id": id,
+            "url": url.unwrap_or("about:blank"),
+            "active": self.active_tab == id
+        }))
+    }
+
+    /// List all open tabs with their URL, title and loading state
+    pub fn list_tabs(&self) -> ToolResult {
+        let tabs: Vec<TabInfo> = self
+            .tabs
+            .iter()
+            .map(|t| {
+                let loading = t
+                    .tab
+                    .evaluate("document.readyState", false)
+                    .ok()
+                    .and_then(|r| r.value)
+                    .map(|v| v != "complete")
+                    .unwrap_or(true);
+
+                TabInfo {
+                    tab_id: t.id.clone(),
+                    url: t.tab.get_url(),
+                    title: t.tab.get_title().unwrap_or_default(),
+                    loading,
+                    active: t.id == self.active_tab,
+                }
+            })
+            .collect();
+
+        ToolResult::ok(serde_json::json!({
+            "tabs": tabs,
+            "active_tab": self.active_tab,
+            "count": tabs.len()
+        }))
+    }
+
+    /// Make another tab the target for tools called without a tab ID
+    pub fn switch_tab(&mut self, tab_id: &str) -> ToolResult {
+        let tab = match self.get_tab(Some(tab_id)) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let _ = tab.activate();
+        self.active_tab = tab_id.to_string();
+
+        ToolResult::ok(serde_json::json!({ "active_tab": tab_id }))
+    }
+
+    /// Close a tab (the active tab if no ID is given)
+    pub fn close_tab(&mut self, tab_id: Option<&str>) -> ToolResult {
+        let id = tab_id.unwrap_or(&self.active_tab).to_string();
+
+        let index = match self.tabs.iter().position(|t| t.id == id) {
+            Some(index) => index,
+            None => {
+                return ToolResult::err(ErrorCode::TabNotFound, format!("Tab not found: {}", id))
+            }
+        };
+
+        if self.tabs.len() == 1 {
+            return ToolResult::err(
+                ErrorCode::InvalidArgument,
+                "Cannot close the last tab. Call agent_stop instead.",
+            );
+        }
+
+        if let Err(e) = self.tabs[index].tab.close(false) {
+            return ToolResult::browser_err(ErrorCode::Internal, "Failed to close tab", &*e);
+        }
+        self.tabs.remove(index);
+        self.dialogs.detach(&id);
+
+        // Fall back to the most recently opened tab
+        if self.active_tab == id {
+            if let Some(last) = self.tabs.last() {
+                self.active_tab = last.id.clone();
+                let _ = last.tab.activate();
+            }
+        }
+
+        ToolResult::ok(serde_json::json!({
+            "closed": id,
+            "active_tab": self.active_tab
+        }))
+    }
+
+    /// Navigate to a URL, then wait for `after` if given
+    pub fn navigate(&self, tab_id: Option<&str>, url: &str, after: Option<&WaitFor>) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        match tab.navigate_to(url) {
             Ok(_) => {
-                // Wait for page to load
-                let _ = self.tab.wait_until_navigated();
-                ToolResult {
-                    success: true,
-                    data: Some(serde_json::json!({ "navigated_to": url })),
-                    error: None,
+                // Wait for page to load (cut short by stop_loading on cancel)
+                let _ = tab.wait_until_navigated();
+                if let Some(result) = self.settle(tab_id, after, Duration::ZERO) {
+                    return result;
                 }
+                ToolResult::ok(serde_json::json!({
+                    "navigated_to": url,
+                    "tab_id": tab_id.unwrap_or(&self.active_tab)
+                }))
+            }
+            Err(e) => {
+                ToolResult::browser_err(ErrorCode::NavigationFailed, "Navigation failed", &*e)
             }
-            Err(e) => ToolResult {
-                success: false,
-                data: None,
-                error: Some(format!("Navigation failed: {}", e)),
-            },
         }
     }
 
     /// Extract text content from the page
-    pub fn extract_text(&self, selector: Option<&str>, max_length: usize) -> ToolResult {
-        match self.tab.get_content() {
-            Ok(html) => {
+    pub fn extract_text(
+        &self,
+        tab_id: Option<&str>,
+        selector: Option<&str>,
+        mode: ExtractMode,
+        frame: Option<&str>,
+        include_frames: bool,
+        page: &PageRequest,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let content = if frame.is_some() || include_frames {
+            frames::frame_html(tab, frame, include_frames)
+        } else {
+            tab.get_content()
+                .map(|html| (html, tab.get_url()))
+                .map_err(|e| {
+                    ToolResult::browser_err(ErrorCode::Internal, "Failed to get page content", &*e)
+                })
+        };
+
+        match content {
+            Ok((html, url)) if mode == ExtractMode::Readable => {
+                let root = match selector.map(|sel_str| (sel_str, Selector::parse(sel_str))) {
+                    Some((_, Ok(sel))) => Some(sel),
+                    Some((sel_str, Err(_))) => {
+                        return ToolResult::err(
+                            ErrorCode::InvalidSelector,
+                            format!("Invalid selector: {}", sel_str),
+                        )
+                    }
+                    None => None,
+                };
+
+                let article = readable::extract_article(&html, Some(&url), root.as_ref());
+                match paging::paginate(&article.markdown, page) {
+                    Ok(chunk) => {
+                        ToolResult::ok(chunk.merge_into(serde_json::json!(article), "markdown"))
+                    }
+                    Err(e) => e,
+                }
+            }
+            Ok((html, _)) => {
                 let document = Html::parse_document(&html);
                 let text = if let Some(sel_str) = selector {
                     match Selector::parse(sel_str) {
                         Ok(sel) => document
                             .select(&sel)
-                            .map(|el| el.text().collect::<String>())
+                            .map(visible_text)
                             .collect::<Vec<_>>()
                             .join(" "),
                         Err(_) => {
-                            return ToolResult {
-                                success: false,
-                                data: None,
-                                error: Some(format!("Invalid selector: {}", sel_str)),
-                            }
+                            return ToolResult::err(
+                                ErrorCode::InvalidSelector,
+                                format!("Invalid selector: {}", sel_str),
+                            )
                         }
                     }
                 } else {
-                    // Remove script and style content, get body text
                     let body_sel = Selector::parse("body").unwrap();
                     document
                         .select(&body_sel)
-                        .map(|el| el.text().collect::<String>())
+                        .map(visible_text)
                         .collect::<Vec<_>>()
                         .join(" ")
                 };
@@ -115,241 +576,157 @@ impl BrowserAgent {
                     .collect::<Vec<_>>()
                     .join(" ");
 
-                let truncated = if cleaned.len() > max_length {
-                    format!("{}... [truncated]", &cleaned[..max_length])
-                } else {
-                    cleaned
-                };
-
-                ToolResult {
-                    success: true,
-                    data: Some(serde_json::json!({
-                        "text": truncated,
-                        "length": truncated.len()
-                    })),
-                    error: None,
+                match paging::paginate(&cleaned, page) {
+                    Ok(chunk) => ToolResult::ok(chunk.merge_into(serde_json::json!({}), "text")),
+                    Err(e) => e,
                 }
             }
-            Err(e) => ToolResult {
-                success: false,
-                data: None,
-                error: Some(format!("Failed to get page content: {}", e)),
-            },
+            Err(e) => e,
         }
     }
 
-    /// Extract links from the page
-    pub fn extract_links(&self, selector: Option<&str>, max_links: usize) -> ToolResult {
-        match self.tab.get_content() {
-            Ok(html) => {
-                let document = Html::parse_document(&html);
-                let link_sel = Selector::parse("a[href]").unwrap();
-
-                let base_url = self.tab.get_url();
+    /// Click an element on the page. Tabs the click opens become agent
+    /// tabs, listed as `popups`, and the first becomes the active tab if
+    /// `switch_to_popup` is set.
+    pub fn click(
+        &mut self,
+        tab_id: Option<&str>,
+        target: &Target,
+        after: Option<&WaitFor>,
+        switch_to_popup: bool,
+    ) -> ToolResult {
+        let before = self.page_targets();
+        let result = self.click_element(tab_id, target, after);
+        if !result.success {
+            return result;
+        }
+        self.adopt_popups(result, &before, switch_to_popup)
+    }
 
-                let container = if let Some(sel_str) = selector {
-                    match Selector::parse(sel_str) {
-                        Ok(sel) => document.select(&sel).next(),
-                        Err(_) => None,
-                    }
-                } else {
-                    document.select(&Selector::parse("body").unwrap()).next()
-                };
+    fn click_element(
+        &self,
+        tab_id: Option<&str>,
+        target: &Target,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let tab = &agent_tab.tab;
 
-                let links: Vec<Link> = if let Some(container_el) = container {
-                    container_el
-                        .select(&link_sel)
-                        .take(max_links)
-                        .filter_map(|el| {
-                            let href = el.value().attr("href")?;
-                            let text = el.text().collect::<String>().trim().to_string();
-
-                            // Resolve relative URLs
-                            let full_href = if href.starts_with("http") {
-                                href.to_string()
-                            } else if href.starts_with('/') {
-                                if let Ok(base) = url::Url::parse(&base_url) {
-                                    format!("{}://{}{}", base.scheme(), base.host_str().unwrap_or(""), href)
-                                } else {
-                                    href.to_string()
-                                }
-                            } else {
-                                href.to_string()
-                            };
-
-                            Some(Link {
-                                text,
-                                href: full_href,
-                            })
-                        })
-                        .collect()
-                } else {
-                    vec![]
-                };
+        let element = match target.resolve(tab, &agent_tab.refs) {
+            Ok(element) => element,
+            Err(e) => return e,
+        };
 
-                ToolResult {
-                    success: true,
-                    data: Some(serde_json::json!({
-                        "links": links,
-                        "count": links.len()
-                    })),
-                    error: None,
+        match element.click() {
+            Ok(_) => {
+                if let Some(result) = self.settle(tab_id, after, Duration::from_millis(500)) {
+                    return result;
                 }
+                ToolResult::ok(serde_json::json!({ "clicked": target.to_string() }))
+            }
+            Err(e) => {
+                ToolResult::browser_err(ErrorCode::ElementNotInteractable, "Click failed", &*e)
             }
-            Err(e) => ToolResult {
-                success: false,
-                data: None,
-                error: Some(format!("Failed to get page content: {}", e)),
-            },
         }
     }
 
-    /// Click an element on the page
-    pub fn click(&self, selector: Option<&str>, text: Option<&str>) -> ToolResult {
-        if let Some(sel) = selector {
-            match self.tab.find_element(sel) {
-                Ok(element) => match element.click() {
-                    Ok(_) => {
-                        std::thread::sleep(Duration::from_millis(500));
-                        ToolResult {
-                            success: true,
-                            data: Some(serde_json::json!({ "clicked": sel })),
-                            error: None,
-                        }
-                    }
-                    Err(e) => ToolResult {
-                        success: false,
-                        data: None,
-                        error: Some(format!("Click failed: {}", e)),
-                    },
-                },
-                Err(e) => ToolResult {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Element not found: {}", e)),
-                },
-            }
-        } else if let Some(txt) = text {
-            // Find element by text content using XPath
-            let xpath = format!("//*[contains(text(), '{}')]", txt);
-            match self.tab.find_element_by_xpath(&xpath) {
-                Ok(element) => match element.click() {
-                    Ok(_) => {
-                        std::thread::sleep(Duration::from_millis(500));
-                        ToolResult {
-                            success: true,
-                            data: Some(serde_json::json!({ "clicked_text": txt })),
-                            error: None,
-                        }
-                    }
-                    Err(e) => ToolResult {
-                        success: false,
-                        data: None,
-                        error: Some(format!("Click failed: {}", e)),
-                    },
-                },
-                Err(e) => ToolResult {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Element with text '{}' not found: {}", txt, e)),
-                },
+    /// Move the mouse over an element
+    pub fn hover(
+        &self,
+        tab_id: Option<&str>,
+        target: &Target,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let tab = &agent_tab.tab;
+
+        let element = match target.resolve(tab, &agent_tab.refs) {
+            Ok(element) => element,
+            Err(e) => return e,
+        };
+
+        match element.move_mouse_over() {
+            Ok(_) => {
+                // Give hover menus and tooltips a moment to appear
+                if let Some(result) = self.settle(tab_id, after, Duration::from_millis(300)) {
+                    return result;
+                }
+                ToolResult::ok(serde_json::json!({ "hovered": target.to_string() }))
             }
-        } else {
-            ToolResult {
-                success: false,
-                data: None,
-                error: Some("Must provide selector or text".to_string()),
+            Err(e) => {
+                ToolResult::browser_err(ErrorCode::ElementNotInteractable, "Hover failed", &*e)
             }
         }
     }
 
     /// Fill a form field
-    pub fn fill_form(&self, selector: &str, value: &str, submit: bool) -> ToolResult {
-        match self.tab.find_element(selector) {
-            Ok(element) => {
-                // Clear and type the value
-                if let Err(e) = element.click() {
-                    return ToolResult {
-                        success: false,
-                        data: None,
-                        error: Some(format!("Failed to focus input: {}", e)),
-                    };
-                }
-
-                if let Err(e) = element.type_into(value) {
-                    return ToolResult {
-                        success: false,
-                        data: None,
-                        error: Some(format!("Failed to type: {}", e)),
-                    };
-                }
+    pub fn fill_form(
+        &self,
+        tab_id: Option<&str>,
+        target: &Target,
+        value: &str,
+        submit: bool,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let tab = &agent_tab.tab;
 
-                if submit {
-                    // Press Enter to submit
-                    let _ = self.tab.press_key("Enter");
-                    std::thread::sleep(Duration::from_millis(1000));
-                }
+        let element = match target.resolve(tab, &agent_tab.refs) {
+            Ok(element) => element,
+            Err(e) => return e,
+        };
 
-                ToolResult {
-                    success: true,
-                    data: Some(serde_json::json!({
-                        "filled": selector,
-                        "value": value
-                    })),
-                    error: None,
-                }
-            }
-            Err(e) => ToolResult {
-                success: false,
-                data: None,
-                error: Some(format!("Input not found: {}", e)),
-            },
+        if let Err(e) = element.click() {
+            return ToolResult::browser_err(
+                ErrorCode::ElementNotInteractable,
+                "Failed to focus input",
+                &*e,
+            );
         }
-    }
 
-    /// Take a screenshot of the page
-    pub fn screenshot(&self, full_page: bool) -> ToolResult {
-        let screenshot_result = if full_page {
-            self.tab.capture_screenshot(
-                headless_chrome::protocol::cdp::Page::CaptureScreenshotFormatOption::Png,
-                None,
-                None,
-                true,
-            )
-        } else {
-            self.tab.capture_screenshot(
-                headless_chrome::protocol::cdp::Page::CaptureScreenshotFormatOption::Png,
-                None,
-                None,
-                false,
-            )
-        };
+        // Replace whatever is already in the field rather than appending
+        if let Err(e) = forms::fill_element(tab, &element, &value.into()) {
+            return e;
+        }
 
-        match screenshot_result {
-            Ok(data) => {
-                let base64_data = base64::Engine::encode(
-                    &base64::engine::general_purpose::STANDARD,
-                    &data,
-                );
-                ToolResult {
-                    success: true,
-                    data: Some(serde_json::json!({
-                        "screenshot": format!("data:image/png;base64,{}", base64_data),
-                        "size": data.len()
-                    })),
-                    error: None,
-                }
+        if submit {
+            // Press Enter to submit
+            let _ = tab.press_key("Enter");
+            if let Some(result) = self.settle(tab_id, after, Duration::from_millis(1000)) {
+                return result;
             }
-            Err(e) => ToolResult {
-                success: false,
-                data: None,
-                error: Some(format!("Screenshot failed: {}", e)),
-            },
+        } else if let Some(result) = self.settle(tab_id, after, Duration::ZERO) {
+            return result;
         }
+
+        ToolResult::ok(serde_json::json!({
+            "filled": target.to_string(),
+            "value": value
+        }))
     }
 
     /// Scroll the page
-    pub fn scroll(&self, direction: &str, amount: Option<i32>) -> ToolResult {
+    pub fn scroll(
+        &self,
+        tab_id: Option<&str>,
+        direction: &str,
+        amount: Option<i32>,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
         let scroll_amount = amount.unwrap_or(500);
 
         let js = match direction {
@@ -358,68 +735,39 @@ impl BrowserAgent {
             "top" => "window.scrollTo(0, 0)".to_string(),
             "bottom" => "window.scrollTo(0, document.body.scrollHeight)".to_string(),
             _ => {
-                return ToolResult {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Invalid direction: {}", direction)),
-                }
+                return ToolResult::err(
+                    ErrorCode::InvalidArgument,
+                    format!("Invalid direction: {}", direction),
+                )
             }
         };
 
-        match self.tab.evaluate(&js, false) {
+        match tab.evaluate(&js, false) {
             Ok(_) => {
-                std::thread::sleep(Duration::from_millis(300));
-                ToolResult {
-                    success: true,
-                    data: Some(serde_json::json!({ "scrolled": direction })),
-                    error: None,
+                if let Some(result) = self.settle(tab_id, after, Duration::from_millis(300)) {
+                    return result;
                 }
+                ToolResult::ok(serde_json::json!({ "scrolled": direction }))
             }
-            Err(e) => ToolResult {
-                success: false,
-                data: None,
-                error: Some(format!("Scroll failed: {}", e)),
-            },
-        }
-    }
-
-    /// Wait for an element or timeout
-    pub fn wait(&self, selector: Option<&str>, timeout_ms: u64) -> ToolResult {
-        if let Some(sel) = selector {
-            match self.tab.wait_for_element_with_custom_timeout(sel, Duration::from_millis(timeout_ms)) {
-                Ok(_) => ToolResult {
-                    success: true,
-                    data: Some(serde_json::json!({ "found": sel })),
-                    error: None,
-                },
-                Err(_) => ToolResult {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Timeout waiting for: {}", sel)),
-                },
-            }
-        } else {
-            std::thread::sleep(Duration::from_millis(timeout_ms));
-            ToolResult {
-                success: true,
-                data: Some(serde_json::json!({ "waited_ms": timeout_ms })),
-                error: None,
-            }
+            Err(e) => ToolResult::browser_err(ErrorCode::ScriptError, "Scroll failed", &*e),
         }
     }
 
     /// Get page information
-    pub fn get_page_info(&self) -> ToolResult {
-        let url = self.tab.get_url();
+    pub fn get_page_info(&self, tab_id: Option<&str>) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
 
-        let title = self
-            .tab
+        let url = tab.get_url();
+
+        let title = tab
             .evaluate("document.title", false)
             .map(|r| r.value.unwrap_or_default().to_string().replace('"', ""))
             .unwrap_or_else(|_| "Unknown".to_string());
 
-        let description = self
-            .tab
+        let description = tab
             .evaluate(
                 "document.querySelector('meta[name=\"description\"]')?.getAttribute('content') || ''",
                 false,
@@ -434,45 +782,185 @@ impl BrowserAgent {
             })
             .unwrap_or(None);
 
-        ToolResult {
-            success: true,
-            data: Some(serde_json::json!({
-                "url": url,
-                "title": title,
-                "description": description
-            })),
-            error: None,
-        }
+        ToolResult::ok(serde_json::json!({
+            "url": url,
+            "title": title,
+            "description": description
+        }))
     }
 
-    /// Execute arbitrary JavaScript
-    pub fn evaluate_js(&self, script: &str) -> ToolResult {
-        match self.tab.evaluate(script, false) {
-            Ok(result) => ToolResult {
-                success: true,
-                data: Some(serde_json::json!({
-                    "result": result.value
-                })),
-                error: None,
-            },
-            Err(e) => ToolResult {
-                success: false,
-                data: None,
-                error: Some(format!("JS evaluation failed: {}", e)),
-            },
+    /// Execute arbitrary JavaScript, in the top document or in `frame`
+    pub fn evaluate_js(
+        &self,
+        tab_id: Option<&str>,
+        script: &str,
+        frame: Option<&str>,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        if let Some(frame) = frame {
+            return match frames::eval_in_frame(tab, frame, script) {
+                Ok(result) => ToolResult::ok(serde_json::json!({ "result": result })),
+                Err(e) => self.call.check().unwrap_or(e),
+            };
+        }
+
+        match tab.evaluate(script, false) {
+            Ok(result) => ToolResult::ok(serde_json::json!({
+                "result": result.value
+            })),
+            Err(e) => self.call.check().unwrap_or_else(|| {
+                ToolResult::browser_err(ErrorCode::ScriptError, "JS evaluation failed", &*e)
+            }),
         }
     }
 
-    /// Close the browser
+    /// Close the browser and delete its managed files
     pub fn close(self) -> Result<(), String> {
-        drop(self.tab);
+        self.files.remove_all();
+        drop(self.tabs);
         drop(self.browser);
         Ok(())
     }
 }
 
+/// Evaluate an expression (awaiting it if it returns a promise) and return
+/// its result as JSON. Unlike `Tab::evaluate`, a thrown exception is an error.
+fn evaluate_value(tab: &Tab, expression: &str) -> Result<serde_json::Value, ToolResult> {
+    evaluate_in(tab, None, expression, true)
+        .map(|result| result.value.unwrap_or(serde_json::Value::Null))
+}
+
+/// Evaluate an expression in the JS world `context` (the page's own when
+/// None), awaiting it if it returns a promise. The result comes back as JSON
+/// or, without `return_by_value`, as a handle. A thrown exception is an
+/// error.
+fn evaluate_in(
+    tab: &Tab,
+    context: Option<Runtime::ExecutionContextId>,
+    expression: &str,
+    return_by_value: bool,
+) -> Result<Runtime::RemoteObject, ToolResult> {
+    let evaluated = tab
+        .call_method(Runtime::Evaluate {
+            expression: expression.to_string(),
+            return_by_value: Some(return_by_value),
+            generate_preview: None,
+            silent: Some(true),
+            await_promise: Some(true),
+            include_command_line_api: None,
+            user_gesture: None,
+            object_group: None,
+            context_id: context,
+            throw_on_side_effect: None,
+            timeout: None,
+            disable_breaks: None,
+            repl_mode: None,
+            allow_unsafe_eval_blocked_by_csp: None,
+            unique_context_id: None,
+            serialization_options: None,
+        })
+        .map_err(|e| ToolResult::browser_err(ErrorCode::ScriptError, "Evaluation failed", &*e))?;
+
+    if let Some(details) = evaluated.exception_details {
+        let message = details
+            .exception
+            .and_then(|exception| exception.description)
+            .unwrap_or(details.text);
+        return Err(ToolResult::err(
+            ErrorCode::ScriptError,
+            format!("Script threw: {}", message),
+        ));
+    }
+
+    Ok(evaluated.result)
+}
+
+/// Call `function` with the element as `this` and `arg` as its argument,
+/// returning its result as JSON. A thrown exception is an error.
+fn call_on_element(
+    element: &Element,
+    function: &str,
+    arg: serde_json::Value,
+) -> Result<serde_json::Value, ToolResult> {
+    call_function_on(
+        element.parent,
+        &element.remote_object_id,
+        function,
+        arg,
+        true,
+    )
+    .map(|result| result.value.unwrap_or(serde_json::Value::Null))
+}
+
+/// Call `function` with the page-side object `object_id` as `this` and
+/// `arg` as its argument, in that object's JS world. The result comes back
+/// as JSON or, without `return_by_value`, as a handle. A thrown exception
+/// is an error.
+fn call_function_on(
+    tab: &Tab,
+    object_id: &str,
+    function: &str,
+    arg: serde_json::Value,
+    return_by_value: bool,
+) -> Result<Runtime::RemoteObject, ToolResult> {
+    let called = tab
+        .call_method(Runtime::CallFunctionOn {
+            function_declaration: function.to_string(),
+            object_id: Some(object_id.to_string()),
+            arguments: Some(vec![Runtime::CallArgument {
+                value: Some(arg),
+                unserializable_value: None,
+                object_id: None,
+            }]),
+            silent: Some(true),
+            return_by_value: Some(return_by_value),
+            generate_preview: None,
+            user_gesture: Some(true),
+            await_promise: Some(true),
+            execution_context_id: None,
+            object_group: None,
+            throw_on_side_effect: None,
+            unique_context_id: None,
+            serialization_options: None,
+        })
+        .map_err(|e| ToolResult::browser_err(ErrorCode::ScriptError, "Evaluation failed", &*e))?;
+
+    if let Some(details) = called.exception_details {
+        let message = details
+            .exception
+            .and_then(|exception| exception.description)
+            .unwrap_or(details.text);
+        return Err(ToolResult::err(
+            ErrorCode::ScriptError,
+            format!("Script threw: {}", message),
+        ));
+    }
+
+    Ok(called.result)
+}
+
+/// The text inside `el`, leaving out script, style and other content that
+/// is never rendered as text
+fn visible_text(el: ElementRef) -> String {
+    el.descendants()
+        .filter_map(|node| node.value().as_text().map(|text| (node, text)))
+        .filter(|(node, _)| {
+            !node.ancestors().any(|ancestor| {
+                ancestor.value().as_element().is_some_and(|e| {
+                    matches!(e.name(), "script" | "style" | "noscript" | "template")
+                })
+            })
+        })
+        .map(|(_, text)| &**text)
+        .collect()
+}
+
 /// Simple HTTP-based scraping (no browser needed)
-pub async fn fetch_page(url: &str) -> ToolResult {
+pub async fn fetch_page(url: &str, mode: ExtractMode, page: &PageRequest) -> ToolResult {
     let client = reqwest::Client::builder()
         .user_agent("VybeR Agent/1.0")
         .timeout(Duration::from_secs(30))
@@ -481,25 +969,37 @@ pub async fn fetch_page(url: &str) -> ToolResult {
     let client = match client {
         Ok(c) => c,
         Err(e) => {
-            return ToolResult {
-                success: false,
-                data: None,
-                error: Some(format!("Failed to create HTTP client: {}", e)),
-            }
+            return ToolResult::err(
+                ErrorCode::Internal,
+                format!("Failed to create HTTP client: {}", e),
+            )
         }
     };
 
     match client.get(url).send().await {
         Ok(response) => {
             if !response.status().is_success() {
-                return ToolResult {
-                    success: false,
-                    data: None,
-                    error: Some(format!("HTTP error: {}", response.status())),
-                };
+                return ToolResult::err(
+                    ErrorCode::HttpStatus {
+                        status: response.status().as_u16(),
+                    },
+                    format!("HTTP error: {}", response.status()),
+                );
             }
 
+            // Resolve links against where redirects ended up
+            let final_url = response.url().to_string();
+
             match response.text().await {
+                Ok(html) if mode == ExtractMode::Readable => {
+                    let article = readable::extract_article(&html, Some(&final_url), None);
+                    let mut data = serde_json::json!(article);
+                    data["url"] = url.into();
+                    match paging::paginate(&article.markdown, page) {
+                        Ok(chunk) => ToolResult::ok(chunk.merge_into(data, "markdown")),
+                        Err(e) => e,
+                    }
+                }
                 Ok(html) => {
                     let document = Html::parse_document(&html);
 
@@ -515,35 +1015,35 @@ pub async fn fetch_page(url: &str) -> ToolResult {
                     let body_sel = Selector::parse("body").unwrap();
                     let text: String = document
                         .select(&body_sel)
-                        .map(|el| el.text().collect::<String>())
+                        .map(visible_text)
                         .collect::<Vec<_>>()
                         .join(" ")
                         .split_whitespace()
-                        .take(2000)
                         .collect::<Vec<_>>()
                         .join(" ");
 
-                    ToolResult {
-                        success: true,
-                        data: Some(serde_json::json!({
-                            "url": url,
-                            "title": title,
-                            "text": text
-                        })),
-                        error: None,
+                    let data = serde_json::json!({
+                        "url": url,
+                        "title": title
+                    });
+                    match paging::paginate(&text, page) {
+                        Ok(chunk) => ToolResult::ok(chunk.merge_into(data, "text")),
+                        Err(e) => e,
                     }
                 }
-                Err(e) => ToolResult {
-                    success: false,
-                    data: None,
-                    error: Some(format!("Failed to read response: {}", e)),
-                },
+                Err(e) => ToolResult::err(
+                    ErrorCode::RequestFailed,
+                    format!("Failed to read response: {}", e),
+                ),
             }
         }
-        Err(e) => ToolResult {
-            success: false,
-            data: None,
-            error: Some(format!("Request failed: {}", e)),
-        },
+        Err(e) => {
+            let code = if e.is_timeout() {
+                ErrorCode::Timeout
+            } else {
+                ErrorCode::RequestFailed
+            };
+            ToolResult::err(code, format!("Request failed: {}", e))
+        }
     }
 }
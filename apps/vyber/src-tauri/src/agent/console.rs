@@ -0,0 +1,320 @@
+/**
+ * VybeR Agent Console Capture
+ *
+ * Buffers each tab's console messages and uncaught exceptions from the CDP
+ * Runtime domain, plus the browser's own messages (failed loads, CSP
+ * violations, deprecations) from the Log domain. The get_console tool reads
+ * the buffer, and the warnings and errors logged while a call ran are
+ * attached to its result (as `data.console`) when it fails, so silent page
+ * failures show up.
+ */
+
+use super::{BrowserAgent, ToolResult, ToolStatus};
+use headless_chrome::protocol::cdp::types::Event;
+use headless_chrome::protocol::cdp::Log::LogEntryLevel;
+use headless_chrome::protocol::cdp::Runtime::{
+    self, ConsoleAPICalledEventTypeOption as ConsoleType,
+};
+use headless_chrome::Tab;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Messages kept per tab before the oldest are dropped
+const MAX_ENTRIES: usize = 500;
+
+/// Stack frames kept per message
+const MAX_STACK_FRAMES: usize = 10;
+
+/// Most messages attached to a failed call's error
+const MAX_ATTACHED: usize = 20;
+
+/// Message severity, ordered so a filter can take a minimum level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleLevel {
+    Debug,
+    Info,
+    #[serde(alias = "warn")]
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsoleEntry {
+    pub level: ConsoleLevel,
+    /// `console`, `exception`, or where a browser message came from, such
+    /// as `network`, `security` or `violation`
+    pub source: String,
+    pub text: String,
+    /// Milliseconds since the epoch
+    pub timestamp: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 1-based, like in DevTools
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    /// Call frames as `function (url:line:column)`, innermost first.
+    /// Only kept for warnings and errors.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stack: Vec<String>,
+}
+
+impl ConsoleEntry {
+    fn new(level: ConsoleLevel, source: &str, text: String, timestamp: f64) -> Self {
+        Self {
+            level,
+            source: source.to_string(),
+            text,
+            timestamp,
+            url: None,
+            line: None,
+            column: None,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Take the location from the top frame and, for warnings and errors,
+    /// keep the frames
+    fn with_stack(mut self, stack: Option<&Runtime::StackTrace>) -> Self {
+        let Some(stack) = stack else {
+            return self;
+        };
+        if let Some(top) = stack.call_frames.first() {
+            self.url = Some(top.url.clone()).filter(|url| !url.is_empty());
+            self.line = Some(top.line_number + 1);
+            self.column = Some(top.column_number + 1);
+        }
+        if self.level >= ConsoleLevel::Warning {
+            self.stack = stack
+                .call_frames
+                .iter()
+                .take(MAX_STACK_FRAMES)
+                .map(|frame| {
+                    format!(
+                        "{} ({}:{}:{})",
+                        if frame.function_name.is_empty() {
+                            "<anonymous>"
+                        } else {
+                            &frame.function_name
+                        },
+                        frame.url,
+                        frame.line_number + 1,
+                        frame.column_number + 1
+                    )
+                })
+                .collect();
+        }
+        self
+    }
+}
+
+/// Turn a CDP event into a buffered message, if it is one
+fn entry_for(event: &Event) -> Option<ConsoleEntry> {
+    match event {
+        Event::RuntimeConsoleAPICalled(event) => {
+            let params = &event.params;
+            let level = match params.Type {
+                ConsoleType::Debug => ConsoleLevel::Debug,
+                ConsoleType::Warning => ConsoleLevel::Warning,
+                ConsoleType::Error | ConsoleType::Assert => ConsoleLevel::Error,
+                ConsoleType::Clear
+                | ConsoleType::StartGroup
+                | ConsoleType::StartGroupCollapsed
+                | ConsoleType::EndGroup
+                    if params.args.is_empty() =>
+                {
+                    return None
+                }
+                _ => ConsoleLevel::Info,
+            };
+            let text = params
+                .args
+                .iter()
+                .map(describe)
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(
+                ConsoleEntry::new(level, "console", text, params.timestamp)
+                    .with_stack(params.stack_trace.as_ref()),
+            )
+        }
+        Event::RuntimeExceptionThrown(event) => {
+            let details = &event.params.exception_details;
+            // The description is the message followed by the stack, which
+            // is kept separately
+            let text = details
+                .exception
+                .as_ref()
+                .and_then(|exception| exception.description.as_deref())
+                .and_then(|description| description.lines().next())
+                .unwrap_or(&details.text)
+                .to_string();
+            let mut entry = ConsoleEntry::new(
+                ConsoleLevel::Error,
+                "exception",
+                text,
+                event.params.timestamp,
+            )
+            .with_stack(details.stack_trace.as_ref());
+            if details.stack_trace.is_none() {
+                entry.url = details.url.clone();
+                entry.line = Some(details.line_number + 1);
+                entry.column = Some(details.column_number + 1);
+            }
+            Some(entry)
+        }
+        Event::LogEntryAdded(event) => {
+            let log = &event.params.entry;
+            let level = match log.level {
+                LogEntryLevel::Verbose => ConsoleLevel::Debug,
+                LogEntryLevel::Info => ConsoleLevel::Info,
+                LogEntryLevel::Warning => ConsoleLevel::Warning,
+                LogEntryLevel::Error => ConsoleLevel::Error,
+            };
+            let source = serde_json::to_value(&log.source)
+                .ok()
+                .and_then(|source| source.as_str().map(str::to_string))
+                .unwrap_or_else(|| "other".to_string());
+            let mut entry = ConsoleEntry::new(level, &source, log.text.clone(), log.timestamp)
+                .with_stack(log.stack_trace.as_ref());
+            if entry.url.is_none() {
+                entry.url = log.url.clone();
+                entry.line = log.line_number;
+            }
+            Some(entry)
+        }
+        _ => None,
+    }
+}
+
+/// Render a console argument the way DevTools would show it collapsed
+fn describe(arg: &Runtime::RemoteObject) -> String {
+    match (&arg.value, &arg.unserializable_value, &arg.description) {
+        (Some(serde_json::Value::String(text)), _, _) => text.clone(),
+        (Some(value), _, _) => value.to_string(),
+        (None, Some(value), _) => value.clone(),
+        (None, None, Some(description)) => description.clone(),
+        (None, None, None) => serde_json::to_value(&arg.Type)
+            .ok()
+            .and_then(|kind| kind.as_str().map(str::to_string))
+            .unwrap_or_default(),
+    }
+}
+
+/// Milliseconds since the epoch, comparable with message timestamps
+pub fn now_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64() * 1000.0)
+}
+
+/// A tab's console buffer, shared with the CDP event listener that fills it
+#[derive(Clone)]
+pub struct ConsoleLog(Arc<Mutex<VecDeque<ConsoleEntry>>>);
+
+impl ConsoleLog {
+    /// Enable the Runtime and Log domains on `tab` and start buffering
+    pub fn attach(tab: &Tab) -> Self {
+        let log = Self(Arc::new(Mutex::new(VecDeque::new())));
+
+        let listener = log.clone();
+        let _ = tab.add_event_listener(Arc::new(move |event: &Event| {
+            if let Some(entry) = entry_for(event) {
+                let mut entries = listener.lock();
+                if entries.len() >= MAX_ENTRIES {
+                    entries.pop_front();
+                }
+                entries.push_back(entry);
+            }
+        }));
+        let _ = tab.enable_runtime();
+        let _ = tab.enable_log();
+
+        log
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<ConsoleEntry>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Messages at `level` or above, logged at or after `since`
+    fn matching(&self, level: Option<ConsoleLevel>, since: Option<f64>) -> Vec<ConsoleEntry> {
+        self.lock()
+            .iter()
+            .filter(|e| level.is_none_or(|level| e.level >= level))
+            .filter(|e| since.is_none_or(|since| e.timestamp >= since))
+            .cloned()
+            .collect()
+    }
+}
+
+impl BrowserAgent {
+    /// List a tab's buffered console messages, most recent last
+    pub fn get_console(
+        &self,
+        tab_id: Option<&str>,
+        level: Option<ConsoleLevel>,
+        since: Option<f64>,
+        limit: usize,
+        clear: bool,
+    ) -> ToolResult {
+        let tab = match self.get_agent_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let matching = tab.console.matching(level, since);
+        let skip = matching.len().saturating_sub(limit);
+        if clear {
+            tab.console.lock().clear();
+        }
+
+        ToolResult::ok(serde_json::json!({
+            "tab_id": tab.id,
+            "messages": &matching[skip..],
+            "count": matching.len() - skip,
+            "total": matching.len(),
+            "cleared": clear
+        }))
+    }
+
+    /// If the current call failed, attach the warnings and errors logged
+    /// while it ran to its result
+    pub fn end_call(&self, mut result: ToolResult) -> ToolResult {
+        if result.status != ToolStatus::Error || result.data.is_some() {
+            return result;
+        }
+
+        let mut messages: Vec<(f64, serde_json::Value)> = self
+            .tabs
+            .iter()
+            .flat_map(|tab| {
+                tab.console
+                    .matching(Some(ConsoleLevel::Warning), Some(self.call_started))
+                    .into_iter()
+                    .map(move |entry| {
+                        let timestamp = entry.timestamp;
+                        let mut message = serde_json::json!(entry);
+                        message["tab_id"] = tab.id.clone().into();
+                        (timestamp, message)
+                    })
+            })
+            .collect();
+        if messages.is_empty() {
+            return result;
+        }
+        messages.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let skip = messages.len().saturating_sub(MAX_ATTACHED);
+        let console: Vec<serde_json::Value> = messages
+            .into_iter()
+            .skip(skip)
+            .map(|(_, message)| message)
+            .collect();
+        result.data = Some(serde_json::json!({ "console": console }));
+        result
+    }
+}
@@ -0,0 +1,369 @@
+/**
+ * VybeR Agent Dialogs
+ *
+ * An `alert()`, `confirm()`, `prompt()` or `beforeunload` dialog blocks its
+ * page, and with it whatever CDP call the agent is making there. Each
+ * session has a dialog policy that answers dialogs as they open, or leaves
+ * them open for the user. Every dialog is recorded, and one left open is
+ * answered with handle_dialog. Answers go straight to the tab's CDP
+ * session, so they work while the worker is stuck behind the dialog.
+ */
+
+use super::console::now_ms;
+use super::{BrowserAgent, ErrorCode, ToolResult};
+use headless_chrome::browser::tab::dialog::Dialog;
+use headless_chrome::protocol::cdp::types::Event;
+use headless_chrome::Tab;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::sync::watch;
+
+/// Dialogs kept in the record before the oldest are dropped
+const MAX_RECORDS: usize = 100;
+
+/// What to do with a dialog when it opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialogAction {
+    /// Press OK, or leave the page for `beforeunload`
+    Accept,
+    /// Press Cancel, or stay on the page for `beforeunload`
+    Dismiss,
+    /// Leave the dialog open for the user to answer with handle_dialog
+    Surface,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogPolicy {
+    pub action: DialogAction,
+    /// Text to enter into prompts that are accepted
+    pub prompt_text: Option<String>,
+    /// Action for `beforeunload` dialogs, when it differs from `action`
+    pub beforeunload: Option<DialogAction>,
+}
+
+impl Default for DialogPolicy {
+    /// Dismiss dialogs, but let navigations leave pages that ask to stay
+    fn default() -> Self {
+        Self {
+            action: DialogAction::Dismiss,
+            prompt_text: None,
+            beforeunload: Some(DialogAction::Accept),
+        }
+    }
+}
+
+impl DialogPolicy {
+    fn action_for(&self, kind: &str) -> DialogAction {
+        match self.beforeunload {
+            Some(action) if kind == "beforeunload" => action,
+            _ => self.action,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialogStatus {
+    Open,
+    Accepted,
+    Dismissed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandledBy {
+    Policy,
+    Caller,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DialogRecord {
+    /// `d1`, `d2`, ... in the order the dialogs opened
+    pub id: String,
+    pub tab_id: String,
+    /// `alert`, `confirm`, `prompt` or `beforeunload`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_prompt: Option<String>,
+    /// The page that opened the dialog
+    pub url: String,
+    /// Milliseconds since the epoch
+    pub opened_at: f64,
+    pub status: DialogStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handled_by: Option<HandledBy>,
+    /// What was entered into an accepted prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_text: Option<String>,
+}
+
+/// An answer to an open dialog, as given in tool input
+#[derive(Debug, Deserialize)]
+pub struct DialogAnswer {
+    /// Tab the dialog is open in, or None for the most recent open dialog
+    pub tab_id: Option<String>,
+    pub accept: bool,
+    pub prompt_text: Option<String>,
+}
+
+#[derive(Default)]
+struct Dialogs {
+    policy: DialogPolicy,
+    records: VecDeque<DialogRecord>,
+    next_id: usize,
+    /// Each tab's handle for answering its dialogs
+    handles: HashMap<String, Arc<Dialog>>,
+}
+
+impl Dialogs {
+    /// The most recent open dialog, in `tab_id` if given
+    fn open(&mut self, tab_id: Option<&str>) -> Option<&mut DialogRecord> {
+        self.records.iter_mut().rev().find(|record| {
+            record.status == DialogStatus::Open && tab_id.is_none_or(|id| record.tab_id == id)
+        })
+    }
+
+    fn open_count(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|record| record.status == DialogStatus::Open)
+            .count()
+    }
+}
+
+/// The session's dialog policy and record, shared with each tab's listener
+#[derive(Clone)]
+pub struct DialogLog {
+    dialogs: Arc<Mutex<Dialogs>>,
+    /// How many dialogs are open, for callers waiting on a blocked call
+    open: Arc<watch::Sender<usize>>,
+}
+
+impl Default for DialogLog {
+    fn default() -> Self {
+        Self {
+            dialogs: Arc::default(),
+            open: Arc::new(watch::channel(0).0),
+        }
+    }
+}
+
+impl DialogLog {
+    fn lock(&self) -> MutexGuard<'_, Dialogs> {
+        self.dialogs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Apply the policy to dialogs opened in `tab`, agent tab `tab_id`
+    pub fn attach(&self, tab_id: &str, tab: &Tab) {
+        let handle = Arc::new(tab.get_dialog());
+        self.lock()
+            .handles
+            .insert(tab_id.to_string(), handle.clone());
+
+        let log = self.clone();
+        let tab_id = tab_id.to_string();
+        let _ = tab.add_event_listener(Arc::new(move |event: &Event| match event {
+            Event::PageJavascriptDialogOpening(event) => {
+                log.opened(&tab_id, &handle, &event.params)
+            }
+            Event::PageJavascriptDialogClosed(event) => {
+                log.closed(&tab_id, event.params.result, &event.params.user_input)
+            }
+            _ => {}
+        }));
+    }
+
+    /// Stop tracking a closed tab
+    pub fn detach(&self, tab_id: &str) {
+        let mut dialogs = self.lock();
+        dialogs.handles.remove(tab_id);
+        for record in dialogs.records.iter_mut() {
+            if record.tab_id == tab_id && record.status == DialogStatus::Open {
+                record.status = DialogStatus::Dismissed;
+            }
+        }
+        let open = dialogs.open_count();
+        drop(dialogs);
+        self.open.send_replace(open);
+    }
+
+    fn opened(
+        &self,
+        tab_id: &str,
+        handle: &Dialog,
+        params: &headless_chrome::protocol::cdp::Page::events::JavascriptDialogOpeningEventParams,
+    ) {
+        let kind = serde_json::to_value(&params.Type)
+            .ok()
+            .and_then(|kind| kind.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        let mut dialogs = self.lock();
+        let action = dialogs.policy.action_for(&kind);
+        let prompt_text = (action == DialogAction::Accept && kind == "prompt")
+            .then(|| {
+                dialogs
+                    .policy
+                    .prompt_text
+                    .clone()
+                    .or_else(|| params.default_prompt.clone())
+            })
+            .flatten();
+
+        dialogs.next_id += 1;
+        let record = DialogRecord {
+            id: format!("d{}", dialogs.next_id),
+            tab_id: tab_id.to_string(),
+            kind,
+            message: params.message.clone(),
+            default_prompt: params.default_prompt.clone().filter(|p| !p.is_empty()),
+            url: params.url.clone(),
+            opened_at: now_ms(),
+            status: match action {
+                DialogAction::Accept => DialogStatus::Accepted,
+                DialogAction::Dismiss => DialogStatus::Dismissed,
+                DialogAction::Surface => DialogStatus::Open,
+            },
+            handled_by: (action != DialogAction::Surface).then_some(HandledBy::Policy),
+            prompt_text: prompt_text.clone(),
+        };
+        if dialogs.records.len() >= MAX_RECORDS {
+            dialogs.records.pop_front();
+        }
+        dialogs.records.push_back(record);
+        let open = dialogs.open_count();
+        drop(dialogs);
+
+        // Listeners run on the tab's event thread, which is free to make
+        // CDP calls
+        let _ = match action {
+            DialogAction::Accept => handle.accept(prompt_text),
+            DialogAction::Dismiss => handle.dismiss(),
+            DialogAction::Surface => Ok(()),
+        };
+        self.open.send_replace(open);
+    }
+
+    /// Settle the record of a dialog that closed, however it was answered
+    fn closed(&self, tab_id: &str, accepted: bool, user_input: &str) {
+        let mut dialogs = self.lock();
+        if let Some(record) = dialogs.open(Some(tab_id)) {
+            record.status = if accepted {
+                DialogStatus::Accepted
+            } else {
+                DialogStatus::Dismissed
+            };
+            if accepted && record.kind == "prompt" {
+                record.prompt_text = Some(user_input.to_string());
+            }
+        }
+        let open = dialogs.open_count();
+        drop(dialogs);
+        self.open.send_replace(open);
+    }
+
+    /// Watch how many dialogs are open
+    pub fn subscribe(&self) -> watch::Receiver<usize> {
+        self.open.subscribe()
+    }
+
+    /// The error for a call cut short by an open dialog
+    pub fn open_error(&self) -> ToolResult {
+        let mut dialogs = self.lock();
+        let Some(record) = dialogs.open(None) else {
+            return ToolResult::err(ErrorCode::Internal, "Dialog closed before it was reported");
+        };
+        let mut result = ToolResult::err(
+            ErrorCode::DialogOpen,
+            format!(
+                "A {} dialog is open in tab {}: \"{}\". Answer it with handle_dialog.",
+                record.kind, record.tab_id, record.message
+            ),
+        );
+        result.data = Some(serde_json::json!({ "dialog": record }));
+        result
+    }
+
+    /// Accept or dismiss an open dialog
+    pub fn answer(&self, answer: &DialogAnswer) -> ToolResult {
+        let mut dialogs = self.lock();
+        let Some(record) = dialogs.open(answer.tab_id.as_deref()) else {
+            return ToolResult::err(
+                ErrorCode::InvalidArgument,
+                match &answer.tab_id {
+                    Some(tab_id) => format!("No dialog is open in tab {}", tab_id),
+                    None => "No dialog is open".to_string(),
+                },
+            );
+        };
+        record.status = if answer.accept {
+            DialogStatus::Accepted
+        } else {
+            DialogStatus::Dismissed
+        };
+        record.handled_by = Some(HandledBy::Caller);
+        if answer.accept && record.kind == "prompt" {
+            record.prompt_text = answer.prompt_text.clone();
+        }
+        let answered = record.clone();
+        let handle = dialogs.handles.get(&answered.tab_id).cloned();
+        let open = dialogs.open_count();
+        drop(dialogs);
+        self.open.send_replace(open);
+
+        let sent = match handle {
+            Some(handle) if answer.accept => handle.accept(answered.prompt_text.clone()),
+            Some(handle) => handle.dismiss(),
+            None => {
+                return ToolResult::err(
+                    ErrorCode::TabNotFound,
+                    format!("Tab not found: {}", answered.tab_id),
+                )
+            }
+        };
+        match sent {
+            Ok(()) => ToolResult::ok(serde_json::json!({ "dialog": answered })),
+            Err(e) => ToolResult::browser_err(ErrorCode::Internal, "Failed to answer dialog", &*e),
+        }
+    }
+}
+
+impl BrowserAgent {
+    /// Set how dialogs are answered from now on
+    pub fn set_dialog_policy(&self, policy: DialogPolicy) -> ToolResult {
+        let mut dialogs = self.dialogs.lock();
+        dialogs.policy = policy;
+        ToolResult::ok(serde_json::json!({ "policy": dialogs.policy }))
+    }
+
+    /// List the dialogs pages have opened, most recent last
+    pub fn get_dialogs(&self, tab_id: Option<&str>, open_only: bool) -> ToolResult {
+        let dialogs = self.dialogs.lock();
+        let records: Vec<&DialogRecord> = dialogs
+            .records
+            .iter()
+            .filter(|record| tab_id.is_none_or(|id| record.tab_id == id))
+            .filter(|record| !open_only || record.status == DialogStatus::Open)
+            .collect();
+        ToolResult::ok(serde_json::json!({
+            "dialogs": records,
+            "count": records.len(),
+            "policy": dialogs.policy
+        }))
+    }
+
+    /// Accept or dismiss an open dialog
+    pub fn handle_dialog(&self, answer: &DialogAnswer) -> ToolResult {
+        self.dialogs.answer(answer)
+    }
+
+    /// A handle for answering dialogs from another thread, since an open
+    /// dialog can leave the worker blocked
+    pub fn dialogs(&self) -> DialogLog {
+        self.dialogs.clone()
+    }
+}
@@ -0,0 +1,120 @@
+/**
+ * VybeR Agent Errors
+ *
+ * Machine-readable error codes for tool results, so callers can decide
+ * whether to retry without string-matching error messages.
+ */
+
+use headless_chrome::browser::tab::element::NoQuadFound;
+use headless_chrome::browser::tab::{ElementNotVisible, NavigationFailed, NoElementFound};
+use headless_chrome::browser::ConnectionClosed;
+use headless_chrome::util::Timeout;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// Why a tool call failed. Serializes as `{ "code": "element_not_found" }`,
+/// with extra fields for variants that carry data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// No element matched the selector, text or ref
+    ElementNotFound,
+    /// The element exists but could not be clicked, focused or typed into
+    ElementNotInteractable,
+    InvalidSelector,
+    /// A tool argument was missing or malformed
+    InvalidArgument,
+    NavigationFailed,
+    /// A wait or the call's own deadline ran out
+    Timeout,
+    Cancelled,
+    /// The session does not exist or has already stopped
+    AgentNotStarted,
+    TabNotFound,
+    /// No frame matched the frame ID, name or URL, or it can't be reached
+    FrameNotFound,
+    /// The session's job queue is full
+    Busy,
+    /// Chrome could not be launched
+    LaunchFailed,
+    /// The connection to Chrome was lost
+    BrowserCrashed,
+    /// An HTTP request completed with a non-success status
+    HttpStatus {
+        status: u16,
+    },
+    /// An HTTP request failed before a response arrived
+    RequestFailed,
+    /// Page JavaScript threw or could not be evaluated
+    ScriptError,
+    /// A JavaScript dialog is waiting for an answer (see handle_dialog)
+    DialogOpen,
+    Internal,
+}
+
+impl ErrorCode {
+    /// Whether repeating the same call might succeed
+    pub fn retryable(&self) -> bool {
+        match self {
+            Self::ElementNotFound
+            | Self::ElementNotInteractable
+            | Self::NavigationFailed
+            | Self::FrameNotFound
+            | Self::Timeout
+            | Self::Busy
+            | Self::RequestFailed => true,
+            Self::HttpStatus { status } => *status >= 500 || *status == 408 || *status == 429,
+            Self::InvalidSelector
+            | Self::InvalidArgument
+            | Self::Cancelled
+            | Self::AgentNotStarted
+            | Self::TabNotFound
+            | Self::LaunchFailed
+            | Self::BrowserCrashed
+            | Self::ScriptError
+            | Self::DialogOpen
+            | Self::Internal => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolError {
+    #[serde(flatten)]
+    pub code: ErrorCode,
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl ToolError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            retryable: code.retryable(),
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Classify an error returned by headless_chrome, using `fallback` when
+    /// the error type says nothing more specific
+    pub fn from_browser(
+        fallback: ErrorCode,
+        context: &str,
+        err: &(dyn Error + Send + Sync + 'static),
+    ) -> Self {
+        let code = if err.is::<ConnectionClosed>() {
+            ErrorCode::BrowserCrashed
+        } else if err.is::<NoElementFound>() {
+            ErrorCode::ElementNotFound
+        } else if err.is::<ElementNotVisible>() || err.is::<NoQuadFound>() {
+            ErrorCode::ElementNotInteractable
+        } else if err.is::<NavigationFailed>() {
+            ErrorCode::NavigationFailed
+        } else if err.is::<Timeout>() {
+            ErrorCode::Timeout
+        } else {
+            fallback
+        };
+        Self::new(code, format!("{}: {}", context, err))
+    }
+}
@@ -0,0 +1,147 @@
+/**
+ * VybeR Agent Output Files
+ *
+ * Screenshots and other binary results can run to megabytes, which is a lot
+ * to push over IPC as base64. Tools that produce them can instead write a
+ * managed file in the agent's temp directory, handed back by ID (`f1`,
+ * `f2`, ...) and path. Tools never write anywhere else, so a page can't
+ * talk the agent into overwriting the user's files. Managed files are
+ * deleted when the agent closes.
+ */
+
+use super::{BrowserAgent, ErrorCode, ToolResult};
+use base64::Engine;
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where a tool puts the file it produces
+#[derive(Debug, Clone, Default)]
+pub enum Output {
+    /// In the result, as a base64 data URL
+    #[default]
+    Inline,
+    /// In a managed file
+    Managed,
+}
+
+impl Output {
+    /// From the `save` tool input field
+    pub fn new(save: bool) -> Self {
+        if save {
+            Self::Managed
+        } else {
+            Self::Inline
+        }
+    }
+}
+
+/// The agent's directory of managed files, created on first use
+pub(super) struct ManagedFiles {
+    dir: PathBuf,
+    next_id: AtomicUsize,
+}
+
+impl ManagedFiles {
+    pub(super) fn new() -> Self {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        Self {
+            dir: std::env::temp_dir().join(format!(
+                "vyber-agent-{}-{}",
+                std::process::id(),
+                started
+            )),
+            next_id: AtomicUsize::new(1),
+        }
+    }
+
+    /// Write `bytes` to a new managed file, returning its ID and path
+    pub(super) fn write(
+        &self,
+        bytes: &[u8],
+        extension: &str,
+    ) -> std::io::Result<(String, PathBuf)> {
+        std::fs::create_dir_all(&self.dir)?;
+        let id = format!("f{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let path = self.dir.join(format!("{}.{}", id, extension));
+        std::fs::write(&path, bytes)?;
+        Ok((id, path))
+    }
+
+    /// Read back the managed file with ID `id`
+    pub(super) fn read(&self, id: &str) -> Result<Vec<u8>, ToolResult> {
+        let not_found = || {
+            ToolResult::err(
+                ErrorCode::InvalidArgument,
+                format!("Managed file not found: {}", id),
+            )
+        };
+        // IDs are only ever `f` and a number, so nothing outside the
+        // directory can be named
+        if !id
+            .strip_prefix('f')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return Err(not_found());
+        }
+
+        let path = std::fs::read_dir(&self.dir)
+            .map_err(|_| not_found())?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .find(|path| path.file_stem().is_some_and(|stem| stem == id))
+            .ok_or_else(not_found)?;
+        std::fs::read(&path).map_err(|e| {
+            ToolResult::err(
+                ErrorCode::Internal,
+                format!("Failed to read managed file {}: {}", id, e),
+            )
+        })
+    }
+
+    /// Delete every managed file
+    pub(super) fn remove_all(&self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+impl BrowserAgent {
+    /// Hand `bytes` back the way `output` asks: as a data URL under `key`,
+    /// or as the ID and path of the file it was written to
+    pub(super) fn deliver(
+        &self,
+        output: &Output,
+        key: &str,
+        bytes: &[u8],
+        mime: &str,
+        extension: &str,
+    ) -> Result<Map<String, Value>, ToolResult> {
+        let mut result = Map::new();
+        let failed = |e: std::io::Error| {
+            ToolResult::err(
+                ErrorCode::Internal,
+                format!("Failed to write {} file: {}", key, e),
+            )
+        };
+
+        match output {
+            Output::Inline => {
+                let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+                result.insert(
+                    key.to_string(),
+                    format!("data:{};base64,{}", mime, encoded).into(),
+                );
+            }
+            Output::Managed => {
+                let (id, path) = self.files.write(bytes, extension).map_err(failed)?;
+                result.insert("file_id".to_string(), id.into());
+                result.insert("path".to_string(), path.display().to_string().into());
+            }
+        }
+        result.insert("size".to_string(), bytes.len().into());
+        Ok(result)
+    }
+}
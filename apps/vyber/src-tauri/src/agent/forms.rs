@@ -0,0 +1,594 @@
+/**
+ * VybeR Agent Form Filling
+ *
+ * Fills several fields in one call. Each field is found by name or id,
+ * label text, placeholder or CSS selector, and filled according to its
+ * kind: text-like fields are cleared and then typed into, so pre-filled
+ * values are replaced rather than appended to, while selects, checkboxes,
+ * radios and date-like inputs are set directly. The result reports each
+ * field's outcome and the validation messages the page shows for it.
+ *
+ * `list_forms` describes the page's forms first, with selectors that
+ * fill_fields and the element tools accept.
+ */
+
+use super::target::element_for_object;
+use super::{call_on_element, evaluate_value, BrowserAgent, ErrorCode, ToolResult, WaitFor};
+use headless_chrome::browser::tab::element::Element;
+use headless_chrome::Tab;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Prefixes that pin a field key to one way of finding the field
+const KEY_PREFIXES: &[&str] = &["name", "label", "placeholder", "selector"];
+
+/// Called with `{ form, key, by }`. Returns the field element or null. Without
+/// `by`, tries name/id, then label, then placeholder, then CSS selector.
+const LOCATE_JS: &str = r#"
+(options) => {
+  let scope = document;
+  if (options.form) {
+    try {
+      scope = document.querySelector(options.form);
+    } catch (e) {
+      return null;
+    }
+    if (!scope) return null;
+  }
+
+  const clean = (s) => (s || "").replace(/\s+/g, " ").trim().toLowerCase();
+  const fields = Array.from(scope.querySelectorAll(
+    "input:not([type=hidden]), textarea, select, [contenteditable]:not([contenteditable=false]), " +
+    "[role=textbox], [role=combobox], [role=checkbox], [role=switch]"
+  ));
+  const key = options.key;
+  const wanted = clean(key);
+  const labels = (el) => [
+    ...Array.from(el.labels || [], (label) => clean(label.innerText)),
+    clean(el.getAttribute("aria-label")),
+    ...(el.getAttribute("aria-labelledby") || "").split(/\s+/)
+      .map((id) => clean(document.getElementById(id)?.innerText)),
+  ].filter(Boolean);
+
+  const strategies = {
+    name: () => fields.find((el) => el.getAttribute("name") === key || el.id === key),
+    label: () => fields.find((el) => labels(el).includes(wanted)) ||
+      fields.find((el) => labels(el).some((label) => label.startsWith(wanted))),
+    placeholder: () => fields.find((el) =>
+      clean(el.getAttribute("placeholder") || el.getAttribute("aria-placeholder")) === wanted),
+    selector: () => {
+      try {
+        return scope.querySelector(key);
+      } catch (e) {
+        return null;
+      }
+    },
+  };
+
+  if (options.by) return strategies[options.by]() || null;
+  for (const strategy of ["name", "label", "placeholder", "selector"]) {
+    const el = strategies[strategy]();
+    if (el) return el;
+  }
+  return null;
+}
+"#;
+
+/// Called on a field with `{ value }`. Sets selects, checkboxes, radios and
+/// date-like inputs directly. Text-like fields are cleared and focused, and
+/// `{ type }` tells the caller what to type into them.
+const FILL_JS: &str = r#"
+function (options) {
+  const value = options.value;
+  const clean = (s) => (s || "").replace(/\s+/g, " ").trim().toLowerCase();
+  const text = (v) => v === null || v === undefined ? "" : Array.isArray(v) ? v.join(",") : String(v);
+  const fire = (el, ...types) => types.forEach((type) => el.dispatchEvent(new Event(type, { bubbles: true })));
+  // Frameworks like React track the value setter, so bypass their override
+  const setValue = (el, v) => {
+    const proto = Object.getPrototypeOf(el);
+    const setter = Object.getOwnPropertyDescriptor(proto, "value")?.set;
+    setter ? setter.call(el, v) : (el.value = v);
+  };
+
+  const tag = this.tagName;
+  const type = tag === "INPUT" ? (this.type || "text").toLowerCase() : "";
+  const role = this.getAttribute("role");
+  if (this.disabled || this.readOnly || this.getAttribute("aria-disabled") === "true") {
+    return { error: "Field is disabled or read-only" };
+  }
+
+  if (tag === "SELECT") {
+    const all = Array.from(this.options);
+    const matched = [];
+    for (const wanted of (Array.isArray(value) ? value : [value]).map(text)) {
+      const option = all.find((o) => o.value === wanted) ||
+        all.find((o) => clean(o.label) === clean(wanted));
+      if (!option) return { error: `No option with value or label "${wanted}"` };
+      matched.push(option);
+    }
+    if (matched.length > 1 && !this.multiple) return { error: "The <select> only allows one option" };
+    for (const option of all) option.selected = matched.includes(option);
+    fire(this, "input", "change");
+    return { kind: "select" };
+  }
+
+  if (type === "checkbox" || role === "checkbox" || role === "switch") {
+    const want = value === true || ["true", "on", "yes", "1", "checked"].includes(clean(text(value)));
+    const checked = () => type === "checkbox" ? this.checked : this.getAttribute("aria-checked") === "true";
+    if (checked() !== want) this.click();
+    if (checked() !== want) return { error: "Clicking the field did not change its checked state" };
+    return { kind: "checkbox" };
+  }
+
+  if (type === "radio") {
+    const group = this.name
+      ? Array.from((this.form || document).querySelectorAll("input[type=radio]")).filter((r) => r.name === this.name)
+      : [this];
+    const radio = value === true ? this : group.find((r) => r.value === text(value)) ||
+      group.find((r) => Array.from(r.labels || []).some((label) => clean(label.innerText) === clean(text(value))));
+    if (!radio) return { error: `No radio option with value or label "${text(value)}"` };
+    if (!radio.checked) radio.click();
+    return { kind: "radio" };
+  }
+
+  if (type === "file") return { error: "File inputs can't be filled with text" };
+  if (["date", "time", "datetime-local", "month", "week", "color", "range"].includes(type)) {
+    setValue(this, text(value));
+    fire(this, "input", "change");
+    if (this.value !== text(value)) {
+      const formats = { date: "YYYY-MM-DD", time: "HH:MM", "datetime-local": "YYYY-MM-DDTHH:MM", month: "YYYY-MM", week: "YYYY-Www" };
+      return { error: `Invalid ${type} value "${text(value)}"` + (formats[type] ? `; use ${formats[type]}` : "") };
+    }
+    return { kind: type };
+  }
+
+  this.focus();
+  if (this.isContentEditable) {
+    const range = document.createRange();
+    range.selectNodeContents(this);
+    const selection = window.getSelection();
+    selection.removeAllRanges();
+    selection.addRange(range);
+    document.execCommand("delete");
+  } else if ("value" in this) {
+    setValue(this, "");
+    fire(this, "input");
+  }
+  return { kind: this.isContentEditable ? "contenteditable" : type || tag.toLowerCase(), type: text(value) };
+}
+"#;
+
+/// Called on a field. Reports its value and any validation messages: the
+/// browser's own, plus error text the page links to an invalid field.
+const VALIDATE_JS: &str = r#"
+function () {
+  const clean = (s) => (s || "").replace(/\s+/g, " ").trim();
+  const type = (this.type || "").toLowerCase();
+  const invalid = this.getAttribute("aria-invalid") === "true";
+  const messages = [];
+  if (this.validationMessage) messages.push(this.validationMessage);
+  if (invalid) {
+    const ids = `${this.getAttribute("aria-errormessage") || ""} ${this.getAttribute("aria-describedby") || ""}`;
+    for (const id of ids.split(/\s+/).filter(Boolean)) {
+      const message = clean(document.getElementById(id)?.innerText);
+      if (message && !messages.includes(message)) messages.push(message);
+    }
+  }
+
+  let value;
+  if (type === "checkbox" || type === "radio") value = this.checked;
+  else if (this.tagName === "SELECT") value = Array.from(this.selectedOptions, (o) => o.value);
+  else if (type !== "password") value = this.isContentEditable ? this.innerText : this.value;
+  return {
+    value,
+    valid: (this.validity ? this.validity.valid : true) && !invalid,
+    messages,
+  };
+}
+"#;
+
+/// Called on the last filled field. Returns the form's submit button, or
+/// submits the form itself and returns null if it has none.
+const SUBMIT_BUTTON_JS: &str = r#"
+function () {
+  const form = this.form || this.closest("form");
+  if (!form) return "no_form";
+  const buttons = [
+    ...form.querySelectorAll("button, input[type=submit], input[type=image]"),
+    ...(form.id ? document.querySelectorAll(`[form="${CSS.escape(form.id)}"]`) : []),
+  ];
+  const button = buttons.find((b) =>
+    !b.disabled && (b.tagName === "INPUT" || (b.getAttribute("type") || "submit").toLowerCase() === "submit"));
+  if (button) return button;
+  form.requestSubmit();
+  return null;
+}
+"#;
+
+/// Called with `{ maxOptions }`. Describes every form and the fields outside
+/// any form, reading live values from the DOM.
+const LIST_FORMS_JS: &str = r#"
+(options) => {
+  const clean = (s) => (s || "").replace(/\s+/g, " ").trim();
+  const unique = (selector) => {
+    try {
+      return document.querySelectorAll(selector).length === 1;
+    } catch (e) {
+      return false;
+    }
+  };
+  const selectorFor = (el) => {
+    if (el.id && unique(`#${CSS.escape(el.id)}`)) return `#${CSS.escape(el.id)}`;
+    const tag = el.tagName.toLowerCase();
+    const name = el.getAttribute("name");
+    if (name) {
+      const byName = `${tag}[name="${CSS.escape(name)}"]`;
+      if (unique(byName)) return byName;
+      if (el.type === "radio" && unique(`${byName}[value="${CSS.escape(el.value)}"]`)) {
+        return `${byName}[value="${CSS.escape(el.value)}"]`;
+      }
+    }
+    const parts = [];
+    for (let node = el; node && node !== document.documentElement; node = node.parentElement) {
+      if (node.id && unique(`#${CSS.escape(node.id)}`)) {
+        parts.unshift(`#${CSS.escape(node.id)}`);
+        break;
+      }
+      const siblings = node.parentElement
+        ? Array.from(node.parentElement.children).filter((c) => c.tagName === node.tagName)
+        : [];
+      parts.unshift(node.tagName.toLowerCase() +
+        (siblings.length > 1 ? `:nth-of-type(${siblings.indexOf(node) + 1})` : ""));
+    }
+    return parts.join(" > ");
+  };
+  const labelFor = (el) => {
+    const label = Array.from(el.labels || [], (l) => clean(l.innerText)).find(Boolean) ||
+      clean(el.getAttribute("aria-label")) ||
+      (el.getAttribute("aria-labelledby") || "").split(/\s+/)
+        .map((id) => clean(document.getElementById(id)?.innerText)).filter(Boolean).join(" ") ||
+      clean(el.getAttribute("placeholder")) || clean(el.getAttribute("title"));
+    return label.replace(/\s*\*$/, "") || null;
+  };
+  const isVisible = (el) => el.getClientRects().length > 0 &&
+    getComputedStyle(el).visibility !== "hidden";
+
+  const fieldSelector = "input, textarea, select, [contenteditable]:not([contenteditable=false]), " +
+    "[role=textbox], [role=combobox], [role=checkbox], [role=switch]";
+  const skipped = new Set(["hidden", "submit", "button", "reset", "image"]);
+
+  const describe = (elements) => {
+    const fields = [];
+    const radioGroups = new Map();
+    for (const el of elements) {
+      const tag = el.tagName.toLowerCase();
+      const type = tag === "input" ? (el.type || "text").toLowerCase()
+        : tag === "select" ? (el.multiple ? "select-multiple" : "select")
+        : tag === "textarea" ? "textarea"
+        : el.isContentEditable ? "contenteditable" : el.getAttribute("role");
+      if (skipped.has(type)) continue;
+
+      if (type === "radio") {
+        const key = el.name || selectorFor(el);
+        let group = radioGroups.get(key);
+        if (!group) {
+          const fieldset = el.closest("fieldset");
+          group = {
+            label: clean(fieldset?.querySelector("legend")?.innerText) || null,
+            name: el.name || null,
+            type,
+            required: false,
+            value: null,
+            selector: el.name ? `input[type="radio"][name="${CSS.escape(el.name)}"]` : selectorFor(el),
+            visible: false,
+            options: [],
+          };
+          radioGroups.set(key, group);
+          fields.push(group);
+        }
+        group.required ||= el.required;
+        group.visible ||= isVisible(el);
+        if (el.checked) group.value = el.value;
+        group.options.push({ value: el.value, label: labelFor(el), checked: el.checked, selector: selectorFor(el) });
+        continue;
+      }
+
+      const role = el.getAttribute("role");
+      const field = {
+        label: labelFor(el),
+        name: el.getAttribute("name") || el.id || null,
+        type,
+        required: el.required || el.getAttribute("aria-required") === "true",
+        value: null,
+        selector: selectorFor(el),
+        visible: isVisible(el),
+      };
+      if (el.disabled || el.readOnly) field.disabled = true;
+      if (el.getAttribute("placeholder")) field.placeholder = el.getAttribute("placeholder");
+
+      if (type === "password") {
+        field.value = el.value ? "********" : "";
+      } else if (type === "checkbox" || role === "checkbox" || role === "switch") {
+        field.value = type === "checkbox" ? el.checked : el.getAttribute("aria-checked") === "true";
+      } else if (tag === "select") {
+        const all = Array.from(el.options);
+        field.value = el.multiple ? Array.from(el.selectedOptions, (o) => o.value) : el.value;
+        field.options = all.slice(0, options.maxOptions)
+          .map((o) => ({ value: o.value, label: clean(o.label), selected: o.selected }));
+        if (all.length > options.maxOptions) field.options_total = all.length;
+      } else if (type === "file") {
+        field.value = Array.from(el.files || [], (f) => f.name);
+      } else {
+        field.value = el.isContentEditable ? el.innerText : el.value ?? null;
+      }
+      fields.push(field);
+    }
+    return fields;
+  };
+
+  // Controls belong to the form they're associated with, which form="..."
+  // can point anywhere; contenteditable and role fields to the form around them
+  const owner = (el) => ("form" in el ? el.form : el.closest("form"));
+  const allFields = Array.from(document.querySelectorAll(fieldSelector));
+
+  const forms = Array.from(document.forms, (form, index) => {
+    const buttons = Array.from(form.elements).filter((el) =>
+      el.tagName === "BUTTON" ? (el.getAttribute("type") || "submit").toLowerCase() === "submit"
+        : el.tagName === "INPUT" && (el.type === "submit" || el.type === "image"));
+    return {
+      index,
+      name: form.getAttribute("name") || form.id || null,
+      selector: selectorFor(form),
+      action: form.action || null,
+      method: (form.getAttribute("method") || "get").toLowerCase(),
+      fields: describe(allFields.filter((el) => owner(el) === form)),
+      submit_buttons: buttons.map((b) => ({
+        text: clean(b.innerText || b.value || b.getAttribute("aria-label")) || null,
+        selector: selectorFor(b),
+      })),
+    };
+  });
+
+  const standalone = describe(allFields.filter((el) => !owner(el)));
+
+  return { forms, standalone, count: forms.length };
+}
+"#;
+
+/// Visible alert text on the page, e.g. a form-level error banner
+const ALERTS_JS: &str = r#"
+Array.from(document.querySelectorAll("[role=alert], [aria-live=assertive]"))
+  .filter((el) => el.getClientRects().length > 0)
+  .map((el) => el.innerText.replace(/\s+/g, " ").trim())
+  .filter(Boolean)
+"#;
+
+/// The outcome for one field
+#[derive(Debug, Serialize)]
+pub struct FieldResult {
+    pub field: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The value after filling; omitted for passwords
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<String>,
+}
+
+/// Set `element` to `value`, clearing and typing into text-like fields.
+/// Returns the kind of field that was filled.
+pub(super) fn fill_element(
+    tab: &Tab,
+    element: &Element,
+    value: &serde_json::Value,
+) -> Result<String, ToolResult> {
+    let filled = call_on_element(element, FILL_JS, serde_json::json!({ "value": value }))?;
+    if let Some(error) = filled["error"].as_str() {
+        return Err(ToolResult::err(ErrorCode::InvalidArgument, error));
+    }
+
+    if let Some(text) = filled["type"].as_str() {
+        if let Err(e) = tab.type_str(text) {
+            return Err(ToolResult::browser_err(
+                ErrorCode::ElementNotInteractable,
+                "Failed to type",
+                &*e,
+            ));
+        }
+    }
+    Ok(filled["kind"].as_str().unwrap_or_default().to_string())
+}
+
+/// Find a field by key, honouring a `name:`, `label:`, `placeholder:` or
+/// `selector:` prefix
+fn locate<'a>(tab: &'a Tab, form: Option<&str>, key: &str) -> Result<Element<'a>, ToolResult> {
+    let (by, bare) = match key.split_once(':') {
+        Some((prefix, rest)) if KEY_PREFIXES.contains(&prefix) => (Some(prefix), rest.trim()),
+        _ => (None, key),
+    };
+
+    let options = serde_json::json!({ "form": form, "key": bare, "by": by });
+    let object = tab
+        .evaluate(&format!("({})({})", LOCATE_JS, options), false)
+        .map_err(|e| {
+            ToolResult::browser_err(ErrorCode::ScriptError, "Failed to find field", &*e)
+        })?;
+    let object_id = object.object_id.ok_or_else(|| {
+        ToolResult::err(
+            ErrorCode::ElementNotFound,
+            format!("No field matching {:?}", key),
+        )
+    })?;
+    element_for_object(tab, object_id)
+}
+
+fn error_message(result: &ToolResult) -> String {
+    result
+        .error
+        .as_ref()
+        .map(|e| e.message.clone())
+        .unwrap_or_default()
+}
+
+impl BrowserAgent {
+    /// Fill several fields, each keyed by name, label, placeholder or
+    /// selector, optionally within one form, and optionally submit
+    pub fn fill_fields(
+        &self,
+        tab_id: Option<&str>,
+        form: Option<&str>,
+        fields: &serde_json::Map<String, serde_json::Value>,
+        submit: bool,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let mut results = Vec::new();
+        let mut elements = Vec::new();
+        for (key, value) in fields {
+            if let Some(result) = self.call.check() {
+                return result;
+            }
+
+            let filled = locate(tab, form, key).and_then(|element| {
+                let kind = fill_element(tab, &element, value)?;
+                Ok((element, kind))
+            });
+            match filled {
+                Ok((element, kind)) => {
+                    results.push(FieldResult {
+                        field: key.clone(),
+                        success: true,
+                        kind: Some(kind),
+                        error: None,
+                        value: None,
+                        valid: true,
+                        messages: Vec::new(),
+                    });
+                    elements.push(Some(element));
+                }
+                Err(e) => {
+                    results.push(FieldResult {
+                        field: key.clone(),
+                        success: false,
+                        kind: None,
+                        error: Some(error_message(&e)),
+                        value: None,
+                        valid: false,
+                        messages: Vec::new(),
+                    });
+                    elements.push(None);
+                }
+            }
+        }
+
+        // Many pages validate on blur
+        let _ = evaluate_value(tab, "document.activeElement?.blur()");
+        if let Some(result) = self.pause(Duration::from_millis(200)) {
+            return result;
+        }
+        self.validate(&mut results, &elements);
+
+        let failed = results.iter().filter(|r| !r.success).count();
+        let mut submitted = false;
+        if submit && failed == 0 {
+            if let Some(last) = elements.iter().rev().flatten().next() {
+                match self.submit_form(tab, last) {
+                    Ok(()) => submitted = true,
+                    Err(e) => return e,
+                }
+                if let Some(result) = self.settle(tab_id, after, Duration::from_millis(1000)) {
+                    return result;
+                }
+                // Fields are gone if the submit navigated away
+                self.validate(&mut results, &elements);
+            }
+        }
+
+        let alerts = evaluate_value(tab, ALERTS_JS).unwrap_or_else(|_| serde_json::json!([]));
+        ToolResult::ok(serde_json::json!({
+            "fields": results,
+            "filled": results.len() - failed,
+            "failed": failed,
+            "submitted": submitted,
+            "alerts": alerts
+        }))
+    }
+
+    /// Describe the page's forms and the fields outside any form
+    pub fn list_forms(&self, tab_id: Option<&str>, max_options: usize) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let options = serde_json::json!({ "maxOptions": max_options });
+        match evaluate_value(tab, &format!("({})({})", LIST_FORMS_JS, options)) {
+            Ok(forms) => ToolResult::ok(forms),
+            Err(e) => e,
+        }
+    }
+
+    fn validate(&self, results: &mut [FieldResult], elements: &[Option<Element>]) {
+        for (result, element) in results.iter_mut().zip(elements) {
+            let Some(element) = element else { continue };
+            let Ok(state) = call_on_element(element, VALIDATE_JS, serde_json::Value::Null) else {
+                continue;
+            };
+            result.value = state.get("value").cloned().filter(|v| !v.is_null());
+            result.valid = state["valid"].as_bool().unwrap_or(true);
+            result.messages = state["messages"]
+                .as_array()
+                .map(|messages| {
+                    messages
+                        .iter()
+                        .filter_map(|m| m.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+    }
+
+    /// Submit `field`'s form by clicking its submit button, falling back to
+    /// requestSubmit, or to Enter when the field isn't in a form
+    fn submit_form(&self, tab: &Tab, field: &Element) -> Result<(), ToolResult> {
+        let button = field
+            .call_js_fn(SUBMIT_BUTTON_JS, vec![], false)
+            .map_err(|e| {
+                ToolResult::browser_err(ErrorCode::ScriptError, "Failed to submit", &*e)
+            })?;
+
+        if button.value.as_ref().and_then(|v| v.as_str()) == Some("no_form") {
+            return field
+                .focus()
+                .and_then(|_| tab.press_key("Enter"))
+                .map(|_| ())
+                .map_err(|e| {
+                    ToolResult::browser_err(
+                        ErrorCode::ElementNotInteractable,
+                        "Failed to submit",
+                        &*e,
+                    )
+                });
+        }
+        if let Some(object_id) = button.object_id {
+            let button = element_for_object(tab, object_id)?;
+            button.click().map_err(|e| {
+                ToolResult::browser_err(
+                    ErrorCode::ElementNotInteractable,
+                    "Failed to click submit",
+                    &*e,
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,378 @@
+/**
+ * VybeR Agent Frames
+ *
+ * Lists a tab's frame tree and runs scripts inside a chosen iframe instead
+ * of the top document. A frame is reached through its document node, so
+ * scripts called on it run in the frame's own JS world and see its page
+ * scripts. Under site isolation, cross-site iframes (embedded checkouts,
+ * sign-in widgets) run in another renderer process. They are missing from
+ * the page's frame tree, so they are found through their owner elements
+ * instead and listed as unreachable.
+ */
+
+use super::{call_function_on, BrowserAgent, ErrorCode, ToolResult};
+use headless_chrome::protocol::cdp::{Page, Runtime, DOM};
+use headless_chrome::Tab;
+use std::collections::HashMap;
+
+/// Called on a frame's document with a script, to run it in the frame's
+/// global scope
+const EVAL_JS: &str = "function (script) { return (0, eval)(script); }";
+
+/// Called on a document. Returns its iframe and frame elements, including
+/// those inside open shadow roots.
+const OWNERS_JS: &str = r#"
+function () {
+  const owners = [];
+  const search = (root) => {
+    owners.push(...root.querySelectorAll("iframe, frame"));
+    for (const el of root.querySelectorAll("*")) {
+      if (el.shadowRoot) search(el.shadowRoot);
+    }
+  };
+  search(this);
+  return owners;
+}
+"#;
+
+/// Called with `{ includeFrames }`. Returns the document's HTML and URL,
+/// with the content of each same-origin iframe inlined in its place when
+/// `includeFrames` is set.
+const HTML_JS: &str = r#"
+(options) => {
+  const inline = (doc) => {
+    const copy = doc.documentElement.cloneNode(true);
+    if (!options.includeFrames) return copy;
+    const slots = copy.querySelectorAll("iframe, frame");
+    doc.querySelectorAll("iframe, frame").forEach((frame, i) => {
+      let inner = null;
+      try {
+        inner = frame.contentDocument;
+      } catch (e) {}
+      if (!inner?.documentElement) return;
+      const section = doc.createElement("section");
+      section.setAttribute("data-frame-src", frame.src);
+      section.append(...(inline(inner).querySelector("body")?.childNodes ?? []));
+      slots[i].replaceWith(section);
+    });
+    return copy;
+  };
+  return { html: inline(document).outerHTML, url: location.href };
+}
+"#;
+
+/// Every frame in the tab, depth first from the top frame, with its depth
+fn all_frames(tab: &Tab) -> Result<Vec<(Page::Frame, usize)>, ToolResult> {
+    fn walk(tree: Page::FrameTree, depth: usize, frames: &mut Vec<(Page::Frame, usize)>) {
+        frames.push((tree.frame, depth));
+        for child in tree.child_frames.unwrap_or_default() {
+            walk(child, depth + 1, frames);
+        }
+    }
+
+    let tree = tab
+        .call_method(Page::GetFrameTree(None))
+        .map_err(|e| ToolResult::browser_err(ErrorCode::Internal, "Failed to list frames", &*e))?
+        .frame_tree;
+    let mut frames = Vec::new();
+    walk(tree, 0, &mut frames);
+    Ok(frames)
+}
+
+/// An iframe whose document runs in another process, known only from its
+/// owner element
+struct RemoteFrame {
+    id: String,
+    parent_id: String,
+    name: Option<String>,
+    /// The owner's `src`, since the frame's own URL isn't visible from here
+    url: String,
+    depth: usize,
+}
+
+/// Iframes below `frames` that are missing from the frame tree because
+/// they run in another process. Each reachable frame's document is searched
+/// for owner elements, rather than asking for the whole DOM at once.
+fn remote_frames(
+    tab: &Tab,
+    frames: &[(Page::Frame, usize)],
+) -> Result<Vec<RemoteFrame>, ToolResult> {
+    fn attribute(node: &DOM::Node, name: &str) -> Option<String> {
+        node.attributes
+            .as_ref()?
+            .chunks(2)
+            .find(|pair| pair[0] == name)
+            .and_then(|pair| pair.get(1).cloned())
+    }
+
+    let depths: HashMap<&str, usize> = frames
+        .iter()
+        .map(|(frame, depth)| (frame.id.as_str(), *depth))
+        .collect();
+    let mut remote = Vec::new();
+    for (frame, depth) in frames {
+        let document = match frame.parent_id {
+            None => frame_document(tab, None)?,
+            // Nothing inside an unreachable frame can be searched
+            Some(_) => match owned_document(tab, &frame.id) {
+                Ok(document) => document,
+                Err(_) => continue,
+            },
+        };
+        let owners = call_function_on(tab, &document, OWNERS_JS, serde_json::Value::Null, false)?;
+        let Some(owners) = owners.object_id else {
+            continue;
+        };
+        let properties = tab
+            .call_method(Runtime::GetProperties {
+                object_id: owners,
+                own_properties: Some(true),
+                accessor_properties_only: None,
+                generate_preview: None,
+                non_indexed_properties_only: None,
+            })
+            .map_err(|e| {
+                ToolResult::browser_err(ErrorCode::Internal, "Failed to list frames", &*e)
+            })?
+            .result;
+
+        for object_id in properties.into_iter().filter_map(|p| p.value?.object_id) {
+            let described = tab.call_method(DOM::DescribeNode {
+                node_id: None,
+                backend_node_id: None,
+                object_id: Some(object_id),
+                depth: Some(0),
+                pierce: None,
+            });
+            // An owner removed since the search has nothing to list
+            let Ok(described) = described else {
+                continue;
+            };
+            let node = described.node;
+            let Some(id) = node.frame_id.clone() else {
+                continue;
+            };
+            if depths.contains_key(id.as_str()) {
+                continue;
+            }
+            remote.push(RemoteFrame {
+                id,
+                parent_id: frame.id.clone(),
+                name: attribute(&node, "name").filter(|name| !name.is_empty()),
+                url: attribute(&node, "src").unwrap_or_default(),
+                depth: depth + 1,
+            });
+        }
+    }
+    Ok(remote)
+}
+
+fn unreachable(frame_id: &str) -> ToolResult {
+    ToolResult::err(
+        ErrorCode::FrameNotFound,
+        format!(
+            "Frame {} runs in another process and can't be reached",
+            frame_id
+        ),
+    )
+}
+
+/// Find a frame by ID, then by name, then by part of its URL
+fn find_frame(tab: &Tab, frame: &str) -> Result<Page::Frame, ToolResult> {
+    let all = all_frames(tab)?;
+    let frames: Vec<&Page::Frame> = all.iter().map(|(f, _)| f).collect();
+    let found = frames
+        .iter()
+        .find(|f| f.id == frame)
+        .or_else(|| frames.iter().find(|f| f.name.as_deref() == Some(frame)))
+        .or_else(|| frames.iter().skip(1).find(|f| f.url.contains(frame)));
+    if let Some(found) = found {
+        return Ok((*found).clone());
+    }
+
+    // Name the frame as unreachable rather than missing when it's there
+    let remote = remote_frames(tab, &all)?;
+    let found = remote
+        .iter()
+        .find(|f| f.id == frame)
+        .or_else(|| remote.iter().find(|f| f.name.as_deref() == Some(frame)))
+        .or_else(|| remote.iter().find(|f| f.url.contains(frame)));
+    Err(match found {
+        Some(found) => unreachable(&found.id),
+        None => ToolResult::err(
+            ErrorCode::FrameNotFound,
+            format!(
+                "Frame not found: {}. Use list_frames to see the page's frames.",
+                frame
+            ),
+        ),
+    })
+}
+
+/// Handle to the document of the iframe with ID `frame_id`
+fn owned_document(tab: &Tab, frame_id: &str) -> Result<String, ToolResult> {
+    // DOM methods only work once the document has been requested
+    let owner = tab
+        .get_document()
+        .and_then(|_| {
+            tab.call_method(DOM::GetFrameOwner {
+                frame_id: frame_id.to_string(),
+            })
+        })
+        .map_err(|e| {
+            ToolResult::browser_err(ErrorCode::FrameNotFound, "Failed to find frame", &*e)
+        })?;
+    let document = tab
+        .call_method(DOM::DescribeNode {
+            node_id: None,
+            backend_node_id: Some(owner.backend_node_id),
+            object_id: None,
+            depth: Some(0),
+            pierce: Some(true),
+        })
+        .map_err(|e| {
+            ToolResult::browser_err(ErrorCode::FrameNotFound, "Failed to find frame", &*e)
+        })?
+        .node
+        .content_document
+        .ok_or_else(|| unreachable(frame_id))?;
+
+    tab.call_method(DOM::ResolveNode {
+        node_id: None,
+        backend_node_id: Some(document.backend_node_id),
+        object_group: None,
+        execution_context_id: None,
+    })
+    .map_err(|e| ToolResult::browser_err(ErrorCode::FrameNotFound, "Failed to reach frame", &*e))?
+    .object
+    .object_id
+    .ok_or_else(|| unreachable(frame_id))
+}
+
+/// Handle to the document of `frame`, or of the top frame when None
+fn frame_document(tab: &Tab, frame: Option<&str>) -> Result<String, ToolResult> {
+    if let Some(frame) = frame {
+        let frame = find_frame(tab, frame)?;
+        if frame.parent_id.is_some() {
+            return owned_document(tab, &frame.id);
+        }
+    }
+
+    tab.evaluate("document", false)
+        .map_err(|e| {
+            ToolResult::browser_err(ErrorCode::ScriptError, "Failed to reach document", &*e)
+        })?
+        .object_id
+        .ok_or_else(|| ToolResult::err(ErrorCode::ScriptError, "Page has no document"))
+}
+
+/// Call `function` with `arg` in `frame`'s JS world (the top document's
+/// when None), with the frame's document as `this`
+pub(super) fn call_in_frame(
+    tab: &Tab,
+    frame: Option<&str>,
+    function: &str,
+    arg: serde_json::Value,
+    return_by_value: bool,
+) -> Result<Runtime::RemoteObject, ToolResult> {
+    let document = frame_document(tab, frame)?;
+    call_function_on(tab, &document, function, arg, return_by_value)
+}
+
+/// Like `call_in_frame`, returning the result as JSON
+pub(super) fn evaluate_in_frame(
+    tab: &Tab,
+    frame: Option<&str>,
+    function: &str,
+    arg: serde_json::Value,
+) -> Result<serde_json::Value, ToolResult> {
+    call_in_frame(tab, frame, function, arg, true)
+        .map(|result| result.value.unwrap_or(serde_json::Value::Null))
+}
+
+/// The HTML and URL of `frame` (the top document when None), optionally
+/// with same-origin iframe content inlined
+pub(super) fn frame_html(
+    tab: &Tab,
+    frame: Option<&str>,
+    include_frames: bool,
+) -> Result<(String, String), ToolResult> {
+    let page = evaluate_in_frame(
+        tab,
+        frame,
+        HTML_JS,
+        serde_json::json!({ "includeFrames": include_frames }),
+    )?;
+    match (page["html"].as_str(), page["url"].as_str()) {
+        (Some(html), Some(url)) => Ok((html.to_string(), url.to_string())),
+        _ => Err(ToolResult::err(
+            ErrorCode::Internal,
+            "Unexpected page content",
+        )),
+    }
+}
+
+/// Run a script in `frame`'s global scope, returning its result as JSON
+pub(super) fn eval_in_frame(
+    tab: &Tab,
+    frame: &str,
+    script: &str,
+) -> Result<serde_json::Value, ToolResult> {
+    evaluate_in_frame(tab, Some(frame), EVAL_JS, script.into())
+}
+
+impl BrowserAgent {
+    /// List the tab's frames, top frame first
+    pub fn list_frames(&self, tab_id: Option<&str>) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let frames = match all_frames(tab) {
+            Ok(frames) => frames,
+            Err(e) => return e,
+        };
+        let remote = match remote_frames(tab, &frames) {
+            Ok(remote) => remote,
+            Err(e) => return e,
+        };
+        let top_origin = frames
+            .first()
+            .map(|(frame, _)| frame.security_origin.clone())
+            .unwrap_or_default();
+
+        let mut frames: Vec<serde_json::Value> = frames
+            .into_iter()
+            .map(|(frame, depth)| {
+                let reachable = frame.parent_id.is_none() || owned_document(tab, &frame.id).is_ok();
+                serde_json::json!({
+                    "frame_id": frame.id,
+                    "parent_id": frame.parent_id,
+                    "name": frame.name.filter(|name| !name.is_empty()),
+                    "url": frame.url,
+                    "depth": depth,
+                    "same_origin": frame.security_origin == top_origin,
+                    "reachable": reachable
+                })
+            })
+            .collect();
+        // Another process means another site, so never the top's origin
+        frames.extend(remote.into_iter().map(|frame| {
+            serde_json::json!({
+                "frame_id": frame.id,
+                "parent_id": frame.parent_id,
+                "name": frame.name,
+                "url": frame.url,
+                "depth": frame.depth,
+                "same_origin": false,
+                "reachable": false
+            })
+        }));
+
+        ToolResult::ok(serde_json::json!({
+            "frames": frames,
+            "count": frames.len()
+        }))
+    }
+}
@@ -0,0 +1,552 @@
+/**
+ * VybeR Agent Input Primitives
+ *
+ * Mouse and keyboard actions beyond a plain left click: double and right
+ * clicks, drag and drop, key chords such as Control+A, choosing `<select>`
+ * options, toggling checkboxes and radios, and clicking a bare point in
+ * the viewport. Elements are targeted the same way `click` targets them.
+ */
+
+use super::{call_on_element, BrowserAgent, ErrorCode, Target, ToolResult, WaitFor};
+use headless_chrome::browser::tab::element::Element;
+use headless_chrome::browser::tab::point::Point;
+use headless_chrome::browser::tab::ModifierKey;
+use headless_chrome::protocol::cdp::Input::{
+    DispatchMouseEvent, DispatchMouseEventTypeOption, MouseButton,
+};
+use headless_chrome::Tab;
+use std::time::Duration;
+
+/// Mouse moves between the start and end of a drag, so drag handlers that
+/// track movement see more than a jump
+const DRAG_STEPS: u32 = 10;
+
+/// Called with `{ values }` on a `<select>`, one of its options or its
+/// label. Matches each value against option values first, then visible
+/// labels.
+const SELECT_JS: &str = r#"
+function (options) {
+  // Text targets land on an option or a label rather than the <select>
+  const select = this.tagName === "LABEL" ? this.control : this.closest("select");
+  if (select?.tagName !== "SELECT") return { error: "Element is not a <select>" };
+  const clean = (s) => (s || "").replace(/\s+/g, " ").trim().toLowerCase();
+  const all = Array.from(select.options);
+  const matched = [];
+  for (const wanted of options.values) {
+    const option = all.find((o) => o.value === wanted) ||
+      all.find((o) => clean(o.label) === clean(wanted));
+    if (!option) return { error: `No option with value or label "${wanted}"` };
+    if (option.disabled) return { error: `Option "${wanted}" is disabled` };
+    matched.push(option);
+  }
+  if (matched.length > 1 && !select.multiple) return { error: "The <select> only allows one option" };
+
+  for (const option of all) option.selected = matched.includes(option);
+  select.dispatchEvent(new Event("input", { bubbles: true }));
+  select.dispatchEvent(new Event("change", { bubbles: true }));
+  return {
+    selected: matched.map((o) => ({ value: o.value, label: o.label })),
+  };
+}
+"#;
+
+/// Called on a checkbox, radio or ARIA equivalent. Reports the current
+/// state, or null for an element that can't be checked.
+const CHECKED_JS: &str = r#"
+function () {
+  if (this.tagName === "INPUT" && (this.type === "checkbox" || this.type === "radio")) {
+    return { checked: this.checked, radio: this.type === "radio" };
+  }
+  const role = this.getAttribute("role");
+  if (["checkbox", "radio", "switch", "menuitemcheckbox", "menuitemradio"].includes(role)) {
+    return { checked: this.getAttribute("aria-checked") === "true", radio: role.endsWith("radio") };
+  }
+  return null;
+}
+"#;
+
+/// Where a drag ends
+pub enum DragDestination {
+    Target(Target),
+    /// Pixels from the dragged element's center
+    Offset {
+        x: f64,
+        y: f64,
+    },
+}
+
+fn mouse_event(
+    tab: &Tab,
+    kind: DispatchMouseEventTypeOption,
+    point: Point,
+    button: MouseButton,
+    buttons: u32,
+    click_count: u32,
+) -> Result<(), ToolResult> {
+    tab.call_method(DispatchMouseEvent {
+        Type: kind,
+        x: point.x,
+        y: point.y,
+        modifiers: None,
+        timestamp: None,
+        button: Some(button),
+        buttons: Some(buttons),
+        click_count: Some(click_count),
+        force: None,
+        tangential_pressure: None,
+        tilt_x: None,
+        tilt_y: None,
+        twist: None,
+        delta_x: None,
+        delta_y: None,
+        pointer_Type: None,
+    })
+    .map(|_| ())
+    .map_err(|e| {
+        ToolResult::browser_err(ErrorCode::ElementNotInteractable, "Mouse input failed", &*e)
+    })
+}
+
+/// Scroll the element into view and return its center
+fn element_point(element: &Element) -> Result<Point, ToolResult> {
+    element
+        .scroll_into_view()
+        .and_then(|e| e.get_midpoint())
+        .map_err(|e| {
+            ToolResult::browser_err(
+                ErrorCode::ElementNotInteractable,
+                "Element has no position",
+                &*e,
+            )
+        })
+}
+
+/// Press and release `button` at `point`, `clicks` times in a row
+fn click_at(tab: &Tab, point: Point, button: MouseButton, clicks: u32) -> Result<(), ToolResult> {
+    let mask = match button {
+        MouseButton::Right => 2,
+        MouseButton::Middle => 4,
+        _ => 1,
+    };
+    mouse_event(
+        tab,
+        DispatchMouseEventTypeOption::MouseMoved,
+        point,
+        MouseButton::None,
+        0,
+        0,
+    )?;
+    for count in 1..=clicks {
+        mouse_event(
+            tab,
+            DispatchMouseEventTypeOption::MousePressed,
+            point,
+            button.clone(),
+            mask,
+            count,
+        )?;
+        mouse_event(
+            tab,
+            DispatchMouseEventTypeOption::MouseReleased,
+            point,
+            button.clone(),
+            0,
+            count,
+        )?;
+    }
+    Ok(())
+}
+
+/// Split a chord like `Control+Shift+T` into its modifiers and key
+fn parse_chord(chord: &str) -> Result<(Vec<ModifierKey>, &str), ToolResult> {
+    // A trailing "++" means the key itself is "+"
+    let (modifiers, key) = match chord.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => chord.rsplit_once('+').unwrap_or(("", chord)),
+    };
+    if key.is_empty() {
+        return Err(ToolResult::err(
+            ErrorCode::InvalidArgument,
+            format!("No key in {:?}", chord),
+        ));
+    }
+
+    let mut parsed = Vec::new();
+    for name in modifiers.split('+').filter(|m| !m.is_empty()) {
+        parsed.push(match name.to_ascii_lowercase().as_str() {
+            "control" | "ctrl" => ModifierKey::Ctrl,
+            "shift" => ModifierKey::Shift,
+            "alt" | "option" => ModifierKey::Alt,
+            "meta" | "cmd" | "command" => ModifierKey::Meta,
+            // Select-all, copy and friends use Command on macOS
+            "controlormeta" if cfg!(target_os = "macos") => ModifierKey::Meta,
+            "controlormeta" => ModifierKey::Ctrl,
+            _ => {
+                return Err(ToolResult::err(
+                    ErrorCode::InvalidArgument,
+                    format!(
+                        "Unknown modifier {:?}. Use Control, Shift, Alt, Meta or ControlOrMeta.",
+                        name
+                    ),
+                ))
+            }
+        });
+    }
+    Ok((parsed, key))
+}
+
+impl BrowserAgent {
+    /// Double-click an element
+    pub fn double_click(
+        &self,
+        tab_id: Option<&str>,
+        target: &Target,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        self.click_with(tab_id, target, MouseButton::Left, 2, after)
+    }
+
+    /// Right-click an element, e.g. to open a context menu
+    pub fn right_click(
+        &self,
+        tab_id: Option<&str>,
+        target: &Target,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        self.click_with(tab_id, target, MouseButton::Right, 1, after)
+    }
+
+    /// Click a point in the viewport, in CSS pixels from its top-left, e.g.
+    /// a spot picked out of a screenshot
+    pub fn click_at(
+        &self,
+        tab_id: Option<&str>,
+        x: f64,
+        y: f64,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        if let Err(e) = click_at(tab, Point { x, y }, MouseButton::Left, 1) {
+            return e;
+        }
+        if let Some(result) = self.settle(tab_id, after, Duration::from_millis(500)) {
+            return result;
+        }
+        ToolResult::ok(serde_json::json!({ "clicked": { "x": x, "y": y } }))
+    }
+
+    fn click_with(
+        &self,
+        tab_id: Option<&str>,
+        target: &Target,
+        button: MouseButton,
+        clicks: u32,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let tab = &agent_tab.tab;
+
+        let result = target
+            .resolve(tab, &agent_tab.refs)
+            .and_then(|element| element_point(&element))
+            .and_then(|point| click_at(tab, point, button.clone(), clicks));
+        if let Err(e) = result {
+            return e;
+        }
+
+        if let Some(result) = self.settle(tab_id, after, Duration::from_millis(500)) {
+            return result;
+        }
+        let action = match (button, clicks) {
+            (MouseButton::Right, _) => "right_clicked",
+            (_, 2) => "double_clicked",
+            _ => "clicked",
+        };
+        ToolResult::ok(serde_json::json!({ action: target.to_string() }))
+    }
+
+    /// Drag an element onto another element or by an offset
+    pub fn drag(
+        &self,
+        tab_id: Option<&str>,
+        source: &Target,
+        destination: &DragDestination,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let (tab, refs) = (&agent_tab.tab, &agent_tab.refs);
+
+        let start = match source.resolve(tab, refs).and_then(|e| element_point(&e)) {
+            Ok(point) => point,
+            Err(e) => return e,
+        };
+        let end = match destination {
+            DragDestination::Target(target) => {
+                match target.resolve(tab, refs).and_then(|e| element_point(&e)) {
+                    Ok(point) => point,
+                    Err(e) => return e,
+                }
+            }
+            DragDestination::Offset { x, y } => Point {
+                x: start.x + x,
+                y: start.y + y,
+            },
+        };
+
+        // Scrolling the destination into view may have moved the source
+        let start = match source.resolve(tab, refs).and_then(|e| element_point(&e)) {
+            Ok(point) => point,
+            Err(e) => return e,
+        };
+
+        let result = (|| {
+            mouse_event(
+                tab,
+                DispatchMouseEventTypeOption::MouseMoved,
+                start,
+                MouseButton::None,
+                0,
+                0,
+            )?;
+            mouse_event(
+                tab,
+                DispatchMouseEventTypeOption::MousePressed,
+                start,
+                MouseButton::Left,
+                1,
+                1,
+            )?;
+            for step in 1..=DRAG_STEPS {
+                let t = f64::from(step) / f64::from(DRAG_STEPS);
+                let point = Point {
+                    x: start.x + (end.x - start.x) * t,
+                    y: start.y + (end.y - start.y) * t,
+                };
+                mouse_event(
+                    tab,
+                    DispatchMouseEventTypeOption::MouseMoved,
+                    point,
+                    MouseButton::Left,
+                    1,
+                    0,
+                )?;
+                if let Some(result) = self.pause(Duration::from_millis(20)) {
+                    return Err(result);
+                }
+            }
+            mouse_event(
+                tab,
+                DispatchMouseEventTypeOption::MouseReleased,
+                end,
+                MouseButton::Left,
+                0,
+                1,
+            )
+        })();
+        if let Err(e) = result {
+            // Don't leave the button held down if the drag was cut short
+            let _ = mouse_event(
+                tab,
+                DispatchMouseEventTypeOption::MouseReleased,
+                end,
+                MouseButton::Left,
+                0,
+                1,
+            );
+            return e;
+        }
+
+        if let Some(result) = self.settle(tab_id, after, Duration::from_millis(500)) {
+            return result;
+        }
+        ToolResult::ok(serde_json::json!({
+            "dragged": source.to_string(),
+            "to": { "x": end.x, "y": end.y }
+        }))
+    }
+
+    /// Press a key or chord such as `Escape` or `Control+A`, `count` times,
+    /// optionally focusing an element first
+    pub fn press_key(
+        &self,
+        tab_id: Option<&str>,
+        keys: &str,
+        target: Option<&Target>,
+        count: u32,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let tab = &agent_tab.tab;
+
+        let (modifiers, key) = match parse_chord(keys) {
+            Ok(chord) => chord,
+            Err(e) => return e,
+        };
+
+        if let Some(target) = target {
+            let element = match target.resolve(tab, &agent_tab.refs) {
+                Ok(element) => element,
+                Err(e) => return e,
+            };
+            if let Err(e) = element.focus() {
+                return ToolResult::browser_err(
+                    ErrorCode::ElementNotInteractable,
+                    "Failed to focus element",
+                    &*e,
+                );
+            }
+        }
+
+        for _ in 0..count {
+            if let Err(e) = tab.press_key_with_modifiers(key, Some(&modifiers)) {
+                return ToolResult::browser_err(
+                    ErrorCode::InvalidArgument,
+                    "Key press failed",
+                    &*e,
+                );
+            }
+        }
+
+        if let Some(result) = self.settle(tab_id, after, Duration::from_millis(300)) {
+            return result;
+        }
+        ToolResult::ok(serde_json::json!({
+            "pressed": keys,
+            "count": count
+        }))
+    }
+
+    /// Choose options of a `<select>` by value or visible label. Options
+    /// not listed are deselected.
+    pub fn select_option(
+        &self,
+        tab_id: Option<&str>,
+        target: &Target,
+        values: &[String],
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let tab = &agent_tab.tab;
+
+        let element = match target.resolve(tab, &agent_tab.refs) {
+            Ok(element) => element,
+            Err(e) => return e,
+        };
+
+        let result =
+            match call_on_element(&element, SELECT_JS, serde_json::json!({ "values": values })) {
+                Ok(result) => result,
+                Err(e) => return e,
+            };
+        if let Some(error) = result.get("error").and_then(|e| e.as_str()) {
+            return ToolResult::err(ErrorCode::InvalidArgument, error);
+        }
+
+        // Dependent fields often reload once the choice is made
+        if let Some(result) = self.settle(tab_id, after, Duration::from_millis(300)) {
+            return result;
+        }
+        ToolResult::ok(serde_json::json!({
+            "target": target.to_string(),
+            "selected": result["selected"]
+        }))
+    }
+
+    /// Check or uncheck a checkbox, radio or switch by clicking it, so the
+    /// page sees a real user action
+    pub fn set_checked(
+        &self,
+        tab_id: Option<&str>,
+        target: &Target,
+        checked: bool,
+        after: Option<&WaitFor>,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let tab = &agent_tab.tab;
+
+        let element = match target.resolve(tab, &agent_tab.refs) {
+            Ok(element) => element,
+            Err(e) => return e,
+        };
+
+        let state = match call_on_element(&element, CHECKED_JS, serde_json::Value::Null) {
+            Ok(state) if state.is_null() => {
+                return ToolResult::err(
+                    ErrorCode::InvalidArgument,
+                    format!("{} is not a checkbox, radio or switch", target),
+                )
+            }
+            Ok(state) => state,
+            Err(e) => return e,
+        };
+        if state["checked"].as_bool() == Some(checked) {
+            return ToolResult::ok(serde_json::json!({
+                "target": target.to_string(),
+                "checked": checked,
+                "changed": false
+            }));
+        }
+        if !checked && state["radio"].as_bool() == Some(true) {
+            return ToolResult::err(
+                ErrorCode::InvalidArgument,
+                "A radio button can't be unchecked; check another option in its group instead",
+            );
+        }
+
+        let is_checked = || {
+            call_on_element(&element, CHECKED_JS, serde_json::Value::Null)
+                .ok()
+                .and_then(|state| state["checked"].as_bool())
+        };
+
+        // Styled checkboxes often hide the input itself or cover it with a
+        // custom control, so fall back to a DOM click when there's nothing
+        // on screen to click or the click landed on something else
+        let clicked =
+            element_point(&element).and_then(|point| click_at(tab, point, MouseButton::Left, 1));
+        if clicked.is_err() || is_checked() != Some(checked) {
+            if let Err(e) = call_on_element(
+                &element,
+                "function () { this.click(); }",
+                serde_json::Value::Null,
+            ) {
+                return e;
+            }
+        }
+
+        if let Some(result) = self.settle(tab_id, after, Duration::from_millis(300)) {
+            return result;
+        }
+        if is_checked() != Some(checked) {
+            return ToolResult::err(
+                ErrorCode::ElementNotInteractable,
+                format!("Clicking {} did not change its checked state", target),
+            );
+        }
+
+        ToolResult::ok(serde_json::json!({
+            "target": target.to_string(),
+            "checked": checked,
+            "changed": true
+        }))
+    }
+}
@@ -0,0 +1,529 @@
+/**
+ * VybeR Agent Request Interception
+ *
+ * Session-wide rules, applied through the CDP Fetch domain, that match
+ * requests by URL glob or regex and resource type and then block them,
+ * rewrite their headers, or answer them with a fixture response. The first
+ * matching rule wins. Fetch is only enabled while at least one rule exists,
+ * so sessions without rules pay nothing.
+ */
+
+use super::network::{headers, resource_type_name};
+use super::{BrowserAgent, ErrorCode, ToolResult};
+use base64::Engine;
+use headless_chrome::browser::tab::RequestPausedDecision;
+use headless_chrome::browser::transport::{SessionId, Transport};
+use headless_chrome::protocol::cdp::Fetch::events::RequestPausedEvent;
+use headless_chrome::protocol::cdp::Fetch::{
+    ContinueRequest, FailRequest, FulfillRequest, HeaderEntry, RequestPattern,
+};
+use headless_chrome::protocol::cdp::Network::ErrorReason;
+use headless_chrome::Tab;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// What a rule does to the requests it matches, as given in tool input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Fail the request as blocked by the client
+    Block,
+    /// Send the request with headers added, replaced or removed
+    ModifyHeaders {
+        #[serde(default)]
+        set_headers: BTreeMap<String, String>,
+        #[serde(default)]
+        remove_headers: Vec<String>,
+    },
+    /// Answer the request without hitting the network
+    Fulfill {
+        #[serde(default = "default_status")]
+        status: u32,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        content_type: Option<String>,
+        /// Response body as text
+        #[serde(skip_serializing)]
+        body: Option<String>,
+        /// Response body as base64, for binary fixtures
+        #[serde(skip_serializing)]
+        body_base64: Option<String>,
+    },
+}
+
+fn default_status() -> u32 {
+    200
+}
+
+/// A rule as given in tool input
+#[derive(Debug, Deserialize)]
+pub struct RuleInput {
+    /// Glob where `*` matches any run of characters and `?` one character
+    pub url_pattern: Option<String>,
+    pub url_regex: Option<String>,
+    /// Resource types such as `image`, `font`, `script`, `xhr`
+    pub resource_types: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Block,
+    ModifyHeaders {
+        set: Vec<HeaderEntry>,
+        remove: Vec<String>,
+    },
+    Fulfill {
+        status: u32,
+        headers: Vec<HeaderEntry>,
+        /// Base64, as Fetch.fulfillRequest expects
+        body: String,
+    },
+}
+
+#[derive(Debug)]
+struct Rule {
+    id: String,
+    url_pattern: Option<String>,
+    url_regex: Option<String>,
+    matcher: Option<Regex>,
+    resource_types: Vec<String>,
+    action: Action,
+    /// The action as given, for listing
+    described: RuleAction,
+    hits: u64,
+}
+
+impl Rule {
+    fn matches(&self, url: &str, resource_type: &str) -> bool {
+        self.matcher.as_ref().is_none_or(|m| m.is_match(url))
+            && (self.resource_types.is_empty()
+                || self.resource_types.iter().any(|t| t == resource_type))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rules {
+    rules: Vec<Rule>,
+    next_id: usize,
+}
+
+/// The session's interception rules, shared with each tab's interceptor
+#[derive(Clone, Default)]
+pub struct Interception(Arc<Mutex<Rules>>);
+
+impl Interception {
+    fn lock(&self) -> MutexGuard<'_, Rules> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Route `tab`'s paused requests through the rules, enabling Fetch if
+    /// there are any
+    pub fn attach(&self, tab: &Tab) {
+        let rules = self.clone();
+        let _ = tab.enable_request_interception(Arc::new(
+            move |_: Arc<Transport>, _: SessionId, event: RequestPausedEvent| rules.decide(&event),
+        ));
+        if !self.lock().rules.is_empty() {
+            let _ = enable_fetch(tab);
+        }
+    }
+
+    fn decide(&self, event: &RequestPausedEvent) -> RequestPausedDecision {
+        let params = &event.params;
+        let resource_type = resource_type_name(&params.resource_Type);
+
+        let action = {
+            let mut rules = self.lock();
+            let Some(rule) = rules
+                .rules
+                .iter_mut()
+                .find(|r| r.matches(&params.request.url, &resource_type))
+            else {
+                return RequestPausedDecision::Continue(None);
+            };
+            rule.hits += 1;
+            rule.action.clone()
+        };
+
+        let request_id = params.request_id.clone();
+        match action {
+            Action::Block => RequestPausedDecision::Fail(FailRequest {
+                request_id,
+                error_reason: ErrorReason::BlockedByClient,
+            }),
+            Action::ModifyHeaders { set, remove } => {
+                let mut request_headers: Vec<HeaderEntry> = headers(&params.request.headers)
+                    .into_iter()
+                    .filter(|h| {
+                        !remove
+                            .iter()
+                            .chain(set.iter().map(|s| &s.name))
+                            .any(|name| name.eq_ignore_ascii_case(&h.name))
+                    })
+                    .map(|h| HeaderEntry {
+                        name: h.name,
+                        value: h.value,
+                    })
+                    .collect();
+                request_headers.extend(set);
+                RequestPausedDecision::Continue(Some(ContinueRequest {
+                    request_id,
+                    url: None,
+                    method: None,
+                    post_data: None,
+                    headers: Some(request_headers),
+                    intercept_response: None,
+                }))
+            }
+            Action::Fulfill {
+                status,
+                headers,
+                body,
+            } => RequestPausedDecision::Fulfill(FulfillRequest {
+                request_id,
+                response_code: status,
+                response_headers: Some(headers),
+                binary_response_headers: None,
+                body: Some(body),
+                response_phrase: None,
+            }),
+        }
+    }
+}
+
+fn enable_fetch(tab: &Tab) -> Result<(), ToolResult> {
+    let patterns = [RequestPattern {
+        url_pattern: Some("*".to_string()),
+        resource_Type: None,
+        request_stage: None,
+    }];
+    tab.enable_fetch(Some(&patterns), None)
+        .map(|_| ())
+        .map_err(|e| {
+            ToolResult::browser_err(ErrorCode::Internal, "Failed to enable interception", &*e)
+        })
+}
+
+/// Turn `*` and `?` wildcards into an anchored regex
+pub(super) fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
+
+fn header_entries(headers: &BTreeMap<String, String>) -> Vec<HeaderEntry> {
+    headers
+        .iter()
+        .map(|(name, value)| HeaderEntry {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
+fn compile_action(action: &RuleAction) -> Result<Action, ToolResult> {
+    Ok(match action {
+        RuleAction::Block => Action::Block,
+        RuleAction::ModifyHeaders {
+            set_headers,
+            remove_headers,
+        } => Action::ModifyHeaders {
+            set: header_entries(set_headers),
+            remove: remove_headers.clone(),
+        },
+        RuleAction::Fulfill {
+            status,
+            headers,
+            content_type,
+            body,
+            body_base64,
+        } => {
+            let body = match (body, body_base64) {
+                (Some(body), _) => base64::engine::general_purpose::STANDARD.encode(body),
+                (None, Some(body_base64)) => {
+                    // Chrome would only reject bad base64 when a request
+                    // matches, long after the rule was added
+                    if let Err(e) = base64::engine::general_purpose::STANDARD.decode(body_base64) {
+                        return Err(ToolResult::err(
+                            ErrorCode::InvalidArgument,
+                            format!("Invalid body_base64: {}", e),
+                        ));
+                    }
+                    body_base64.clone()
+                }
+                (None, None) => String::new(),
+            };
+            let mut headers = header_entries(headers);
+            if let Some(content_type) = content_type {
+                headers.retain(|h| !h.name.eq_ignore_ascii_case("content-type"));
+                headers.push(HeaderEntry {
+                    name: "Content-Type".to_string(),
+                    value: content_type.clone(),
+                });
+            }
+            Action::Fulfill {
+                status: *status,
+                headers,
+                body,
+            }
+        }
+    })
+}
+
+impl BrowserAgent {
+    /// Add an interception rule for every tab in the session
+    pub fn add_intercept_rule(&self, input: RuleInput) -> ToolResult {
+        let matcher = match (&input.url_regex, &input.url_pattern) {
+            (Some(url_regex), _) => Regex::new(url_regex).map(Some),
+            (None, Some(url_pattern)) => Regex::new(&glob_to_regex(url_pattern)).map(Some),
+            (None, None) => Ok(None),
+        };
+        let matcher = match matcher {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                return ToolResult::err(
+                    ErrorCode::InvalidArgument,
+                    format!("Invalid URL pattern: {}", e),
+                )
+            }
+        };
+        let action = match compile_action(&input.action) {
+            Ok(action) => action,
+            Err(e) => return e,
+        };
+
+        let id = {
+            let mut rules = self.interception.lock();
+            rules.next_id += 1;
+            let id = format!("r{}", rules.next_id);
+            rules.rules.push(Rule {
+                id: id.clone(),
+                url_pattern: input.url_pattern,
+                url_regex: input.url_regex,
+                matcher,
+                resource_types: input
+                    .resource_types
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|t| t.to_lowercase())
+                    .collect(),
+                action,
+                described: input.action,
+                hits: 0,
+            });
+            id
+        };
+
+        for agent_tab in &self.tabs {
+            if let Err(e) = enable_fetch(&agent_tab.tab) {
+                // Don't leave a rule behind that only some tabs apply
+                let _ = self.remove_intercept_rule(Some(&id));
+                return e;
+            }
+        }
+
+        ToolResult::ok(serde_json::json!({
+            "rule_id": id,
+            "rules": self.interception.lock().rules.len()
+        }))
+    }
+
+    /// List the session's interception rules and how often each matched
+    pub fn list_intercept_rules(&self) -> ToolResult {
+        let rules: Vec<serde_json::Value> = self
+            .interception
+            .lock()
+            .rules
+            .iter()
+            .map(|rule| {
+                let mut listed = serde_json::json!(rule.described);
+                listed["rule_id"] = rule.id.clone().into();
+                listed["url_pattern"] = serde_json::json!(rule.url_pattern);
+                listed["url_regex"] = serde_json::json!(rule.url_regex);
+                listed["resource_types"] = serde_json::json!(rule.resource_types);
+                listed["hits"] = rule.hits.into();
+                listed
+            })
+            .collect();
+
+        ToolResult::ok(serde_json::json!({
+            "rules": rules,
+            "count": rules.len()
+        }))
+    }
+
+    /// Remove one rule, or all of them when `rule_id` is None
+    pub fn remove_intercept_rule(&self, rule_id: Option<&str>) -> ToolResult {
+        let (removed, remaining) = {
+            let mut rules = self.interception.lock();
+            let before = rules.rules.len();
+            match rule_id {
+                Some(rule_id) => rules.rules.retain(|r| r.id != rule_id),
+                None => rules.rules.clear(),
+            }
+            (before - rules.rules.len(), rules.rules.len())
+        };
+
+        if let (Some(rule_id), 0) = (rule_id, removed) {
+            return ToolResult::err(
+                ErrorCode::InvalidArgument,
+                format!("Rule not found: {}", rule_id),
+            );
+        }
+
+        if remaining == 0 {
+            for agent_tab in &self.tabs {
+                if let Err(e) = agent_tab.tab.disable_fetch() {
+                    return ToolResult::browser_err(
+                        ErrorCode::Internal,
+                        "Failed to disable interception",
+                        &*e,
+                    );
+                }
+            }
+        }
+
+        ToolResult::ok(serde_json::json!({
+            "removed": removed,
+            "rules": remaining
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob_matches(glob: &str, url: &str) -> bool {
+        Regex::new(&glob_to_regex(glob)).unwrap().is_match(url)
+    }
+
+    fn action(json: serde_json::Value) -> RuleAction {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn fulfill(action: Action) -> (u32, Vec<HeaderEntry>, String) {
+        match action {
+            Action::Fulfill {
+                status,
+                headers,
+                body,
+            } => (status, headers, body),
+            other => panic!("expected a fulfill action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn globs_escape_regex_characters() {
+        assert_eq!(glob_to_regex("a.b+c"), r"^a\.b\+c$");
+        assert!(glob_matches(
+            "https://x.test/a?b=(1)",
+            "https://x.test/a?b=(1)"
+        ));
+        assert!(!glob_matches("https://x.test/a.js", "https://x.test/aXjs"));
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_matches("*.png", "https://cdn.test/img/logo.png"));
+        assert!(glob_matches(
+            "https://api.test/v?/*",
+            "https://api.test/v2/users"
+        ));
+        assert!(!glob_matches(
+            "https://api.test/v?/*",
+            "https://api.test/v10/users"
+        ));
+    }
+
+    #[test]
+    fn globs_are_anchored() {
+        assert!(!glob_matches("https://x.test/", "https://x.test/page"));
+        assert!(!glob_matches("x.test/*", "https://x.test/page"));
+        assert!(glob_matches("*x.test/*", "https://x.test/page"));
+    }
+
+    #[test]
+    fn bad_base64_bodies_are_rejected() {
+        let result = compile_action(&action(serde_json::json!({
+            "action": "fulfill",
+            "body_base64": "not base64!"
+        })));
+        assert_eq!(
+            result.unwrap_err().error.unwrap().code,
+            ErrorCode::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn base64_bodies_pass_through() {
+        let compiled = compile_action(&action(serde_json::json!({
+            "action": "fulfill",
+            "status": 201,
+            "body_base64": "iVBORw0KGgo="
+        })));
+        let (status, headers, body) = fulfill(compiled.unwrap());
+        assert_eq!(status, 201);
+        assert!(headers.is_empty());
+        assert_eq!(body, "iVBORw0KGgo=");
+    }
+
+    #[test]
+    fn text_bodies_are_encoded() {
+        let compiled = compile_action(&action(serde_json::json!({
+            "action": "fulfill",
+            "body": "{\"ok\":true}"
+        })));
+        let (status, _, body) = fulfill(compiled.unwrap());
+        assert_eq!(status, 200);
+        assert_eq!(body, "eyJvayI6dHJ1ZX0=");
+    }
+
+    #[test]
+    fn content_type_replaces_an_existing_header() {
+        let compiled = compile_action(&action(serde_json::json!({
+            "action": "fulfill",
+            "headers": { "content-type": "text/plain", "X-Fixture": "1" },
+            "content_type": "application/json"
+        })));
+        let (_, headers, _) = fulfill(compiled.unwrap());
+        let headers: Vec<(&str, &str)> = headers
+            .iter()
+            .map(|h| (h.name.as_str(), h.value.as_str()))
+            .collect();
+        assert_eq!(
+            headers,
+            [("X-Fixture", "1"), ("Content-Type", "application/json")]
+        );
+    }
+
+    #[test]
+    fn modify_headers_keeps_sets_and_removals() {
+        let compiled = compile_action(&action(serde_json::json!({
+            "action": "modify_headers",
+            "set_headers": { "Authorization": "Bearer test" },
+            "remove_headers": ["Cookie"]
+        })));
+        match compiled.unwrap() {
+            Action::ModifyHeaders { set, remove } => {
+                assert_eq!(set.len(), 1);
+                assert_eq!(set[0].name, "Authorization");
+                assert_eq!(set[0].value, "Bearer test");
+                assert_eq!(remove, ["Cookie"]);
+            }
+            other => panic!("expected a modify_headers action, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+/**
+ * VybeR Agent Link Extraction
+ *
+ * Collects links from the live page (so visibility reflects layout), then
+ * resolves them against the page URL and `<base href>`, drops duplicates
+ * and applies the caller's origin, scheme and pattern filters.
+ */
+
+use super::frames::evaluate_in_frame;
+use super::readable::resolve_base;
+use super::{BrowserAgent, ErrorCode, ToolResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use url::Url;
+
+/// Called with `{ selector }`. Returns the document's URL and raw attribute
+/// values; resolution happens in Rust.
+const LINKS_JS: &str = r#"
+(options) => {
+  let root = document.body || document.documentElement;
+  if (options.selector) {
+    try {
+      root = document.querySelector(options.selector);
+    } catch (e) {
+      return { invalidSelector: true };
+    }
+  }
+
+  const clean = (s) => (s || "").replace(/\s+/g, " ").trim();
+  const isVisible = (el) => {
+    if (el.checkVisibility && !el.checkVisibility({ checkOpacity: true, checkVisibilityCSS: true })) return false;
+    if (el.closest("[aria-hidden='true'], [inert]")) return false;
+    const rect = el.getBoundingClientRect();
+    return rect.width > 0 && rect.height > 0;
+  };
+
+  const links = !root ? [] : Array.from(root.querySelectorAll("a[href], area[href]"), (el) => ({
+    href: el.getAttribute("href"),
+    text: clean(el.innerText) || clean(el.getAttribute("aria-label")) ||
+      clean(el.querySelector("img[alt]")?.alt) || clean(el.getAttribute("alt")),
+    rel: el.getAttribute("rel"),
+    target: el.getAttribute("target"),
+    title: el.getAttribute("title"),
+    visible: isVisible(el),
+  }));
+
+  return {
+    url: location.href,
+    baseHref: document.querySelector("base[href]")?.getAttribute("href") ?? null,
+    links,
+  };
+}
+"#;
+
+/// Which links to keep
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LinkFilter {
+    /// Only links with the same origin as the page
+    #[serde(default)]
+    pub same_origin: bool,
+    /// Only links with one of these schemes, e.g. `["https"]`
+    pub schemes: Option<Vec<String>>,
+    /// Only links whose resolved URL matches this regex
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Link {
+    pub text: String,
+    pub href: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub visible: bool,
+    /// Same origin as the page
+    pub internal: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageLinks {
+    #[serde(default)]
+    invalid_selector: bool,
+    #[serde(default)]
+    url: String,
+    base_href: Option<String>,
+    #[serde(default)]
+    links: Vec<RawLink>,
+}
+
+#[derive(Deserialize)]
+struct RawLink {
+    href: String,
+    text: String,
+    rel: Option<String>,
+    target: Option<String>,
+    title: Option<String>,
+    visible: bool,
+}
+
+impl BrowserAgent {
+    /// Extract links from the page (or the first element matching `selector`),
+    /// or from `frame`'s document
+    pub fn extract_links(
+        &self,
+        tab_id: Option<&str>,
+        selector: Option<&str>,
+        frame: Option<&str>,
+        filter: &LinkFilter,
+        max_links: usize,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let pattern = match filter.pattern.as_deref().map(Regex::new) {
+            Some(Ok(pattern)) => Some(pattern),
+            Some(Err(e)) => {
+                return ToolResult::err(
+                    ErrorCode::InvalidArgument,
+                    format!("Invalid pattern: {}", e),
+                )
+            }
+            None => None,
+        };
+
+        let options = serde_json::json!({ "selector": selector });
+        let page: PageLinks =
+            match evaluate_in_frame(tab, frame, LINKS_JS, options).and_then(|value| {
+                serde_json::from_value(value).map_err(|e| {
+                    ToolResult::err(ErrorCode::Internal, format!("Unexpected link data: {}", e))
+                })
+            }) {
+                Ok(page) => page,
+                Err(e) => return e,
+            };
+        if page.invalid_selector {
+            return ToolResult::err(
+                ErrorCode::InvalidSelector,
+                format!("Invalid selector: {}", selector.unwrap_or_default()),
+            );
+        }
+
+        let page_url = match Url::parse(&page.url) {
+            Ok(url) => url,
+            Err(e) => {
+                return ToolResult::err(ErrorCode::Internal, format!("Invalid page URL: {}", e))
+            }
+        };
+
+        let base = resolve_base(page_url.clone(), page.base_href.as_deref());
+        let mut links: Vec<Link> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+
+        for raw in page.links {
+            let Ok(url) = base.join(raw.href.trim()) else {
+                continue;
+            };
+            // Script links don't go anywhere
+            if url.scheme() == "javascript" {
+                continue;
+            }
+
+            let internal = url.origin() == page_url.origin();
+            if filter.same_origin && !internal {
+                continue;
+            }
+            if let Some(schemes) = &filter.schemes {
+                if !schemes
+                    .iter()
+                    .any(|s| s.trim_end_matches(':').eq_ignore_ascii_case(url.scheme()))
+                {
+                    continue;
+                }
+            }
+            let href = url.to_string();
+            if pattern.as_ref().is_some_and(|p| !p.is_match(&href)) {
+                continue;
+            }
+
+            // Keep the first occurrence, filling in what it lacks from repeats
+            if let Some(&index) = seen.get(&href) {
+                let link = &mut links[index];
+                if link.text.is_empty() {
+                    link.text = raw.text;
+                }
+                link.visible |= raw.visible;
+                continue;
+            }
+
+            seen.insert(href.clone(), links.len());
+            links.push(Link {
+                text: raw.text,
+                href,
+                rel: raw.rel.filter(|r| !r.trim().is_empty()),
+                target: raw.target.filter(|t| !t.trim().is_empty()),
+                title: raw.title.filter(|t| !t.trim().is_empty()),
+                visible: raw.visible,
+                internal,
+            });
+        }
+
+        let total = links.len();
+        links.truncate(max_links);
+
+        ToolResult::ok(serde_json::json!({
+            "links": links,
+            "count": links.len(),
+            "total": total,
+            "truncated": total > links.len()
+        }))
+    }
+}
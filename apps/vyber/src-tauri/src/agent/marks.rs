@@ -0,0 +1,139 @@
+/**
+ * VybeR Agent Set-of-Marks
+ *
+ * For vision models: a marked screenshot overlays a numbered box on every
+ * visible interactive element in the viewport and returns the numbers with
+ * each element's role, name, selector and box. `click_mark` clicks an
+ * element by its number, and `click_at` clicks any point in the image.
+ * Marks map to snapshot refs, so like refs they go stale on navigation.
+ */
+
+use super::snapshot::{run_snapshot, RefWorld};
+use super::{BrowserAgent, ErrorCode, Target, ToolResult, WaitFor};
+use headless_chrome::Tab;
+
+/// Most elements marked in one screenshot. Past this the labels overlap
+/// too much to read.
+const MAX_MARKS: usize = 200;
+
+/// Called with the `elements` from a snapshot with boxes. Draws the overlay,
+/// records which ref each mark number stands for, and returns the marks.
+const DRAW_MARKS_JS: &str = r##"
+(elements) => {
+  window.__vyberMarkOverlay?.remove();
+  const refs = window.__vyberRefs || new Map();
+  const unique = (selector) => {
+    try {
+      return document.querySelectorAll(selector).length === 1;
+    } catch (e) {
+      return false;
+    }
+  };
+  const selectorFor = (el) => {
+    if (el.id && unique(`#${CSS.escape(el.id)}`)) return `#${CSS.escape(el.id)}`;
+    const parts = [];
+    for (let node = el; node && node !== document.documentElement; node = node.parentElement) {
+      if (node.id && unique(`#${CSS.escape(node.id)}`)) {
+        parts.unshift(`#${CSS.escape(node.id)}`);
+        break;
+      }
+      const siblings = node.parentElement
+        ? Array.from(node.parentElement.children).filter((c) => c.tagName === node.tagName)
+        : [];
+      parts.unshift(node.tagName.toLowerCase() +
+        (siblings.length > 1 ? `:nth-of-type(${siblings.indexOf(node) + 1})` : ""));
+    }
+    return parts.join(" > ");
+  };
+
+  const COLORS = ["#e6194b", "#3cb44b", "#4363d8", "#f58231", "#911eb4", "#008080", "#9a6324", "#800000"];
+  // A shadow root keeps page styles off the overlay
+  const host = document.createElement("div");
+  host.style.cssText = "position:fixed;inset:0;z-index:2147483647;pointer-events:none;";
+  const root = host.attachShadow({ mode: "closed" });
+
+  window.__vyberMarks = new Map();
+  const marks = elements.map((element, i) => {
+    const mark = i + 1;
+    const { x, y, width, height } = element.box;
+    const color = COLORS[i % COLORS.length];
+    window.__vyberMarks.set(mark, element.ref);
+
+    const box = document.createElement("div");
+    box.style.cssText = `position:absolute;left:${x}px;top:${y}px;width:${width}px;height:${height}px;` +
+      `border:2px solid ${color};box-sizing:border-box;`;
+    const label = document.createElement("span");
+    label.textContent = mark;
+    // Above the box when there's room, otherwise just inside it
+    label.style.cssText = `position:absolute;left:-2px;${y >= 16 ? "bottom:100%" : "top:0"};` +
+      `background:${color};color:#fff;font:bold 12px/16px sans-serif;padding:0 3px;`;
+    box.append(label);
+    root.append(box);
+
+    const el = refs.get(element.ref)?.deref();
+    return { mark, ...element, selector: el ? selectorFor(el) : null };
+  });
+
+  document.documentElement.append(host);
+  window.__vyberMarkOverlay = host;
+  return marks;
+}
+"##;
+
+const CLEAR_MARKS_JS: &str =
+    "window.__vyberMarkOverlay?.remove(); delete window.__vyberMarkOverlay;";
+
+/// Draw marks over the viewport's interactive elements and return them.
+/// The mark table lives in the ref world next to the refs it points at.
+pub(super) fn draw_marks(tab: &Tab, refs: &RefWorld) -> Result<serde_json::Value, ToolResult> {
+    let snapshot = run_snapshot(tab, refs, true, MAX_MARKS, true)?;
+    refs.evaluate(
+        tab,
+        &format!("({})({})", DRAW_MARKS_JS, snapshot["elements"]),
+    )
+}
+
+/// Remove the overlay. The mark table stays for click_mark.
+pub(super) fn clear_marks(tab: &Tab, refs: &RefWorld) {
+    let _ = refs.lookup(tab, CLEAR_MARKS_JS, true);
+}
+
+impl BrowserAgent {
+    /// Click the element numbered `mark` in the latest marked screenshot
+    pub fn click_mark(
+        &mut self,
+        tab_id: Option<&str>,
+        mark: u32,
+        after: Option<&WaitFor>,
+        switch_to_popup: bool,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+
+        let expression = format!("window.__vyberMarks?.get({}) ?? null", mark);
+        let found = agent_tab.refs.lookup(&agent_tab.tab, &expression, true);
+        let element_ref = match found.map(|object| object.and_then(|o| o.value)) {
+            Ok(Some(serde_json::Value::String(element_ref))) => element_ref,
+            Ok(_) => {
+                return ToolResult::err(
+                    ErrorCode::ElementNotFound,
+                    format!(
+                        "Unknown mark: {}. Take a new screenshot with marks to get current marks.",
+                        mark
+                    ),
+                )
+            }
+            Err(e) => return e,
+        };
+
+        let target = Target::element_ref(element_ref.clone());
+        let mut result = self.click(tab_id, &target, after, switch_to_popup);
+        if let Some(data) = result.data.as_mut().filter(|_| result.success) {
+            data["clicked"] = format!("mark {}", mark).into();
+            data["ref"] = element_ref.into();
+        }
+        result
+    }
+}
@@ -0,0 +1,788 @@
+/**
+ * VybeR Agent Network Capture
+ *
+ * Records each tab's requests and responses from the CDP Network domain so
+ * the agent can see why a page or API call failed. Metadata is always
+ * recorded (bounded to the most recent requests); response bodies are
+ * captured only when turned on, up to a size cap. The log can be queried
+ * by tool and exported as HAR 1.2.
+ */
+
+use super::{BrowserAgent, ErrorCode, ToolResult};
+use headless_chrome::protocol::cdp::types::Event;
+use headless_chrome::protocol::cdp::Network::{self, ResourceTiming};
+use headless_chrome::Tab;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Requests kept per tab before the oldest are dropped
+const MAX_ENTRIES: usize = 1000;
+
+/// Default cap on a captured response body, in bytes
+pub const DEFAULT_MAX_BODY_SIZE: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+struct Body {
+    text: String,
+    base64: bool,
+}
+
+/// One request and, once it arrives, its response
+#[derive(Debug, Clone)]
+struct NetworkEntry {
+    request_id: String,
+    url: String,
+    method: String,
+    resource_type: String,
+    request_headers: Vec<Header>,
+    post_data: Option<String>,
+    /// Seconds since the epoch
+    started_at: f64,
+    /// Monotonic seconds, comparable with the other timestamps
+    started: f64,
+    finished: Option<f64>,
+    status: Option<u32>,
+    status_text: String,
+    response_headers: Vec<Header>,
+    mime_type: String,
+    protocol: Option<String>,
+    remote_ip: Option<String>,
+    from_cache: bool,
+    timing: Option<ResourceTiming>,
+    redirect_url: Option<String>,
+    /// Decoded bytes received
+    data_length: u64,
+    /// Bytes transferred over the wire
+    encoded_length: Option<f64>,
+    error: Option<String>,
+    body: Option<Body>,
+}
+
+impl NetworkEntry {
+    fn duration_ms(&self) -> Option<f64> {
+        self.finished
+            .map(|end| ((end - self.started) * 1000.0).max(0.0))
+    }
+
+    fn failed(&self) -> bool {
+        self.error.is_some() || self.status.is_some_and(|s| s >= 400)
+    }
+
+    fn content_type(&self) -> Option<&str> {
+        self.request_headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct NetworkState {
+    entries: VecDeque<NetworkEntry>,
+    capture_bodies: bool,
+    max_body_size: usize,
+    /// Requests still loading, for network-idle waits. Kept apart from
+    /// `entries` so dropping old entries doesn't lose track of them.
+    in_flight: HashSet<String>,
+    last_activity: Instant,
+}
+
+impl NetworkState {
+    fn entry_mut(&mut self, request_id: &str) -> Option<&mut NetworkEntry> {
+        // Redirects reuse the request ID, so the latest entry is the live one
+        self.entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.request_id == request_id)
+    }
+
+    fn on_event(&mut self, event: &Event) {
+        match event {
+            Event::NetworkRequestWillBeSent(event) => {
+                let params = &event.params;
+                self.last_activity = Instant::now();
+                // Event streams stay open for good, so they never count as loading
+                if params.Type != Some(Network::ResourceType::EventSource) {
+                    self.in_flight.insert(params.request_id.clone());
+                }
+                if let Some(redirect) = &params.redirect_response {
+                    if let Some(entry) = self.entry_mut(&params.request_id) {
+                        apply_response(entry, redirect);
+                        entry.finished = Some(params.timestamp);
+                        entry.redirect_url = Some(params.request.url.clone());
+                    }
+                }
+
+                let request = &params.request;
+                let url = match &request.url_fragment {
+                    Some(fragment) => format!("{}{}", request.url, fragment),
+                    None => request.url.clone(),
+                };
+                self.entries.push_back(NetworkEntry {
+                    request_id: params.request_id.clone(),
+                    url,
+                    method: request.method.clone(),
+                    resource_type: params
+                        .Type
+                        .as_ref()
+                        .map(resource_type_name)
+                        .unwrap_or_else(|| "other".to_string()),
+                    request_headers: headers(&request.headers),
+                    post_data: request.post_data.clone(),
+                    started_at: params.wall_time,
+                    started: params.timestamp,
+                    finished: None,
+                    status: None,
+                    status_text: String::new(),
+                    response_headers: Vec::new(),
+                    mime_type: String::new(),
+                    protocol: None,
+                    remote_ip: None,
+                    from_cache: false,
+                    timing: None,
+                    redirect_url: None,
+                    data_length: 0,
+                    encoded_length: None,
+                    error: None,
+                    body: None,
+                });
+                if self.entries.len() > MAX_ENTRIES {
+                    self.entries.pop_front();
+                }
+            }
+            Event::NetworkResponseReceived(event) => {
+                let params = &event.params;
+                if let Some(entry) = self.entry_mut(&params.request_id) {
+                    apply_response(entry, &params.response);
+                    entry.resource_type = resource_type_name(&params.Type);
+                }
+            }
+            Event::NetworkDataReceived(event) => {
+                let params = &event.params;
+                if let Some(entry) = self.entry_mut(&params.request_id) {
+                    entry.data_length += u64::from(params.data_length);
+                }
+            }
+            Event::NetworkLoadingFinished(event) => {
+                let params = &event.params;
+                self.in_flight.remove(&params.request_id);
+                self.last_activity = Instant::now();
+                if let Some(entry) = self.entry_mut(&params.request_id) {
+                    entry.finished = Some(params.timestamp);
+                    entry.encoded_length = Some(params.encoded_data_length);
+                }
+            }
+            Event::NetworkLoadingFailed(event) => {
+                let params = &event.params;
+                self.in_flight.remove(&params.request_id);
+                self.last_activity = Instant::now();
+                if let Some(entry) = self.entry_mut(&params.request_id) {
+                    entry.finished = Some(params.timestamp);
+                    entry.error = Some(match &params.blocked_reason {
+                        Some(reason) => format!("{} (blocked: {:?})", params.error_text, reason),
+                        None => params.error_text.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn apply_response(entry: &mut NetworkEntry, response: &Network::Response) {
+    entry.status = Some(response.status);
+    entry.status_text = response.status_text.clone();
+    entry.response_headers = headers(&response.headers);
+    entry.mime_type = response.mime_type.clone();
+    entry.protocol = response.protocol.clone();
+    entry.remote_ip = response.remote_ip_address.clone();
+    entry.from_cache = response.from_disk_cache.unwrap_or(false)
+        || response.from_prefetch_cache.unwrap_or(false)
+        || response.from_service_worker.unwrap_or(false);
+    entry.timing = response.timing.clone();
+    entry.encoded_length = Some(response.encoded_data_length);
+}
+
+pub fn resource_type_name(resource_type: &Network::ResourceType) -> String {
+    format!("{:?}", resource_type).to_lowercase()
+}
+
+/// CDP joins repeated headers with newlines
+pub fn headers(headers: &Network::Headers) -> Vec<Header> {
+    let Some(serde_json::Value::Object(map)) = &headers.0 else {
+        return Vec::new();
+    };
+    map.iter()
+        .flat_map(|(name, value)| {
+            let value = value
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| value.to_string());
+            value
+                .split('\n')
+                .map(|v| Header {
+                    name: name.clone(),
+                    value: v.to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// A tab's network log, shared with the CDP event listener that fills it
+#[derive(Clone)]
+pub struct NetworkLog(Arc<Mutex<NetworkState>>);
+
+impl NetworkLog {
+    /// Enable the Network domain on `tab` and start recording
+    pub fn attach(tab: &Tab) -> Self {
+        let log = Self(Arc::new(Mutex::new(NetworkState {
+            entries: VecDeque::new(),
+            capture_bodies: false,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            in_flight: HashSet::new(),
+            last_activity: Instant::now(),
+        })));
+
+        let listener = log.clone();
+        let _ = tab.add_event_listener(Arc::new(move |event: &Event| {
+            listener.lock().on_event(event);
+        }));
+
+        // Bodies are fetched once loading finishes, on the tab's event thread
+        let bodies = log.clone();
+        let _ =
+            tab.register_response_handling(
+                "vyber-network-log",
+                Box::new(
+                    move |params: Network::events::ResponseReceivedEventParams,
+                          fetch_body: &dyn Fn() -> Result<
+                        Network::GetResponseBodyReturnObject,
+                        _,
+                    >| {
+                        let max_body_size = {
+                            let state = bodies.lock();
+                            if !state.capture_bodies {
+                                return;
+                            }
+                            state.max_body_size
+                        };
+                        if params.response.encoded_data_length > max_body_size as f64 {
+                            return;
+                        }
+                        let Ok(body) = fetch_body() else {
+                            return;
+                        };
+                        if body.body.len() > max_body_size {
+                            return;
+                        }
+                        if let Some(entry) = bodies.lock().entry_mut(&params.request_id) {
+                            entry.body = Some(Body {
+                                text: body.body,
+                                base64: body.base_64_encoded,
+                            });
+                        }
+                    },
+                ),
+            );
+
+        log
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, NetworkState> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// How long the tab has had no requests loading, or None while some are
+    pub fn idle_for(&self) -> Option<Duration> {
+        let state = self.lock();
+        state
+            .in_flight
+            .is_empty()
+            .then(|| state.last_activity.elapsed())
+    }
+}
+
+/// Which recorded requests a query or export covers
+#[derive(Debug, Default)]
+pub struct NetworkFilter {
+    pub url_pattern: Option<Regex>,
+    pub resource_type: Option<String>,
+    pub status: Option<u32>,
+    pub failed_only: bool,
+}
+
+impl NetworkFilter {
+    fn matches(&self, entry: &NetworkEntry) -> bool {
+        self.url_pattern
+            .as_ref()
+            .is_none_or(|p| p.is_match(&entry.url))
+            && self
+                .resource_type
+                .as_ref()
+                .is_none_or(|t| t.eq_ignore_ascii_case(&entry.resource_type))
+            && self.status.is_none_or(|s| entry.status == Some(s))
+            && (!self.failed_only || entry.failed())
+    }
+}
+
+/// Compile a user-supplied URL pattern
+pub fn url_pattern(pattern: Option<&str>) -> Result<Option<Regex>, ToolResult> {
+    pattern.map(Regex::new).transpose().map_err(|e| {
+        ToolResult::err(
+            ErrorCode::InvalidArgument,
+            format!("Invalid url_pattern: {}", e),
+        )
+    })
+}
+
+impl BrowserAgent {
+    /// Turn response body capture on or off, or clear the log
+    pub fn network_capture(
+        &self,
+        tab_id: Option<&str>,
+        capture_bodies: Option<bool>,
+        max_body_size: Option<usize>,
+        clear: bool,
+    ) -> ToolResult {
+        let tab = match self.get_agent_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let mut state = tab.network.lock();
+        if let Some(capture_bodies) = capture_bodies {
+            state.capture_bodies = capture_bodies;
+        }
+        if let Some(max_body_size) = max_body_size {
+            state.max_body_size = max_body_size;
+        }
+        let cleared = if clear {
+            std::mem::take(&mut state.entries).len()
+        } else {
+            0
+        };
+
+        ToolResult::ok(serde_json::json!({
+            "tab_id": tab.id,
+            "capture_bodies": state.capture_bodies,
+            "max_body_size": state.max_body_size,
+            "recorded": state.entries.len(),
+            "cleared": cleared
+        }))
+    }
+
+    /// List recorded requests, most recent last
+    pub fn network_requests(
+        &self,
+        tab_id: Option<&str>,
+        filter: &NetworkFilter,
+        limit: usize,
+        include_headers: bool,
+        include_bodies: bool,
+    ) -> ToolResult {
+        let tab = match self.get_agent_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let state = tab.network.lock();
+        let matching: Vec<&NetworkEntry> =
+            state.entries.iter().filter(|e| filter.matches(e)).collect();
+        let skip = matching.len().saturating_sub(limit);
+
+        let requests: Vec<serde_json::Value> = matching[skip..]
+            .iter()
+            .map(|entry| {
+                let mut request = serde_json::json!({
+                    "request_id": entry.request_id,
+                    "url": entry.url,
+                    "method": entry.method,
+                    "type": entry.resource_type,
+                    "status": entry.status,
+                    "status_text": entry.status_text,
+                    "mime_type": entry.mime_type,
+                    "duration_ms": entry.duration_ms().map(|ms| ms.round()),
+                    "size": entry.encoded_length.map(|len| len as u64),
+                    "from_cache": entry.from_cache,
+                    "pending": entry.finished.is_none()
+                });
+                if let Some(error) = &entry.error {
+                    request["error"] = error.clone().into();
+                }
+                if let Some(redirect_url) = &entry.redirect_url {
+                    request["redirect_url"] = redirect_url.clone().into();
+                }
+                if include_headers {
+                    request["request_headers"] = serde_json::json!(entry.request_headers);
+                    request["response_headers"] = serde_json::json!(entry.response_headers);
+                }
+                if include_bodies {
+                    if let Some(post_data) = &entry.post_data {
+                        request["request_body"] = post_data.clone().into();
+                    }
+                    if let Some(body) = &entry.body {
+                        request["response_body"] = body.text.clone().into();
+                        request["response_body_base64"] = body.base64.into();
+                    }
+                }
+                request
+            })
+            .collect();
+
+        ToolResult::ok(serde_json::json!({
+            "requests": requests,
+            "count": requests.len(),
+            "total": matching.len(),
+            "capture_bodies": state.capture_bodies
+        }))
+    }
+
+    /// Export recorded requests as a HAR 1.2 log, returned inline or
+    /// written to a managed file when `save` is set
+    pub fn export_har(
+        &self,
+        tab_id: Option<&str>,
+        filter: &NetworkFilter,
+        save: bool,
+    ) -> ToolResult {
+        let tab = match self.get_agent_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let entries: Vec<serde_json::Value> = tab
+            .network
+            .lock()
+            .entries
+            .iter()
+            .filter(|e| filter.matches(e))
+            .map(har_entry)
+            .collect();
+        let count = entries.len();
+        let har = serde_json::json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": "VybeR Agent",
+                    "version": env!("CARGO_PKG_VERSION")
+                },
+                "pages": [],
+                "entries": entries
+            }
+        });
+
+        if !save {
+            return ToolResult::ok(serde_json::json!({
+                "har": har,
+                "entries": count
+            }));
+        }
+        let written = serde_json::to_vec_pretty(&har)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| self.files.write(&bytes, "har").map_err(|e| e.to_string()));
+        match written {
+            Ok((id, path)) => ToolResult::ok(serde_json::json!({
+                "file_id": id,
+                "path": path.display().to_string(),
+                "entries": count
+            })),
+            Err(e) => ToolResult::err(
+                ErrorCode::Internal,
+                format!("Failed to write HAR file: {}", e),
+            ),
+        }
+    }
+}
+
+fn har_entry(entry: &NetworkEntry) -> serde_json::Value {
+    let query_string: Vec<Header> = url::Url::parse(&entry.url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| Header {
+                    name: name.into_owned(),
+                    value: value.into_owned(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let http_version = http_version(entry.protocol.as_deref());
+    let timings = har_timings(entry);
+    let time: f64 = ["blocked", "dns", "connect", "send", "wait", "receive"]
+        .iter()
+        .filter_map(|phase| timings[phase].as_f64())
+        .filter(|ms| *ms > 0.0)
+        .sum();
+
+    let mut request = serde_json::json!({
+        "method": entry.method,
+        "url": entry.url,
+        "httpVersion": http_version,
+        "cookies": [],
+        "headers": entry.request_headers,
+        "queryString": query_string,
+        "headersSize": -1,
+        "bodySize": entry.post_data.as_ref().map_or(0, |data| data.len() as i64)
+    });
+    if let Some(post_data) = &entry.post_data {
+        request["postData"] = serde_json::json!({
+            "mimeType": entry.content_type().unwrap_or_default(),
+            "text": post_data
+        });
+    }
+
+    let mut content = serde_json::json!({
+        "size": entry.data_length,
+        "mimeType": entry.mime_type
+    });
+    if let Some(body) = &entry.body {
+        content["text"] = body.text.clone().into();
+        if body.base64 {
+            content["encoding"] = "base64".into();
+        }
+    }
+
+    let mut har = serde_json::json!({
+        "startedDateTime": iso8601(entry.started_at),
+        "time": time.round(),
+        "request": request,
+        "response": {
+            "status": entry.status.unwrap_or(0),
+            "statusText": entry.status_text,
+            "httpVersion": http_version,
+            "cookies": [],
+            "headers": entry.response_headers,
+            "content": content,
+            "redirectURL": entry.redirect_url.clone().unwrap_or_default(),
+            "headersSize": -1,
+            "bodySize": entry.encoded_length.map_or(-1, |len| len as i64)
+        },
+        "cache": {},
+        "timings": timings,
+        "_resourceType": entry.resource_type
+    });
+    if let Some(ip) = &entry.remote_ip {
+        har["serverIPAddress"] = ip.trim_start_matches('[').trim_end_matches(']').into();
+    }
+    if let Some(error) = &entry.error {
+        har["_error"] = error.clone().into();
+    }
+    har
+}
+
+fn http_version(protocol: Option<&str>) -> String {
+    match protocol {
+        Some("h2") => "HTTP/2".to_string(),
+        Some("h3") => "HTTP/3".to_string(),
+        Some(protocol) => protocol.to_uppercase(),
+        None => String::new(),
+    }
+}
+
+/// HAR timing phases in milliseconds, -1 where a phase didn't happen
+fn har_timings(entry: &NetworkEntry) -> serde_json::Value {
+    let total = entry.duration_ms().unwrap_or(0.0);
+    let Some(t) = &entry.timing else {
+        return serde_json::json!({
+            "blocked": -1, "dns": -1, "connect": -1, "ssl": -1,
+            "send": 0, "wait": 0, "receive": total
+        });
+    };
+
+    let span = |start: f64, end: f64| if start >= 0.0 { end - start } else { -1.0 };
+    let blocked = [t.dns_start, t.connect_start, t.send_start]
+        .into_iter()
+        .find(|ms| *ms >= 0.0)
+        .unwrap_or(0.0);
+    // Offset of the request's own start from when it was first issued
+    let queued = ((t.request_time - entry.started) * 1000.0).max(0.0);
+    let receive = entry
+        .finished
+        .map(|end| ((end - t.request_time) * 1000.0 - t.receive_headers_end).max(0.0))
+        .unwrap_or(0.0);
+
+    serde_json::json!({
+        "blocked": queued + blocked,
+        "dns": span(t.dns_start, t.dns_end),
+        "connect": span(t.connect_start, t.connect_end),
+        "ssl": span(t.ssl_start, t.ssl_end),
+        "send": (t.send_end - t.send_start).max(0.0),
+        "wait": (t.receive_headers_end - t.send_end).max(0.0),
+        "receive": receive
+    })
+}
+
+/// Format seconds since the epoch as an ISO 8601 UTC timestamp
+fn iso8601(secs: f64) -> String {
+    let millis = (secs * 1000.0).round() as i64;
+    let (days, ms_of_day) = (millis.div_euclid(86_400_000), millis.rem_euclid(86_400_000));
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A request issued at 100s (monotonic) that finished at 100.5s
+    fn entry(timing: Option<ResourceTiming>) -> NetworkEntry {
+        NetworkEntry {
+            request_id: "1".to_string(),
+            url: "https://example.test/".to_string(),
+            method: "GET".to_string(),
+            resource_type: "document".to_string(),
+            request_headers: Vec::new(),
+            post_data: None,
+            started_at: 1_700_000_000.0,
+            started: 100.0,
+            finished: Some(100.5),
+            status: Some(200),
+            status_text: "OK".to_string(),
+            response_headers: Vec::new(),
+            mime_type: "text/html".to_string(),
+            protocol: Some("h2".to_string()),
+            remote_ip: None,
+            from_cache: false,
+            timing,
+            redirect_url: None,
+            data_length: 0,
+            encoded_length: None,
+            error: None,
+            body: None,
+        }
+    }
+
+    /// Timing for a request sent 7.8125ms after it was issued, with phase
+    /// offsets in milliseconds from then
+    fn timing(phases: serde_json::Value) -> ResourceTiming {
+        let mut timing = serde_json::json!({
+            "requestTime": 100.0078125,
+            "proxyStart": -1, "proxyEnd": -1,
+            "dnsStart": -1, "dnsEnd": -1,
+            "connectStart": -1, "connectEnd": -1,
+            "sslStart": -1, "sslEnd": -1,
+            "workerStart": -1, "workerReady": -1,
+            "workerFetchStart": -1, "workerRespondWithSettled": -1,
+            "sendStart": 0, "sendEnd": 0,
+            "pushStart": 0, "pushEnd": 0,
+            "receiveHeadersStart": 0, "receiveHeadersEnd": 0
+        });
+        for (key, value) in phases.as_object().unwrap() {
+            timing[key] = value.clone();
+        }
+        serde_json::from_value(timing).unwrap()
+    }
+
+    /// HAR requires the non-negative phases (ssl aside, as it's part of
+    /// connect) to add up to the entry's total time
+    fn phase_total(timings: &serde_json::Value) -> f64 {
+        ["blocked", "dns", "connect", "send", "wait", "receive"]
+            .iter()
+            .map(|phase| timings[*phase].as_f64().unwrap())
+            .filter(|ms| *ms >= 0.0)
+            .sum()
+    }
+
+    #[test]
+    fn iso8601_at_the_epoch() {
+        assert_eq!(iso8601(0.0), "1970-01-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn iso8601_on_leap_days() {
+        assert_eq!(iso8601(1_709_210_096.789), "2024-02-29T12:34:56.789Z");
+        assert_eq!(iso8601(951_782_400.0), "2000-02-29T00:00:00.000Z");
+    }
+
+    #[test]
+    fn iso8601_before_1970() {
+        assert_eq!(iso8601(-1.5), "1969-12-31T23:59:58.500Z");
+        assert_eq!(iso8601(-2_208_988_800.0), "1900-01-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn http_versions() {
+        assert_eq!(http_version(Some("h2")), "HTTP/2");
+        assert_eq!(http_version(Some("h3")), "HTTP/3");
+        assert_eq!(http_version(Some("http/1.1")), "HTTP/1.1");
+        assert_eq!(http_version(None), "");
+    }
+
+    #[test]
+    fn timings_on_a_reused_connection() {
+        let entry = entry(Some(timing(serde_json::json!({
+            "sendStart": 2, "sendEnd": 3, "receiveHeadersEnd": 150
+        }))));
+        let timings = har_timings(&entry);
+
+        assert_eq!(
+            timings,
+            serde_json::json!({
+                "blocked": 9.8125, "dns": -1.0, "connect": -1.0, "ssl": -1.0,
+                "send": 1.0, "wait": 147.0, "receive": 342.1875
+            })
+        );
+        assert_eq!(phase_total(&timings), entry.duration_ms().unwrap());
+    }
+
+    #[test]
+    fn timings_on_a_new_connection() {
+        let entry = entry(Some(timing(serde_json::json!({
+            "dnsStart": 1, "dnsEnd": 5,
+            "connectStart": 5, "connectEnd": 40,
+            "sslStart": 20, "sslEnd": 40,
+            "sendStart": 40, "sendEnd": 41, "receiveHeadersEnd": 100
+        }))));
+        let timings = har_timings(&entry);
+
+        assert_eq!(
+            timings,
+            serde_json::json!({
+                "blocked": 8.8125, "dns": 4.0, "connect": 35.0, "ssl": 20.0,
+                "send": 1.0, "wait": 59.0, "receive": 392.1875
+            })
+        );
+        assert_eq!(phase_total(&timings), entry.duration_ms().unwrap());
+    }
+
+    #[test]
+    fn timings_without_resource_timing() {
+        let timings = har_timings(&entry(None));
+        assert_eq!(
+            timings,
+            serde_json::json!({
+                "blocked": -1, "dns": -1, "connect": -1, "ssl": -1,
+                "send": 0, "wait": 0, "receive": 500.0
+            })
+        );
+    }
+}
@@ -0,0 +1,308 @@
+/**
+ * VybeR Agent Paging
+ *
+ * Splits long extracted text into chunks so agents can read a page in full
+ * over several calls. Chunks are measured in characters (never splitting a
+ * UTF-8 sequence), prefer to end at a paragraph or sentence break, and can
+ * be sized by an estimated token budget instead.
+ */
+
+use super::{ErrorCode, ToolResult};
+use serde::{Deserialize, Serialize};
+
+/// Default chunk size in characters
+pub const DEFAULT_CHUNK_CHARS: usize = 8000;
+
+/// Rough characters-per-token ratio for English-like text
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Which chunk of a long extraction to return, and how big chunks are
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PageRequest {
+    /// Chunk size in characters
+    pub max_length: Option<usize>,
+    /// Chunk size as an estimated token count (overrides max_length)
+    pub max_tokens: Option<usize>,
+    /// Zero-based chunk index
+    pub chunk: Option<usize>,
+    /// `next_cursor` from a previous call (overrides chunk and sizes)
+    pub cursor: Option<String>,
+}
+
+/// One chunk plus what the caller needs to fetch the rest
+#[derive(Debug, Serialize)]
+pub struct Chunk {
+    #[serde(skip)]
+    pub content: String,
+    pub chunk: usize,
+    pub total_chunks: usize,
+    /// Characters in this chunk
+    pub length: usize,
+    /// Characters in the whole text
+    pub total_length: usize,
+    pub estimated_tokens: usize,
+    /// Pass back as `cursor` to get the next chunk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl Chunk {
+    /// Add the chunk metadata to `data`, with the content under `field`
+    pub fn merge_into(self, mut data: serde_json::Value, field: &str) -> serde_json::Value {
+        if let serde_json::Value::Object(meta) = serde_json::json!(&self) {
+            for (key, value) in meta {
+                data[key] = value;
+            }
+        }
+        data[field] = self.content.into();
+        data
+    }
+}
+
+/// Rough token count for `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Cursors look like `2:8000` (chunk index and chunk size in characters)
+fn parse_cursor(cursor: &str) -> Option<(usize, usize)> {
+    let (chunk, size) = cursor.split_once(':')?;
+    Some((chunk.parse().ok()?, size.parse().ok()?))
+}
+
+/// Return the requested chunk of `text`
+pub fn paginate(text: &str, request: &PageRequest) -> Result<Chunk, ToolResult> {
+    let (index, chunk_chars) = match &request.cursor {
+        Some(cursor) => parse_cursor(cursor).ok_or_else(|| {
+            ToolResult::err(
+                ErrorCode::InvalidArgument,
+                format!("Invalid cursor: {}", cursor),
+            )
+        })?,
+        None => {
+            let chunk_chars = request
+                .max_tokens
+                .map(|tokens| tokens.saturating_mul(CHARS_PER_TOKEN))
+                .or(request.max_length)
+                .unwrap_or(DEFAULT_CHUNK_CHARS);
+            (request.chunk.unwrap_or(0), chunk_chars)
+        }
+    };
+    if chunk_chars == 0 {
+        return Err(ToolResult::err(
+            ErrorCode::InvalidArgument,
+            "Chunk size must be greater than zero",
+        ));
+    }
+
+    let bounds = chunk_bounds(text, chunk_chars);
+    let (start, end) = *bounds.get(index).ok_or_else(|| {
+        ToolResult::err(
+            ErrorCode::InvalidArgument,
+            format!(
+                "Chunk {} out of range (text has {} chunks)",
+                index,
+                bounds.len()
+            ),
+        )
+    })?;
+
+    let content = text[start..end].to_string();
+    let next_cursor = (index + 1 < bounds.len()).then(|| format!("{}:{}", index + 1, chunk_chars));
+    Ok(Chunk {
+        chunk: index,
+        total_chunks: bounds.len(),
+        length: content.chars().count(),
+        total_length: text.chars().count(),
+        estimated_tokens: estimate_tokens(&content),
+        next_cursor,
+        content,
+    })
+}
+
+/// Byte ranges of each chunk. Always returns at least one (possibly empty) chunk.
+fn chunk_bounds(text: &str, chunk_chars: usize) -> Vec<(usize, usize)> {
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    loop {
+        let rest = &text[start..];
+        let limit = match rest.char_indices().nth(chunk_chars) {
+            Some((offset, _)) => start + offset,
+            None => {
+                bounds.push((start, text.len()));
+                return bounds;
+            }
+        };
+
+        let end = break_point(text, start, limit);
+        bounds.push((start, end));
+        start = end;
+    }
+}
+
+/// Where to end a chunk that may run up to `limit`: after the last
+/// paragraph break, line break, sentence end or space in the second half
+/// of the window, or at `limit` itself if there is none
+fn break_point(text: &str, start: usize, limit: usize) -> usize {
+    let window = &text[start..limit];
+    let half = window.len() / 2;
+    for separator in ["\n\n", "\n", ". ", "? ", "! ", " "] {
+        if let Some(at) = window.rfind(separator) {
+            if at >= half {
+                return start + at + separator.len();
+            }
+        }
+    }
+    limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(max_length: usize, chunk: usize) -> PageRequest {
+        PageRequest {
+            max_length: Some(max_length),
+            chunk: Some(chunk),
+            ..PageRequest::default()
+        }
+    }
+
+    fn cursor(cursor: &str) -> PageRequest {
+        PageRequest {
+            cursor: Some(cursor.to_string()),
+            ..PageRequest::default()
+        }
+    }
+
+    /// Every chunk of `text`, following cursors from the first
+    fn all_chunks(text: &str, max_length: usize) -> Vec<Chunk> {
+        let mut chunks = vec![paginate(text, &request(max_length, 0)).unwrap()];
+        while let Some(next) = chunks.last().unwrap().next_cursor.clone() {
+            chunks.push(paginate(text, &cursor(&next)).unwrap());
+        }
+        chunks
+    }
+
+    fn error_code(result: Result<Chunk, ToolResult>) -> ErrorCode {
+        result.unwrap_err().error.unwrap().code
+    }
+
+    #[test]
+    fn multi_byte_text_splits_on_char_boundaries() {
+        let text = "héllo wörld ünïcödé ".repeat(20);
+        let chunks = all_chunks(&text, 7);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.length <= 7);
+            assert_eq!(chunk.length, chunk.content.chars().count());
+        }
+        let joined: String = chunks.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(joined, text);
+    }
+
+    #[test]
+    fn emoji_are_never_split() {
+        // No separators, so every break lands exactly on the limit
+        let text = "👍🏽🎉🇯🇵😀".repeat(9);
+        let chunks = all_chunks(&text, 5);
+
+        let joined: String = chunks.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(joined, text);
+        assert!(chunks.iter().all(|c| c.length <= 5));
+        assert_eq!(chunks[0].total_length, text.chars().count());
+    }
+
+    #[test]
+    fn chunks_prefer_paragraph_breaks() {
+        let text = format!("{}\n\n{}", "a".repeat(60), "b".repeat(60));
+        let first = paginate(&text, &request(100, 0)).unwrap();
+
+        assert_eq!(first.content, format!("{}\n\n", "a".repeat(60)));
+        assert_eq!(first.total_chunks, 2);
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let text = "word ".repeat(100);
+        let first = paginate(&text, &request(40, 0)).unwrap();
+        let next = first.next_cursor.unwrap();
+        assert_eq!(parse_cursor(&next), Some((1, 40)));
+
+        let by_cursor = paginate(&text, &cursor(&next)).unwrap();
+        let by_index = paginate(&text, &request(40, 1)).unwrap();
+        assert_eq!(by_cursor.chunk, 1);
+        assert_eq!(by_cursor.content, by_index.content);
+    }
+
+    #[test]
+    fn cursor_overrides_chunk_and_size() {
+        let text = "word ".repeat(100);
+        let mut request = cursor("2:40");
+        request.max_length = Some(10);
+        request.chunk = Some(0);
+
+        let chunk = paginate(&text, &request).unwrap();
+        assert_eq!(chunk.chunk, 2);
+        assert_eq!(chunk.next_cursor.as_deref(), Some("3:40"));
+    }
+
+    #[test]
+    fn last_chunk_has_no_cursor() {
+        let chunk = paginate("short text", &PageRequest::default()).unwrap();
+        assert_eq!(chunk.total_chunks, 1);
+        assert!(chunk.next_cursor.is_none());
+    }
+
+    #[test]
+    fn empty_text_is_one_empty_chunk() {
+        let chunk = paginate("", &PageRequest::default()).unwrap();
+        assert_eq!(chunk.total_chunks, 1);
+        assert_eq!(chunk.content, "");
+    }
+
+    #[test]
+    fn max_tokens_sizes_chunks() {
+        let request = PageRequest {
+            max_tokens: Some(10),
+            max_length: Some(5),
+            ..PageRequest::default()
+        };
+        let chunk = paginate(&"x".repeat(100), &request).unwrap();
+        assert_eq!(chunk.length, 10 * CHARS_PER_TOKEN);
+    }
+
+    #[test]
+    fn out_of_range_chunks_are_rejected() {
+        let text = "word ".repeat(10);
+        assert_eq!(
+            error_code(paginate(&text, &request(20, 5))),
+            ErrorCode::InvalidArgument
+        );
+        assert_eq!(
+            error_code(paginate(&text, &cursor("99:20"))),
+            ErrorCode::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        for bad in ["", "1", "a:b", "1:", ":20", "-1:20", "1:20:3", "1:0"] {
+            assert_eq!(
+                error_code(paginate("text", &cursor(bad))),
+                ErrorCode::InvalidArgument,
+                "cursor {:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn zero_chunk_size_is_rejected() {
+        assert_eq!(
+            error_code(paginate("text", &request(0, 0))),
+            ErrorCode::InvalidArgument
+        );
+    }
+}
@@ -0,0 +1,216 @@
+/**
+ * VybeR Agent PDF Export
+ *
+ * Prints a page to PDF through Page.printToPDF, for archiving what the
+ * agent saw. Paper sizes and margins take inches or CSS-style lengths.
+ * Like screenshots, the PDF comes back inline or goes to a file (see
+ * `files`). Chrome only prints from headless sessions.
+ */
+
+use super::files::Output;
+use super::{BrowserAgent, ErrorCode, ToolResult};
+use headless_chrome::types::PrintToPdfOptions;
+use serde::Deserialize;
+
+/// Named paper sizes, in inches
+const PAPER_SIZES: &[(&str, f64, f64)] = &[
+    ("letter", 8.5, 11.0),
+    ("legal", 8.5, 14.0),
+    ("tabloid", 11.0, 17.0),
+    ("ledger", 17.0, 11.0),
+    ("a0", 33.1, 46.8),
+    ("a1", 23.4, 33.1),
+    ("a2", 16.54, 23.4),
+    ("a3", 11.7, 16.54),
+    ("a4", 8.27, 11.7),
+    ("a5", 5.83, 8.27),
+    ("a6", 4.13, 5.83),
+];
+
+/// Stands in for a header or footer that wasn't given, since Chrome would
+/// otherwise print its default date and title
+const EMPTY_TEMPLATE: &str = "<span></span>";
+
+/// A length in inches. Tool input gives a number of inches or a string
+/// with a unit: `px`, `in`, `cm` or `mm`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "LengthInput")]
+pub struct Inches(f64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LengthInput {
+    Number(f64),
+    Text(String),
+}
+
+impl TryFrom<LengthInput> for Inches {
+    type Error = String;
+
+    fn try_from(input: LengthInput) -> Result<Self, Self::Error> {
+        let inches = match input {
+            LengthInput::Number(inches) => inches,
+            LengthInput::Text(text) => {
+                let text = text.trim();
+                let split = text
+                    .find(|c: char| c.is_ascii_alphabetic())
+                    .unwrap_or(text.len());
+                let (number, unit) = text.split_at(split);
+                let number: f64 = number
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid length: {}", text))?;
+                match unit.to_ascii_lowercase().as_str() {
+                    "" | "in" => number,
+                    "px" => number / 96.0,
+                    "cm" => number / 2.54,
+                    "mm" => number / 25.4,
+                    _ => return Err(format!("unknown unit in length: {}", text)),
+                }
+            }
+        };
+        if !(inches >= 0.0 && inches.is_finite()) {
+            return Err("lengths must not be negative".to_string());
+        }
+        Ok(Self(inches))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Margins {
+    pub top: Option<Inches>,
+    pub right: Option<Inches>,
+    pub bottom: Option<Inches>,
+    pub left: Option<Inches>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "PdfInput")]
+pub struct PdfOptions {
+    /// Paper width and height in inches, or Chrome's default (Letter)
+    pub paper: Option<(f64, f64)>,
+    pub margins: Margins,
+    pub landscape: bool,
+    pub print_background: bool,
+    pub scale: Option<f64>,
+    pub header_template: Option<String>,
+    pub footer_template: Option<String>,
+    /// Pages to print, e.g. `1-5, 8`
+    pub page_ranges: Option<String>,
+    /// Let CSS `@page` sizes override the paper size
+    pub prefer_css_page_size: bool,
+    pub output: Output,
+}
+
+/// The option fields as they appear in tool input
+#[derive(Deserialize)]
+struct PdfInput {
+    format: Option<String>,
+    width: Option<Inches>,
+    height: Option<Inches>,
+    #[serde(default)]
+    margin: Margins,
+    #[serde(default)]
+    landscape: bool,
+    #[serde(default)]
+    print_background: bool,
+    scale: Option<f64>,
+    header_template: Option<String>,
+    footer_template: Option<String>,
+    page_ranges: Option<String>,
+    #[serde(default)]
+    prefer_css_page_size: bool,
+    #[serde(default)]
+    save: bool,
+}
+
+impl TryFrom<PdfInput> for PdfOptions {
+    type Error = String;
+
+    fn try_from(input: PdfInput) -> Result<Self, Self::Error> {
+        let paper = match (input.format, input.width, input.height) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+                return Err("give either format or width and height, not both".to_string())
+            }
+            (Some(format), None, None) => {
+                let (_, width, height) = PAPER_SIZES
+                    .iter()
+                    .find(|(name, _, _)| name.eq_ignore_ascii_case(&format))
+                    .ok_or_else(|| format!("unknown paper format: {}", format))?;
+                Some((*width, *height))
+            }
+            (None, Some(Inches(width)), Some(Inches(height))) => Some((width, height)),
+            (None, None, None) => None,
+            (None, _, _) => return Err("give both width and height".to_string()),
+        };
+
+        if let Some(scale) = input.scale {
+            if !(0.1..=2.0).contains(&scale) {
+                return Err("scale must be between 0.1 and 2".to_string());
+            }
+        }
+
+        Ok(Self {
+            paper,
+            margins: input.margin,
+            landscape: input.landscape,
+            print_background: input.print_background,
+            scale: input.scale,
+            header_template: input.header_template,
+            footer_template: input.footer_template,
+            page_ranges: input.page_ranges,
+            prefer_css_page_size: input.prefer_css_page_size,
+            output: Output::new(input.save),
+        })
+    }
+}
+
+impl PdfOptions {
+    fn params(&self) -> PrintToPdfOptions {
+        let header_footer = self.header_template.is_some() || self.footer_template.is_some();
+        let template = |template: &Option<String>| {
+            header_footer.then(|| template.as_deref().unwrap_or(EMPTY_TEMPLATE).to_string())
+        };
+        PrintToPdfOptions {
+            landscape: Some(self.landscape),
+            display_header_footer: Some(header_footer),
+            print_background: Some(self.print_background),
+            scale: self.scale,
+            paper_width: self.paper.map(|(width, _)| width),
+            paper_height: self.paper.map(|(_, height)| height),
+            margin_top: self.margins.top.map(|m| m.0),
+            margin_bottom: self.margins.bottom.map(|m| m.0),
+            margin_left: self.margins.left.map(|m| m.0),
+            margin_right: self.margins.right.map(|m| m.0),
+            page_ranges: self.page_ranges.clone(),
+            header_template: template(&self.header_template),
+            footer_template: template(&self.footer_template),
+            prefer_css_page_size: Some(self.prefer_css_page_size),
+            ..Default::default()
+        }
+    }
+}
+
+impl BrowserAgent {
+    /// Print the page to PDF, returned inline or written to a file
+    pub fn print_pdf(&self, tab_id: Option<&str>, options: &PdfOptions) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let bytes = match tab.print_to_pdf(Some(options.params())) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return self.call.check().unwrap_or_else(|| {
+                    ToolResult::browser_err(ErrorCode::Internal, "Printing to PDF failed", &*e)
+                })
+            }
+        };
+
+        match self.deliver(&options.output, "pdf", &bytes, "application/pdf", "pdf") {
+            Ok(result) => ToolResult::ok(result.into()),
+            Err(e) => e,
+        }
+    }
+}
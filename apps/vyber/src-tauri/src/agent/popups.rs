@@ -0,0 +1,151 @@
+/**
+ * VybeR Agent Popups
+ *
+ * Links with `target=_blank`, `window.open` and OAuth sign-in flows open
+ * new tabs or windows the agent didn't ask for. Chrome reports each new
+ * page through Target.targetCreated, which headless_chrome turns into a
+ * tab on the Browser. A click compares the browser's pages before and
+ * after, adopts any new ones as agent tabs and reports them. Pages that
+ * open some other way (timers, scripts) stay orphaned until listed and
+ * adopted with list_popups and adopt_popup.
+ */
+
+use super::{AgentTab, BrowserAgent, ErrorCode, ToolResult};
+use headless_chrome::Tab;
+use std::collections::HashSet;
+use std::sync::{Arc, PoisonError};
+
+impl BrowserAgent {
+    /// Target IDs of every page in the browser, the agent's or not
+    pub(super) fn page_targets(&self) -> HashSet<String> {
+        self.browser
+            .get_tabs()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|tab| tab.get_target_id().clone())
+            .collect()
+    }
+
+    /// Pages in the browser that aren't agent tabs, oldest first
+    fn orphans(&self) -> Vec<Arc<Tab>> {
+        self.browser
+            .get_tabs()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|tab| {
+                let target_id = tab.get_target_id();
+                !self.ignored_targets.contains(target_id)
+                    && !self.tabs.iter().any(|t| t.tab.get_target_id() == target_id)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The agent tab that opened `tab`, if it was opened by one
+    fn opener_of(&self, tab: &Tab) -> Option<String> {
+        let opener_id = tab.get_target_info().ok()?.opener_id?;
+        self.tabs
+            .iter()
+            .find(|t| *t.tab.get_target_id() == opener_id)
+            .map(|t| t.id.clone())
+    }
+
+    /// Make `tab` an agent tab, returning its description
+    fn adopt(&mut self, tab: Arc<Tab>, activate: bool) -> serde_json::Value {
+        let opener_tab_id = self.opener_of(&tab);
+        let id = format!("t{}", self.next_tab_id);
+        self.next_tab_id += 1;
+
+        let info = serde_json::json!({
+            "tab_id": id,
+            "url": tab.get_url(),
+            "title": tab.get_title().unwrap_or_default(),
+            "opener_tab_id": opener_tab_id
+        });
+        if activate {
+            let _ = tab.activate();
+            self.active_tab = id.clone();
+        }
+        self.tabs
+            .push(AgentTab::new(id, tab, &self.interception, &self.dialogs));
+        info
+    }
+
+    /// Adopt the pages opened since `before` was taken and add them to
+    /// `result` as `popups`, switching to the first one if `activate` is set
+    pub(super) fn adopt_popups(
+        &mut self,
+        mut result: ToolResult,
+        before: &HashSet<String>,
+        activate: bool,
+    ) -> ToolResult {
+        let opened: Vec<Arc<Tab>> = self
+            .orphans()
+            .into_iter()
+            .filter(|tab| !before.contains(tab.get_target_id()))
+            .collect();
+        if opened.is_empty() {
+            return result;
+        }
+
+        let popups: Vec<serde_json::Value> = opened
+            .into_iter()
+            .enumerate()
+            .map(|(i, tab)| self.adopt(tab, activate && i == 0))
+            .collect();
+        if let Some(data) = result.data.as_mut() {
+            data["popups"] = popups.into();
+            data["active_tab"] = self.active_tab.clone().into();
+        }
+        result
+    }
+
+    /// List pages that opened on their own and aren't agent tabs yet
+    pub fn list_popups(&self) -> ToolResult {
+        let popups: Vec<serde_json::Value> = self
+            .orphans()
+            .iter()
+            .map(|tab| {
+                serde_json::json!({
+                    "target_id": tab.get_target_id(),
+                    "url": tab.get_url(),
+                    "title": tab.get_title().unwrap_or_default(),
+                    "opener_tab_id": self.opener_of(tab)
+                })
+            })
+            .collect();
+
+        ToolResult::ok(serde_json::json!({
+            "popups": popups,
+            "count": popups.len()
+        }))
+    }
+
+    /// Make an orphaned page (the most recent if no target ID is given) an
+    /// agent tab
+    pub fn adopt_popup(&mut self, target_id: Option<&str>, activate: bool) -> ToolResult {
+        let orphans = self.orphans();
+        let tab = match target_id {
+            Some(target_id) => orphans.into_iter().find(|t| t.get_target_id() == target_id),
+            None => orphans.into_iter().last(),
+        };
+        let Some(tab) = tab else {
+            return ToolResult::err(
+                ErrorCode::TabNotFound,
+                match target_id {
+                    Some(target_id) => format!(
+                        "Popup not found: {}. Use list_popups to see orphaned pages.",
+                        target_id
+                    ),
+                    None => "No orphaned popups to adopt".to_string(),
+                },
+            );
+        };
+
+        let mut info = self.adopt(tab, activate);
+        info["active"] = (self.active_tab == info["tab_id"]).into();
+        ToolResult::ok(info)
+    }
+}
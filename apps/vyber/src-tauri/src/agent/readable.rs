@@ -0,0 +1,998 @@
+/**
+ * VybeR Agent Readable Extraction
+ *
+ * Readability-style main content extraction. Paragraph-bearing blocks are
+ * scored to find the article, navigation and other boilerplate is dropped,
+ * and what's left is rendered as Markdown along with the byline, publish
+ * date and language.
+ */
+
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use url::Url;
+
+/// What extract_text and fetch_page return
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractMode {
+    /// All body text, whitespace-collapsed
+    #[default]
+    Text,
+    /// The main content as Markdown, without boilerplate
+    Readable,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Article {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    pub markdown: String,
+}
+
+/// Elements that never carry article content
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "object", "embed", "nav",
+    "footer", "aside", "button", "input", "select", "textarea", "dialog", "menu",
+];
+
+/// class/id words that mark boilerplate (matched against word prefixes)
+const NEGATIVE_WORDS: &[&str] = &[
+    "comment",
+    "cookie",
+    "consent",
+    "banner",
+    "share",
+    "social",
+    "related",
+    "newsletter",
+    "subscribe",
+    "promo",
+    "advert",
+    "sponsor",
+    "sidebar",
+    "popup",
+    "modal",
+    "breadcrumb",
+    "footer",
+    "navbar",
+    "menu",
+    "masthead",
+    "skip",
+];
+
+/// class/id words that suggest article content
+const POSITIVE_WORDS: &[&str] = &[
+    "article", "body", "content", "entry", "main", "post", "story", "text", "blog",
+];
+
+/// Extract the main content of `html`. With a `root` selector, the first
+/// match is used as the content instead of the highest-scoring block.
+pub fn extract_article(html: &str, page_url: Option<&str>, root: Option<&Selector>) -> Article {
+    let document = Html::parse_document(html);
+    let base = page_url.and_then(|url| base_url(&document, url));
+
+    let content = match root {
+        Some(selector) => document.select(selector).next(),
+        None => find_main_content(&document),
+    };
+
+    let markdown = content
+        .map(|el| {
+            let renderer = Renderer {
+                base: base.as_ref(),
+            };
+            let mut blocks = Vec::new();
+            renderer.blocks(el, &mut blocks);
+            blocks.join("\n\n")
+        })
+        .unwrap_or_default();
+
+    let lang = document_lang(&document)
+        .or_else(|| content.and_then(|el| detect_lang(&el.text().collect::<String>())));
+
+    let ld = json_ld(&document);
+
+    Article {
+        title: document_title(&document),
+        byline: byline(&document, &ld),
+        published: published_date(&document, &ld, content),
+        lang,
+        markdown,
+    }
+}
+
+/// The URL relative links resolve against: the page URL, overridden by
+/// `<base href>` when present
+pub fn base_url(document: &Html, page_url: &str) -> Option<Url> {
+    let page = Url::parse(page_url).ok()?;
+    let base_sel = Selector::parse("base[href]").unwrap();
+    let base_href = document
+        .select(&base_sel)
+        .next()
+        .and_then(|el| el.value().attr("href"));
+    Some(resolve_base(page, base_href))
+}
+
+/// Apply a `<base href>` value to the page URL, ignoring one that doesn't resolve
+pub fn resolve_base(page: Url, base_href: Option<&str>) -> Url {
+    match base_href.map(|href| page.join(href.trim())) {
+        Some(Ok(base)) => base,
+        _ => page,
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lowercase words of an element's class and id attributes
+fn class_words(el: &ElementRef) -> Vec<String> {
+    let value = el.value();
+    let mut words: Vec<String> = value.classes().map(|c| c.to_lowercase()).collect();
+    if let Some(id) = value.id() {
+        words.push(id.to_lowercase());
+    }
+    words
+        .iter()
+        .flat_map(|w| {
+            w.split(|c: char| !c.is_ascii_alphanumeric())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn has_word(words: &[String], list: &[&str]) -> bool {
+    words.iter().any(|w| list.iter().any(|l| w.starts_with(l)))
+}
+
+fn is_negative(words: &[String]) -> bool {
+    // "ad" is too short to match as a prefix
+    words.iter().any(|w| w == "ad" || w == "ads") || has_word(words, NEGATIVE_WORDS)
+}
+
+/// Whether an element (and everything inside it) should be left out
+fn is_boilerplate(el: &ElementRef) -> bool {
+    let value = el.value();
+    let tag = value.name();
+    if SKIPPED_TAGS.contains(&tag) {
+        return true;
+    }
+    if value.attr("hidden").is_some() || value.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    if let Some(style) = value.attr("style") {
+        let style = style.replace(' ', "").to_lowercase();
+        if style.contains("display:none") || style.contains("visibility:hidden") {
+            return true;
+        }
+    }
+    if matches!(
+        value.attr("role"),
+        Some("navigation" | "banner" | "contentinfo" | "complementary" | "dialog")
+    ) {
+        return true;
+    }
+    // Page headers hold navigation; article headers hold the headline
+    if tag == "header" {
+        let heading = Selector::parse("h1, h2").unwrap();
+        if el.select(&heading).next().is_none() {
+            return true;
+        }
+    }
+    // Words like "ad" and "share" are common class fragments, so leave the
+    // document skeleton alone and only judge containers below it
+    !matches!(tag, "html" | "body" | "main" | "article") && is_negative(&class_words(el))
+}
+
+fn inside_boilerplate(el: &ElementRef) -> bool {
+    is_boilerplate(el)
+        || el
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| is_boilerplate(&a))
+}
+
+/// Fraction of an element's text that sits inside links
+fn link_density(el: &ElementRef) -> f64 {
+    let total = collapse_whitespace(&el.text().collect::<String>()).len();
+    if total == 0 {
+        return 1.0;
+    }
+    let link_sel = Selector::parse("a").unwrap();
+    let linked: usize = el
+        .select(&link_sel)
+        .map(|a| collapse_whitespace(&a.text().collect::<String>()).len())
+        .sum();
+    (linked as f64 / total as f64).min(1.0)
+}
+
+fn initial_score(el: &ElementRef) -> f64 {
+    let tag_score = match el.value().name() {
+        "article" => 10.0,
+        "div" | "main" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    let words = class_words(el);
+    let mut class_score = 0.0;
+    if has_word(&words, POSITIVE_WORDS) {
+        class_score += 25.0;
+    }
+    if is_negative(&words) {
+        class_score -= 25.0;
+    }
+    tag_score + class_score
+}
+
+/// Pick the element most likely to hold the article
+fn find_main_content(document: &Html) -> Option<ElementRef<'_>> {
+    let paragraph_sel = Selector::parse("p, pre, td, blockquote").unwrap();
+    let mut scores = HashMap::new();
+    let mut candidates = Vec::new();
+
+    for paragraph in document.select(&paragraph_sel) {
+        if inside_boilerplate(&paragraph) {
+            continue;
+        }
+        let text = collapse_whitespace(&paragraph.text().collect::<String>());
+        if text.len() < 25 {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (text.len() as f64 / 100.0).min(3.0);
+
+        // Credit the parent fully and the grandparent by half
+        let ancestors: Vec<ElementRef> = paragraph
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .take(2)
+            .collect();
+        for (level, ancestor) in ancestors.into_iter().enumerate() {
+            let entry = scores.entry(ancestor.id()).or_insert_with(|| {
+                candidates.push(ancestor);
+                initial_score(&ancestor)
+            });
+            *entry += if level == 0 { score } else { score / 2.0 };
+        }
+    }
+
+    let best = candidates
+        .into_iter()
+        .map(|el| (scores[&el.id()] * (1.0 - link_density(&el)), el))
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, el)| el);
+
+    // In order of preference; a combined selector would always find body,
+    // which comes first in the document
+    best.or_else(|| {
+        ["article", "main", "[role=main]", "body"]
+            .iter()
+            .find_map(|fallback| document.select(&Selector::parse(fallback).unwrap()).next())
+    })
+}
+
+/// Renders an element tree as Markdown blocks
+struct Renderer<'u> {
+    base: Option<&'u Url>,
+}
+
+impl Renderer<'_> {
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with("javascript:") || href.starts_with("data:") {
+            return None;
+        }
+        match self.base {
+            Some(base) => base.join(href).ok().map(|u| u.to_string()),
+            None => Some(href.to_string()),
+        }
+    }
+
+    /// Render the children of `el` as a sequence of blocks
+    fn blocks(&self, el: ElementRef, out: &mut Vec<String>) {
+        let mut paragraph = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) => push_text(&mut paragraph, text),
+                Node::Element(_) => {
+                    let child = ElementRef::wrap(child).unwrap();
+                    if is_boilerplate(&child) {
+                        continue;
+                    }
+                    if is_block(child.value().name()) {
+                        flush_paragraph(&mut paragraph, out);
+                        self.block(child, out);
+                    } else {
+                        self.inline(child, &mut paragraph);
+                    }
+                }
+                _ => {}
+            }
+        }
+        flush_paragraph(&mut paragraph, out);
+    }
+
+    fn block(&self, el: ElementRef, out: &mut Vec<String>) {
+        let tag = el.value().name();
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = tag[1..].parse::<usize>().unwrap_or(1);
+                let text = self.inline_text(el);
+                if !text.is_empty() {
+                    out.push(format!("{} {}", "#".repeat(level), text));
+                }
+            }
+            "p" => {
+                let text = self.inline_text(el);
+                if !text.is_empty() {
+                    out.push(text);
+                }
+            }
+            "ul" | "ol" => {
+                let list = self.list(el, tag == "ol");
+                if !list.is_empty() {
+                    out.push(list);
+                }
+            }
+            "pre" => {
+                let code = el.text().collect::<String>();
+                let code = code.trim_matches('\n');
+                if !code.trim().is_empty() {
+                    out.push(format!("```{}\n{}\n```", code_language(el), code));
+                }
+            }
+            "blockquote" => {
+                let mut inner = Vec::new();
+                self.blocks(el, &mut inner);
+                let quoted = inner
+                    .join("\n\n")
+                    .lines()
+                    .map(|line| {
+                        if line.is_empty() {
+                            ">".to_string()
+                        } else {
+                            format!("> {}", line)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if !quoted.is_empty() {
+                    out.push(quoted);
+                }
+            }
+            "table" => self.table(el, out),
+            "hr" => out.push("---".to_string()),
+            "dt" => {
+                let text = self.inline_text(el);
+                if !text.is_empty() {
+                    out.push(format!("**{}**", text));
+                }
+            }
+            _ => self.blocks(el, out),
+        }
+    }
+
+    fn list(&self, el: ElementRef, ordered: bool) -> String {
+        let mut items = Vec::new();
+        let mut number = el
+            .value()
+            .attr("start")
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(1);
+
+        for item in el.children().filter_map(ElementRef::wrap) {
+            if item.value().name() != "li" || is_boilerplate(&item) {
+                continue;
+            }
+            let mut inner = Vec::new();
+            self.blocks(item, &mut inner);
+            let body = inner.join("\n");
+            if body.trim().is_empty() {
+                continue;
+            }
+
+            let marker = if ordered {
+                let marker = format!("{}. ", number);
+                number += 1;
+                marker
+            } else {
+                "- ".to_string()
+            };
+            let indent = " ".repeat(marker.len());
+            let rendered = body
+                .lines()
+                .enumerate()
+                .map(|(i, line)| {
+                    if i == 0 {
+                        format!("{}{}", marker, line)
+                    } else if line.is_empty() {
+                        String::new()
+                    } else {
+                        format!("{}{}", indent, line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            items.push(rendered);
+        }
+        items.join("\n")
+    }
+
+    fn table(&self, el: ElementRef, out: &mut Vec<String>) {
+        let row_sel = Selector::parse("tr").unwrap();
+        let nested_sel = Selector::parse("table").unwrap();
+        // Rows of this table only, not of tables nested inside it
+        let rows: Vec<ElementRef> = el
+            .select(&row_sel)
+            .filter(|row| {
+                row.ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .find(|a| a.value().name() == "table")
+                    .map(|t| t.id())
+                    == Some(el.id())
+            })
+            .collect();
+
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                row.children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|c| matches!(c.value().name(), "td" | "th"))
+                    .map(|c| self.inline_text(c).replace('|', "\\|"))
+                    .collect()
+            })
+            .collect();
+
+        // Layout tables (one column, or tables of tables) are just containers
+        let columns = cells.iter().map(Vec::len).max().unwrap_or(0);
+        if columns < 2 || el.select(&nested_sel).next().is_some() {
+            for row in rows {
+                for cell in row.children().filter_map(ElementRef::wrap) {
+                    self.blocks(cell, out);
+                }
+            }
+            return;
+        }
+
+        let mut lines = Vec::new();
+        for (i, row) in cells.iter().enumerate() {
+            let mut row = row.clone();
+            row.resize(columns, String::new());
+            lines.push(format!("| {} |", row.join(" | ")));
+            if i == 0 {
+                lines.push(format!("|{}", " --- |".repeat(columns)));
+            }
+        }
+        out.push(lines.join("\n"));
+    }
+
+    /// Inline Markdown for the contents of `el`, whitespace-collapsed
+    fn inline_text(&self, el: ElementRef) -> String {
+        tidy_inline(&self.inline_content(el))
+    }
+
+    /// Inline Markdown for the contents of `el`, with source whitespace
+    /// flattened to spaces and `<br>` kept as newlines
+    fn inline_content(&self, el: ElementRef) -> String {
+        let mut text = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(t) => push_text(&mut text, t),
+                Node::Element(_) => {
+                    let child = ElementRef::wrap(child).unwrap();
+                    if !is_boilerplate(&child) {
+                        self.inline(child, &mut text);
+                    }
+                }
+                _ => {}
+            }
+        }
+        text
+    }
+
+    fn inline(&self, el: ElementRef, out: &mut String) {
+        let value = el.value();
+        match value.name() {
+            "br" => out.push('\n'),
+            "img" => {
+                let alt = collapse_whitespace(value.attr("alt").unwrap_or(""));
+                if let Some(src) = value.attr("src").and_then(|s| self.resolve(s)) {
+                    out.push_str(&format!("![{}]({})", alt, src));
+                }
+            }
+            "a" => {
+                let content = self.inline_content(el);
+                match value.attr("href").and_then(|h| self.resolve(h)) {
+                    Some(href) => wrap_inline(out, &content, "[", &format!("]({})", href)),
+                    None => out.push_str(&content),
+                }
+            }
+            "strong" | "b" => wrap_inline(out, &self.inline_content(el), "**", "**"),
+            "em" | "i" => wrap_inline(out, &self.inline_content(el), "*", "*"),
+            "code" | "kbd" | "samp" => {
+                let mut code = String::new();
+                push_text(&mut code, &el.text().collect::<String>());
+                wrap_inline(out, &code, "`", "`")
+            }
+            "del" | "s" | "strike" => wrap_inline(out, &self.inline_content(el), "~~", "~~"),
+            tag => {
+                // Block elements nested in inline context still need a gap
+                let gap = if is_block(tag) { " " } else { "" };
+                out.push_str(gap);
+                out.push_str(&self.inline_content(el));
+                out.push_str(gap);
+            }
+        }
+    }
+}
+
+fn is_block(tag: &str) -> bool {
+    matches!(
+        tag,
+        "p" | "div"
+            | "section"
+            | "article"
+            | "main"
+            | "header"
+            | "figure"
+            | "figcaption"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "ul"
+            | "ol"
+            | "li"
+            | "pre"
+            | "blockquote"
+            | "table"
+            | "hr"
+            | "dl"
+            | "dt"
+            | "dd"
+            | "details"
+            | "summary"
+            | "address"
+            | "center"
+    )
+}
+
+/// Append a text node with its newlines and tabs flattened to spaces, so
+/// only `<br>` produces a line break
+fn push_text(out: &mut String, text: &str) {
+    out.extend(
+        text.chars()
+            .map(|c| if c.is_whitespace() { ' ' } else { c }),
+    );
+}
+
+/// Wrap inline content in Markdown markers, keeping the whitespace around
+/// it outside the markers where Markdown expects it
+fn wrap_inline(out: &mut String, content: &str, open: &str, close: &str) {
+    let text = tidy_inline(content);
+    if text.is_empty() {
+        out.push_str(content);
+        return;
+    }
+    if content.starts_with(char::is_whitespace) {
+        out.push(' ');
+    }
+    out.push_str(open);
+    out.push_str(&text);
+    out.push_str(close);
+    if content.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+}
+
+/// Collapse whitespace within each line while keeping `<br>` line breaks
+fn tidy_inline(text: &str) -> String {
+    text.split('\n')
+        .map(collapse_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("  \n")
+}
+
+fn flush_paragraph(paragraph: &mut String, out: &mut Vec<String>) {
+    let text = tidy_inline(paragraph);
+    if !text.is_empty() {
+        out.push(text);
+    }
+    paragraph.clear();
+}
+
+/// Language hint from a `language-xx` / `lang-xx` class on a code block
+fn code_language(pre: ElementRef) -> String {
+    let code_sel = Selector::parse("code").unwrap();
+    std::iter::once(pre)
+        .chain(pre.select(&code_sel))
+        .flat_map(|el| el.value().classes().map(str::to_string).collect::<Vec<_>>())
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+                .map(str::to_string)
+        })
+        .unwrap_or_default()
+}
+
+fn meta_content(document: &Html, selectors: &str) -> Option<String> {
+    let selector = Selector::parse(selectors).ok()?;
+    document
+        .select(&selector)
+        .filter_map(|el| el.value().attr("content"))
+        .map(collapse_whitespace)
+        .find(|content| !content.is_empty())
+}
+
+fn document_title(document: &Html) -> Option<String> {
+    meta_content(
+        document,
+        "meta[property='og:title'], meta[name='twitter:title']",
+    )
+    .or_else(|| {
+        let title_sel = Selector::parse("title, h1").unwrap();
+        document
+            .select(&title_sel)
+            .map(|el| collapse_whitespace(&el.text().collect::<String>()))
+            .find(|title| !title.is_empty())
+    })
+}
+
+/// JSON-LD metadata blocks, flattened so `@graph` entries are searchable
+fn json_ld(document: &Html) -> Vec<serde_json::Value> {
+    let ld_sel = Selector::parse("script[type='application/ld+json']").unwrap();
+    let mut items = Vec::new();
+    for script in document.select(&ld_sel) {
+        if let Ok(value) =
+            serde_json::from_str::<serde_json::Value>(&script.text().collect::<String>())
+        {
+            let mut stack = vec![value];
+            while let Some(value) = stack.pop() {
+                match value {
+                    serde_json::Value::Array(values) => stack.extend(values),
+                    serde_json::Value::Object(mut map) => {
+                        if let Some(graph) = map.remove("@graph") {
+                            stack.push(graph);
+                        }
+                        items.push(serde_json::Value::Object(map));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    items
+}
+
+fn ld_author_name(author: &serde_json::Value) -> Option<String> {
+    match author {
+        serde_json::Value::String(name) => Some(name.clone()),
+        serde_json::Value::Object(map) => {
+            map.get("name").and_then(|n| n.as_str()).map(str::to_string)
+        }
+        serde_json::Value::Array(authors) => {
+            let names: Vec<String> = authors.iter().filter_map(ld_author_name).collect();
+            (!names.is_empty()).then(|| names.join(", "))
+        }
+        _ => None,
+    }
+}
+
+fn byline(document: &Html, ld: &[serde_json::Value]) -> Option<String> {
+    let from_meta = meta_content(
+        document,
+        "meta[name='author'], meta[property='article:author']",
+    )
+    .filter(|author| !author.starts_with("http"));
+    let from_ld = || {
+        ld.iter()
+            .filter_map(|item| item.get("author"))
+            .find_map(ld_author_name)
+    };
+    let from_markup = || {
+        let byline_sel =
+            Selector::parse("[rel='author'], [itemprop='author'], .byline, .author, .post-author")
+                .unwrap();
+        document
+            .select(&byline_sel)
+            .map(|el| collapse_whitespace(&el.text().collect::<String>()))
+            .find(|text| !text.is_empty() && text.len() < 100)
+    };
+    from_meta.or_else(from_ld).or_else(from_markup)
+}
+
+fn published_date(
+    document: &Html,
+    ld: &[serde_json::Value],
+    content: Option<ElementRef>,
+) -> Option<String> {
+    let from_meta = meta_content(
+        document,
+        "meta[property='article:published_time'], meta[itemprop='datePublished'], \
+         meta[name='date'], meta[name='pubdate'], meta[name='publishdate'], \
+         meta[name='publish-date'], meta[name='dc.date'], meta[name='DC.date.issued'], \
+         meta[name='dcterms.created'], meta[name='parsely-pub-date']",
+    );
+    let from_ld = || {
+        ld.iter()
+            .filter_map(|item| item.get("datePublished").and_then(|d| d.as_str()))
+            .map(str::to_string)
+            .next()
+    };
+    let from_time = || {
+        let time_sel = Selector::parse("time[datetime]").unwrap();
+        let in_content = content.and_then(|el| el.select(&time_sel).next());
+        in_content
+            .or_else(|| document.select(&time_sel).next())
+            .and_then(|el| el.value().attr("datetime"))
+            .map(str::to_string)
+    };
+    from_meta.or_else(from_ld).or_else(from_time)
+}
+
+fn document_lang(document: &Html) -> Option<String> {
+    let html_sel = Selector::parse("html[lang]").unwrap();
+    document
+        .select(&html_sel)
+        .next()
+        .and_then(|el| el.value().attr("lang"))
+        .map(|lang| lang.trim().to_string())
+        .filter(|lang| !lang.is_empty())
+        .or_else(|| meta_content(document, "meta[http-equiv='content-language' i]"))
+}
+
+/// Guess the language from common function words when the page doesn't say
+fn detect_lang(text: &str) -> Option<String> {
+    const STOPWORDS: &[(&str, &[&str])] = &[
+        (
+            "en",
+            &[
+                "the", "and", "of", "to", "is", "in", "that", "it", "for", "with", "was",
+            ],
+        ),
+        (
+            "de",
+            &[
+                "der", "die", "und", "das", "ist", "nicht", "mit", "ein", "zu", "den", "sich",
+            ],
+        ),
+        (
+            "fr",
+            &[
+                "le", "les", "et", "des", "est", "une", "pour", "que", "dans", "du", "pas",
+            ],
+        ),
+        (
+            "es",
+            &[
+                "el", "los", "y", "que", "es", "por", "una", "para", "con", "del", "las",
+            ],
+        ),
+        (
+            "it",
+            &[
+                "il", "di", "che", "e", "per", "una", "sono", "non", "con", "gli", "della",
+            ],
+        ),
+        (
+            "pt",
+            &[
+                "o", "que", "e", "não", "uma", "para", "com", "os", "da", "do", "em",
+            ],
+        ),
+        (
+            "nl",
+            &[
+                "het", "een", "en", "van", "is", "dat", "niet", "op", "voor", "zijn", "met",
+            ],
+        ),
+    ];
+
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .take(2000)
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut counts: Vec<(&str, usize)> = STOPWORDS
+        .iter()
+        .map(|(lang, stopwords)| {
+            (
+                *lang,
+                words
+                    .iter()
+                    .filter(|w| stopwords.contains(&w.as_str()))
+                    .count(),
+            )
+        })
+        .collect();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    // Require a clear winner over enough text to mean something
+    match counts.as_slice() {
+        [(lang, best), (_, second), ..] if *best >= 10 && *best * 2 > *second * 3 => {
+            Some(lang.to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEWS_PAGE: &str = r#"
+<html lang="en">
+<head>
+  <title>Tide tables | Coast News</title>
+  <meta name="author" content="Ada Shore">
+  <meta property="article:published_time" content="2024-05-01T08:00:00Z">
+  <script>var tracking = "script text";</script>
+  <style>.story { color: black; }</style>
+</head>
+<body>
+  <header><a href="/">Coast News</a> <a href="/weather">Weather</a></header>
+  <nav><ul><li><a href="/sport">Sport</a></li><li><a href="/arts">Arts</a></li></ul></nav>
+  <div class="cookie-banner"><p>We use cookies to improve your experience, as most sites do.</p></div>
+  <article class="story">
+    <p>The spring tides arrive this weekend, bringing the lowest water of the year to the bay.</p>
+    <p>Harbour staff say the sandbars will be exposed for hours, and walkers should check times.</p>
+    <div class="share-buttons"><p>Share this story with your friends, family and colleagues.</p></div>
+    <p>Tables for the month are posted at <a href="/tides">the harbour office</a>.</p>
+  </article>
+  <aside><p>Most read: ten beaches you should visit before the summer crowds arrive.</p></aside>
+  <footer><p>Copyright Coast News, all rights reserved, since nineteen fifty.</p></footer>
+</body>
+</html>
+"#;
+
+    const STRUCTURED_PAGE: &str = r#"
+<html><body><main>
+  <h1>Packing list</h1>
+  <p>Everything you need for a weekend on the water, checked off before you leave.</p>
+  <h2>Clothing</h2>
+  <ul>
+    <li>Waterproof jacket</li>
+    <li>Warm <strong>wool</strong> layers
+      <ul><li>Hat</li><li>Gloves</li></ul>
+    </li>
+  </ul>
+  <h3>Steps</h3>
+  <ol start="3">
+    <li>Check the forecast</li>
+    <li>File a float plan</li>
+  </ol>
+</main></body></html>
+"#;
+
+    fn markdown(html: &str) -> String {
+        extract_article(html, Some("https://news.example/2024/tides"), None).markdown
+    }
+
+    #[test]
+    fn keeps_the_article_and_drops_boilerplate() {
+        let markdown = markdown(NEWS_PAGE);
+
+        assert!(markdown.contains("The spring tides arrive this weekend"));
+        assert!(markdown.contains("walkers should check times"));
+        for boilerplate in [
+            "Sport",
+            "Weather",
+            "cookies",
+            "Share this story",
+            "Most read",
+            "Copyright",
+            "script text",
+            "color: black",
+        ] {
+            assert!(!markdown.contains(boilerplate), "kept {:?}", boilerplate);
+        }
+    }
+
+    #[test]
+    fn resolves_links_against_the_page() {
+        assert!(markdown(NEWS_PAGE).contains("[the harbour office](https://news.example/tides)"));
+    }
+
+    #[test]
+    fn reads_metadata() {
+        let article = extract_article(NEWS_PAGE, None, None);
+        assert_eq!(article.byline.as_deref(), Some("Ada Shore"));
+        assert_eq!(article.lang.as_deref(), Some("en"));
+        assert!(article
+            .title
+            .is_some_and(|title| title.contains("Tide tables")));
+        assert!(article
+            .published
+            .is_some_and(|date| date.starts_with("2024-05-01")));
+    }
+
+    #[test]
+    fn renders_headings_and_lists() {
+        let markdown = markdown(STRUCTURED_PAGE);
+        let blocks: Vec<&str> = markdown.split("\n\n").collect();
+
+        assert_eq!(
+            blocks,
+            [
+                "# Packing list",
+                "Everything you need for a weekend on the water, checked off before you leave.",
+                "## Clothing",
+                "- Waterproof jacket\n- Warm **wool** layers\n  - Hat\n  - Gloves",
+                "### Steps",
+                "3. Check the forecast\n4. File a float plan",
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_main_without_an_article() {
+        // No paragraph is long enough to score
+        let html = r#"
+<html><body>
+  <div>Opening hours vary</div>
+  <main><p>Short note.</p><p>Back soon.</p></main>
+</body></html>
+"#;
+        assert_eq!(markdown(html), "Short note.\n\nBack soon.");
+    }
+
+    #[test]
+    fn falls_back_to_body_without_main() {
+        let html = "<html><body><nav><a href='/'>Home</a></nav><p>Just a line.</p></body></html>";
+        assert_eq!(markdown(html), "Just a line.");
+    }
+
+    #[test]
+    fn keeps_content_inside_a_page_wide_form() {
+        // ASP.NET WebForms wraps the whole page in one form
+        let html = r#"
+<html><body><form id="form1" method="post" action="./story.aspx">
+  <input type="hidden" name="__VIEWSTATE" value="dDwtMTA4MTk2MDM4Nzs7Pg==">
+  <div class="story">
+    <h1>Harbour wall repairs</h1>
+    <p>Work on the old harbour wall starts on Monday and is expected to last most of the summer.</p>
+    <p>The footpath along the wall stays open, though the steps at the north end will be fenced off.</p>
+  </div>
+  <input type="submit" name="btnSubscribe" value="Subscribe">
+</form></body></html>
+"#;
+        let markdown = markdown(html);
+        assert!(markdown.contains("# Harbour wall repairs"));
+        assert!(markdown.contains("Work on the old harbour wall starts on Monday"));
+        assert!(markdown.contains("the steps at the north end will be fenced off."));
+        assert!(!markdown.contains("Subscribe"));
+    }
+
+    #[test]
+    fn root_selector_picks_the_content() {
+        let root = Selector::parse(".share-buttons").unwrap();
+        let article = extract_article(NEWS_PAGE, None, Some(&root));
+        assert_eq!(
+            article.markdown,
+            "Share this story with your friends, family and colleagues."
+        );
+
+        let missing = Selector::parse("#missing").unwrap();
+        assert_eq!(
+            extract_article(NEWS_PAGE, None, Some(&missing)).markdown,
+            ""
+        );
+    }
+}
@@ -0,0 +1,318 @@
+/**
+ * VybeR Agent Screenshots
+ *
+ * Captures the viewport, the full page, a single element or a clip
+ * rectangle as PNG, JPEG or WebP, at a chosen device scale factor. The
+ * image comes back inline as a data URL or goes to a file (see `files`).
+ * Viewport captures can be marked for vision models (see `marks`).
+ */
+
+use super::files::Output;
+use super::marks::{clear_marks, draw_marks};
+use super::{call_on_element, BrowserAgent, ErrorCode, OptionalTarget, Target, ToolResult};
+use base64::Engine;
+use headless_chrome::browser::tab::element::Element;
+use headless_chrome::protocol::cdp::Page;
+use headless_chrome::Tab;
+use serde::Deserialize;
+
+/// Largest device scale factor accepted. Full-page captures grow with its
+/// square, so this keeps them from exhausting memory.
+const MAX_SCALE: f64 = 4.0;
+
+/// Called on the element. Scrolls it into view and returns its border box
+/// in page coordinates.
+const ELEMENT_RECT_JS: &str = r#"
+function () {
+  this.scrollIntoView({ block: "center", inline: "center", behavior: "instant" });
+  const rect = this.getBoundingClientRect();
+  // An element in an iframe is measured from the frame's viewport, so add
+  // the offsets of the frames it sits in
+  let win = this.ownerDocument.defaultView;
+  let x = rect.left;
+  let y = rect.top;
+  while (win.frameElement) {
+    const frame = win.frameElement;
+    const box = frame.getBoundingClientRect();
+    x += box.left + frame.clientLeft;
+    y += box.top + frame.clientTop;
+    win = win.parent;
+  }
+  return { x: x + win.scrollX, y: y + win.scrollY, width: rect.width, height: rect.height };
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    #[serde(alias = "jpg")]
+    Jpeg,
+    Webp,
+}
+
+impl ImageFormat {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpeg",
+            Self::Webp => "webp",
+        }
+    }
+
+    fn protocol(self) -> Page::CaptureScreenshotFormatOption {
+        match self {
+            Self::Png => Page::CaptureScreenshotFormatOption::Png,
+            Self::Jpeg => Page::CaptureScreenshotFormatOption::Jpeg,
+            Self::Webp => Page::CaptureScreenshotFormatOption::Webp,
+        }
+    }
+}
+
+/// A rectangle in CSS pixels, measured from the top-left of the page
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Clip {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// The part of the page to capture
+#[derive(Debug, Clone, Default)]
+pub enum Region {
+    /// What is currently on screen
+    #[default]
+    Viewport,
+    /// The whole scrollable page
+    FullPage,
+    Element(Target),
+    Clip(Clip),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "ScreenshotInput")]
+pub struct ScreenshotOptions {
+    pub region: Region,
+    pub format: ImageFormat,
+    /// JPEG/WebP quality from 0 to 100
+    pub quality: Option<u32>,
+    /// Device scale factor
+    pub scale: f64,
+    /// Overlay numbered boxes on the interactive elements
+    pub marks: bool,
+    pub output: Output,
+}
+
+/// The option fields as they appear in tool input
+#[derive(Deserialize)]
+struct ScreenshotInput {
+    #[serde(flatten)]
+    target: OptionalTarget,
+    clip: Option<Clip>,
+    #[serde(default)]
+    full_page: bool,
+    #[serde(default)]
+    format: ImageFormat,
+    quality: Option<u32>,
+    scale: Option<f64>,
+    #[serde(default)]
+    marks: bool,
+    #[serde(default)]
+    save: bool,
+}
+
+impl TryFrom<ScreenshotInput> for ScreenshotOptions {
+    type Error = String;
+
+    fn try_from(input: ScreenshotInput) -> Result<Self, Self::Error> {
+        let region = match (input.target.0, input.clip, input.full_page) {
+            (None, None, false) => Region::Viewport,
+            (None, None, true) => Region::FullPage,
+            (Some(target), None, false) => Region::Element(target),
+            (None, Some(clip), false) => {
+                if clip.width <= 0.0 || clip.height <= 0.0 {
+                    return Err("clip width and height must be positive".to_string());
+                }
+                Region::Clip(clip)
+            }
+            _ => return Err("give only one of selector/text/ref, clip or full_page".to_string()),
+        };
+
+        match (input.quality, input.format) {
+            (Some(_), ImageFormat::Png) => {
+                return Err("quality only applies to jpeg and webp".to_string())
+            }
+            (Some(quality), _) if quality > 100 => {
+                return Err("quality must be between 0 and 100".to_string())
+            }
+            _ => {}
+        }
+
+        if input.marks && !matches!(region, Region::Viewport) {
+            return Err("marks only apply to viewport screenshots".to_string());
+        }
+
+        let scale = input.scale.unwrap_or(1.0);
+        if !(scale > 0.0 && scale <= MAX_SCALE) {
+            return Err(format!("scale must be above 0 and at most {}", MAX_SCALE));
+        }
+
+        Ok(Self {
+            region,
+            format: input.format,
+            quality: input.quality,
+            scale,
+            marks: input.marks,
+            output: Output::new(input.save),
+        })
+    }
+}
+
+impl BrowserAgent {
+    /// Take a screenshot, returned inline or written to a file
+    pub fn screenshot(&self, tab_id: Option<&str>, options: &ScreenshotOptions) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+        let tab = &agent_tab.tab;
+
+        let clip = match &options.region {
+            // Chrome only applies a scale to a clip, so a scaled viewport
+            // capture clips to the visible area
+            Region::Viewport if options.scale == 1.0 => Ok(None),
+            Region::Viewport => viewport_clip(tab).map(Some),
+            Region::FullPage => page_clip(tab).map(Some),
+            Region::Element(target) => target
+                .resolve(tab, &agent_tab.refs)
+                .and_then(|element| element_clip(&element, target))
+                .map(Some),
+            Region::Clip(clip) => Ok(Some(*clip)),
+        };
+        let clip = match clip {
+            Ok(clip) => clip,
+            Err(e) => return e,
+        };
+
+        let marks = if options.marks {
+            match draw_marks(tab, &agent_tab.refs) {
+                Ok(marks) => Some(marks),
+                Err(e) => {
+                    clear_marks(tab, &agent_tab.refs);
+                    return e;
+                }
+            }
+        } else {
+            None
+        };
+
+        let captured = tab.call_method(Page::CaptureScreenshot {
+            format: Some(options.format.protocol()),
+            quality: options.quality,
+            clip: clip.map(|clip| Page::Viewport {
+                x: clip.x,
+                y: clip.y,
+                width: clip.width,
+                height: clip.height,
+                scale: options.scale,
+            }),
+            from_surface: Some(true),
+            capture_beyond_viewport: Some(!matches!(options.region, Region::Viewport)),
+            optimize_for_speed: None,
+        });
+        if marks.is_some() {
+            clear_marks(tab, &agent_tab.refs);
+        }
+        let bytes = match captured {
+            Ok(captured) => match base64::engine::general_purpose::STANDARD.decode(captured.data) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    return ToolResult::err(
+                        ErrorCode::Internal,
+                        format!("Screenshot was not valid base64: {}", e),
+                    )
+                }
+            },
+            Err(e) => {
+                return ToolResult::browser_err(ErrorCode::Internal, "Screenshot failed", &*e)
+            }
+        };
+
+        let format = options.format.name();
+        let mut result = match self.deliver(
+            &options.output,
+            "screenshot",
+            &bytes,
+            &format!("image/{}", format),
+            format,
+        ) {
+            Ok(result) => result,
+            Err(e) => return e,
+        };
+        result.insert("format".to_string(), format.into());
+        if let Some(clip) = clip {
+            result.insert(
+                "width".to_string(),
+                (clip.width * options.scale).round().into(),
+            );
+            result.insert(
+                "height".to_string(),
+                (clip.height * options.scale).round().into(),
+            );
+        }
+        if let Some(marks) = marks {
+            result.insert("marks".to_string(), marks);
+        }
+        ToolResult::ok(result.into())
+    }
+}
+
+/// The visible part of the page
+fn viewport_clip(tab: &Tab) -> Result<Clip, ToolResult> {
+    let metrics = layout_metrics(tab)?;
+    let viewport = metrics.css_layout_viewport;
+    Ok(Clip {
+        x: viewport.page_x.into(),
+        y: viewport.page_y.into(),
+        width: viewport.client_width.into(),
+        height: viewport.client_height.into(),
+    })
+}
+
+/// The whole scrollable page
+fn page_clip(tab: &Tab) -> Result<Clip, ToolResult> {
+    let metrics = layout_metrics(tab)?;
+    let content = metrics.css_content_size;
+    Ok(Clip {
+        x: 0.0,
+        y: 0.0,
+        width: content.width.ceil(),
+        height: content.height.ceil(),
+    })
+}
+
+fn layout_metrics(tab: &Tab) -> Result<Page::GetLayoutMetricsReturnObject, ToolResult> {
+    tab.call_method(Page::GetLayoutMetrics(None)).map_err(|e| {
+        ToolResult::browser_err(ErrorCode::Internal, "Failed to measure the page", &*e)
+    })
+}
+
+/// The element's border box, after scrolling it into view
+fn element_clip(element: &Element, target: &Target) -> Result<Clip, ToolResult> {
+    let rect = call_on_element(element, ELEMENT_RECT_JS, serde_json::Value::Null)?;
+    let clip: Clip = serde_json::from_value(rect).map_err(|e| {
+        ToolResult::err(
+            ErrorCode::ScriptError,
+            format!("Failed to measure element: {}", e),
+        )
+    })?;
+    if clip.width <= 0.0 || clip.height <= 0.0 {
+        return Err(ToolResult::err(
+            ErrorCode::ElementNotInteractable,
+            format!("{} has no size to capture", target),
+        ));
+    }
+    Ok(clip)
+}
@@ -0,0 +1,301 @@
+/**
+ * VybeR Agent Page Snapshot
+ *
+ * Walks the DOM with computed ARIA roles and returns a compact outline of
+ * the visible interactive elements. Each element gets a ref like `e12` that
+ * click, fill_form and hover accept instead of a selector. Refs are kept in
+ * a table of weak references in an isolated JS world, where page scripts
+ * can't read or rewrite them. They survive re-snapshots of the same page and
+ * are dropped when the page navigates away.
+ */
+
+use super::{evaluate_in, BrowserAgent, ErrorCode, ToolResult};
+use headless_chrome::protocol::cdp::types::Event;
+use headless_chrome::protocol::cdp::Page;
+use headless_chrome::protocol::cdp::Runtime::{ExecutionContextId, RemoteObject};
+use headless_chrome::Tab;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Name of the isolated world the ref and mark tables live in
+const WORLD_NAME: &str = "vyber-refs";
+
+/// Called with `{ viewportOnly, maxElements, boxes }`. With `boxes`, also
+/// returns each ref'd element's role, name and viewport bounding box.
+const SNAPSHOT_JS: &str = r#"
+(options) => {
+  const refs = window.__vyberRefs || (window.__vyberRefs = new Map());
+  window.__vyberRefSeq = window.__vyberRefSeq || 0;
+  for (const [ref, weak] of refs) {
+    if (!weak.deref()?.isConnected) refs.delete(ref);
+  }
+
+  const INTERACTIVE = new Set([
+    "button", "link", "textbox", "searchbox", "checkbox", "radio", "combobox",
+    "listbox", "option", "menuitem", "menuitemcheckbox", "menuitemradio", "tab",
+    "switch", "slider", "spinbutton", "treeitem",
+  ]);
+  const VALUE_ROLES = new Set(["textbox", "searchbox", "combobox", "spinbutton", "slider"]);
+
+  const implicitRole = (el) => {
+    const tag = el.tagName.toLowerCase();
+    if (/^h[1-6]$/.test(tag)) return "heading";
+    switch (tag) {
+      case "a":
+      case "area":
+        return el.hasAttribute("href") ? "link" : null;
+      case "button":
+      case "summary":
+        return "button";
+      case "select":
+        return el.multiple || el.size > 1 ? "listbox" : "combobox";
+      case "textarea":
+        return "textbox";
+      case "input": {
+        const type = (el.getAttribute("type") || "text").toLowerCase();
+        if (type === "hidden") return null;
+        if (["button", "submit", "reset", "image"].includes(type)) return "button";
+        if (type === "checkbox") return "checkbox";
+        if (type === "radio") return "radio";
+        if (type === "range") return "slider";
+        if (type === "number") return "spinbutton";
+        if (type === "search") return "searchbox";
+        return "textbox";
+      }
+    }
+    if (el.isContentEditable && !el.parentElement?.isContentEditable) return "textbox";
+    return null;
+  };
+
+  const roleOf = (el) => (el.getAttribute("role") || "").trim().split(/\s+/)[0] || implicitRole(el);
+  const clean = (s) => (s || "").replace(/\s+/g, " ").trim();
+  const clip = (s) => (s.length > 80 ? s.slice(0, 77) + "..." : s);
+
+  const nameOf = (el) => {
+    const labelledBy = el.getAttribute("aria-labelledby");
+    if (labelledBy) {
+      const text = clean(labelledBy.split(/\s+/).map((id) => document.getElementById(id)?.innerText || "").join(" "));
+      if (text) return text;
+    }
+    const label = clean(el.getAttribute("aria-label"));
+    if (label) return label;
+    if (el.labels?.length) {
+      const text = clean(Array.from(el.labels, (l) => l.innerText).join(" "));
+      if (text) return text;
+    }
+    if (el.tagName === "INPUT" && ["button", "submit", "reset"].includes(el.type)) return clean(el.value);
+    const text = clean(el.innerText);
+    if (text) return text;
+    return clean(
+      el.getAttribute("alt") || el.querySelector?.("img[alt]")?.alt ||
+      el.getAttribute("placeholder") || el.getAttribute("title")
+    );
+  };
+
+  const isVisible = (el) => {
+    if (el.checkVisibility && !el.checkVisibility({ checkOpacity: true, checkVisibilityCSS: true })) return false;
+    const rect = el.getBoundingClientRect();
+    return rect.width > 0 && rect.height > 0;
+  };
+
+  const inViewport = (el) => {
+    const rect = el.getBoundingClientRect();
+    return rect.bottom > 0 && rect.right > 0 && rect.top < innerHeight && rect.left < innerWidth;
+  };
+
+  const refFor = (el) => {
+    if (el.__vyberRef && refs.get(el.__vyberRef)?.deref() === el) return el.__vyberRef;
+    const ref = "e" + ++window.__vyberRefSeq;
+    el.__vyberRef = ref;
+    refs.set(ref, new WeakRef(el));
+    return ref;
+  };
+
+  const lines = [];
+  const elements = [];
+  let count = 0;
+  let truncated = false;
+  const root = document.body || document.documentElement;
+  const walker = document.createTreeWalker(root, NodeFilter.SHOW_ELEMENT);
+  for (let el = walker.currentNode; el; el = walker.nextNode()) {
+    const role = roleOf(el);
+    if (!role || !(INTERACTIVE.has(role) || role === "heading")) continue;
+    if (el.closest("[aria-hidden='true'], [inert]") || !isVisible(el)) continue;
+    if (options.viewportOnly && !inViewport(el)) continue;
+    if (count >= options.maxElements) {
+      truncated = true;
+      break;
+    }
+    count++;
+
+    const name = nameOf(el);
+    let line = "- " + role + (name ? ' "' + clip(name).replace(/"/g, '\\"') + '"' : "");
+
+    // Headings give the outline structure but aren't targets
+    if (role === "heading") {
+      lines.push(line + " level=" + (el.getAttribute("aria-level") || el.tagName.slice(1)));
+      continue;
+    }
+
+    const ref = refFor(el);
+    line += " [" + ref + "]";
+    if (options.boxes) {
+      const rect = el.getBoundingClientRect();
+      const box = {
+        x: Math.round(rect.x),
+        y: Math.round(rect.y),
+        width: Math.round(rect.width),
+        height: Math.round(rect.height),
+      };
+      elements.push({ ref, role, name: clip(name), box });
+    }
+    if (el.disabled || el.getAttribute("aria-disabled") === "true") line += " disabled";
+    if (el.checked === true || el.getAttribute("aria-checked") === "true") line += " checked";
+    const expanded = el.getAttribute("aria-expanded");
+    if (expanded) line += " expanded=" + expanded;
+    if (el.required) line += " required";
+    if (VALUE_ROLES.has(role) && el.type !== "password") {
+      const value = clean(el.isContentEditable ? el.innerText : el.value);
+      if (value) line += ' value="' + clip(value).replace(/"/g, '\\"') + '"';
+    }
+    lines.push(line);
+  }
+
+  return {
+    url: location.href,
+    title: document.title,
+    snapshot: lines.join("\n"),
+    count,
+    truncated,
+    ...(options.boxes && { elements }),
+  };
+}
+"#;
+
+/// The isolated JS world holding a tab's ref and mark tables. It shares the
+/// top document's DOM but not its globals. Navigating destroys it, and the
+/// next snapshot creates a fresh one.
+#[derive(Clone, Default)]
+pub struct RefWorld(Arc<Mutex<Option<ExecutionContextId>>>);
+
+impl RefWorld {
+    /// Forget the world when `tab` destroys its context. Relies on the
+    /// Runtime domain being enabled (see `ConsoleLog::attach`).
+    pub fn attach(tab: &Tab) -> Self {
+        let world = Self::default();
+
+        let listener = world.clone();
+        let _ = tab.add_event_listener(Arc::new(move |event: &Event| match event {
+            Event::RuntimeExecutionContextDestroyed(destroyed) => {
+                let mut context = listener.lock();
+                if *context == Some(destroyed.params.execution_context_id) {
+                    *context = None;
+                }
+            }
+            Event::RuntimeExecutionContextsCleared(_) => *listener.lock() = None,
+            _ => {}
+        }));
+
+        world
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<ExecutionContextId>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Evaluate `expression` in the world and return its result as JSON,
+    /// creating the world first if the page doesn't have one yet
+    pub(super) fn evaluate(
+        &self,
+        tab: &Tab,
+        expression: &str,
+    ) -> Result<serde_json::Value, ToolResult> {
+        let existing = *self.lock();
+        let context = match existing {
+            Some(context) => context,
+            None => self.create(tab)?,
+        };
+        evaluate_in(tab, Some(context), expression, true)
+            .map(|result| result.value.unwrap_or(serde_json::Value::Null))
+    }
+
+    /// Evaluate `expression` in the world if the page has one. Without it
+    /// there are no refs or marks to look up, so this returns None.
+    pub(super) fn lookup(
+        &self,
+        tab: &Tab,
+        expression: &str,
+        return_by_value: bool,
+    ) -> Result<Option<RemoteObject>, ToolResult> {
+        let existing = *self.lock();
+        existing
+            .map(|context| evaluate_in(tab, Some(context), expression, return_by_value))
+            .transpose()
+    }
+
+    fn create(&self, tab: &Tab) -> Result<ExecutionContextId, ToolResult> {
+        let frame_id = tab
+            .call_method(Page::GetFrameTree(None))
+            .map_err(|e| {
+                ToolResult::browser_err(ErrorCode::ScriptError, "Failed to get frame tree", &*e)
+            })?
+            .frame_tree
+            .frame
+            .id;
+        let context = tab
+            .call_method(Page::CreateIsolatedWorld {
+                frame_id,
+                world_name: Some(WORLD_NAME.to_string()),
+                grant_univeral_access: None,
+            })
+            .map_err(|e| {
+                ToolResult::browser_err(ErrorCode::ScriptError, "Failed to create ref world", &*e)
+            })?
+            .execution_context_id;
+        *self.lock() = Some(context);
+        Ok(context)
+    }
+}
+
+impl BrowserAgent {
+    /// Outline the page's visible interactive elements with refs for
+    /// click, fill_form and hover
+    pub fn snapshot(
+        &self,
+        tab_id: Option<&str>,
+        viewport_only: bool,
+        max_elements: usize,
+    ) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        match run_snapshot(
+            &agent_tab.tab,
+            &agent_tab.refs,
+            viewport_only,
+            max_elements,
+            false,
+        ) {
+            Ok(snapshot) => ToolResult::ok(snapshot),
+            Err(e) => e,
+        }
+    }
+}
+
+/// Run the snapshot script in the ref world, with element boxes if `boxes`
+/// is set
+pub(super) fn run_snapshot(
+    tab: &Tab,
+    refs: &RefWorld,
+    viewport_only: bool,
+    max_elements: usize,
+    boxes: bool,
+) -> Result<serde_json::Value, ToolResult> {
+    let options = serde_json::json!({
+        "viewportOnly": viewport_only,
+        "maxElements": max_elements,
+        "boxes": boxes
+    });
+    refs.evaluate(tab, &format!("({})({})", SNAPSHOT_JS, options))
+}
@@ -0,0 +1,701 @@
+/**
+ * VybeR Agent Cookies and Web Storage
+ *
+ * Cookies go through the CDP Network domain, so HttpOnly cookies are
+ * visible too, and can be moved in and out of the browser as JSON (the
+ * Playwright/Puppeteer shape) or a Netscape cookies.txt file. localStorage
+ * and sessionStorage are read and written in the page, for its origin.
+ */
+
+use super::{evaluate_value, BrowserAgent, ErrorCode, ToolResult};
+use headless_chrome::protocol::cdp::Network::{self, CookieParam, CookieSameSite};
+use headless_chrome::Tab;
+use serde::{Deserialize, Serialize};
+
+/// A cookie as tools return and accept it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CookieData {
+    pub name: String,
+    pub value: String,
+    /// A leading dot means subdomains get the cookie too
+    pub domain: String,
+    #[serde(default = "default_path")]
+    pub path: String,
+    /// Seconds since the epoch; None for a session cookie
+    #[serde(
+        default,
+        alias = "expirationDate",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires: Option<f64>,
+    #[serde(default, alias = "httpOnly")]
+    pub http_only: bool,
+    #[serde(default)]
+    pub secure: bool,
+    /// Strict, Lax or None
+    #[serde(default, alias = "sameSite", skip_serializing_if = "Option::is_none")]
+    pub same_site: Option<String>,
+}
+
+fn default_path() -> String {
+    "/".to_string()
+}
+
+impl From<Network::Cookie> for CookieData {
+    fn from(cookie: Network::Cookie) -> Self {
+        Self {
+            name: cookie.name,
+            value: cookie.value,
+            domain: cookie.domain,
+            path: cookie.path,
+            expires: (!cookie.session && cookie.expires > 0.0).then_some(cookie.expires),
+            http_only: cookie.http_only,
+            secure: cookie.secure,
+            same_site: cookie.same_site.map(|s| format!("{:?}", s)),
+        }
+    }
+}
+
+impl CookieData {
+    /// Whether the cookie is sent to `domain` or one of its subdomains
+    fn matches_domain(&self, domain: &str) -> bool {
+        let domain = domain.trim_start_matches('.').to_lowercase();
+        let own = self.domain.trim_start_matches('.').to_lowercase();
+        own == domain || own.ends_with(&format!(".{}", domain))
+    }
+
+    fn to_param(&self) -> CookieParam {
+        // Setting a domain always makes a domain cookie, so host-only
+        // cookies are set by URL instead
+        let host_only = !self.domain.starts_with('.');
+        let scheme = if self.secure { "https" } else { "http" };
+        CookieParam {
+            name: self.name.clone(),
+            value: self.value.clone(),
+            url: host_only.then(|| format!("{}://{}{}", scheme, self.domain, self.path)),
+            domain: (!host_only).then(|| self.domain.clone()),
+            path: Some(self.path.clone()),
+            secure: Some(self.secure),
+            http_only: Some(self.http_only),
+            same_site: self.same_site.as_deref().and_then(same_site),
+            expires: self.expires.filter(|e| *e > 0.0),
+            priority: None,
+            same_party: None,
+            source_scheme: None,
+            source_port: None,
+            partition_key: None,
+        }
+    }
+}
+
+/// Accepts the spellings used by browsers and cookie-export extensions
+fn same_site(value: &str) -> Option<CookieSameSite> {
+    match value.to_ascii_lowercase().as_str() {
+        "strict" => Some(CookieSameSite::Strict),
+        "lax" => Some(CookieSameSite::Lax),
+        "none" | "no_restriction" => Some(CookieSameSite::None),
+        _ => None,
+    }
+}
+
+/// A cookie to set, as given in tool input
+#[derive(Debug, Deserialize)]
+pub struct NewCookie {
+    pub name: String,
+    pub value: String,
+    /// URL the cookie is for; defaults to the page URL when no domain is given
+    pub url: Option<String>,
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    pub expires: Option<f64>,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub secure: bool,
+    pub same_site: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieFormat {
+    /// An array of cookie objects
+    #[default]
+    Json,
+    /// Tab-separated cookies.txt, as read by curl and wget
+    Netscape,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageKind {
+    Local,
+    Session,
+}
+
+impl StorageKind {
+    fn global(self) -> &'static str {
+        match self {
+            Self::Local => "localStorage",
+            Self::Session => "sessionStorage",
+        }
+    }
+}
+
+fn all_cookies(tab: &Tab) -> Result<Vec<CookieData>, ToolResult> {
+    tab.call_method(Network::GetAllCookies(None))
+        .map(|r| r.cookies.into_iter().map(CookieData::from).collect())
+        .map_err(|e| ToolResult::browser_err(ErrorCode::Internal, "Failed to get cookies", &*e))
+}
+
+fn set_cookies(tab: &Tab, cookies: Vec<CookieParam>) -> Result<(), ToolResult> {
+    tab.call_method(Network::SetCookies { cookies })
+        .map(|_| ())
+        .map_err(|e| {
+            ToolResult::browser_err(ErrorCode::InvalidArgument, "Failed to set cookies", &*e)
+        })
+}
+
+fn delete_cookies(tab: &Tab, cookies: &[CookieData]) -> Result<(), ToolResult> {
+    for cookie in cookies {
+        tab.call_method(Network::DeleteCookies {
+            name: cookie.name.clone(),
+            url: None,
+            domain: Some(cookie.domain.clone()),
+            path: Some(cookie.path.clone()),
+            partition_key: None,
+        })
+        .map_err(|e| {
+            ToolResult::browser_err(ErrorCode::Internal, "Failed to delete cookie", &*e)
+        })?;
+    }
+    Ok(())
+}
+
+/// Playwright's cookie shape, which Puppeteer and most extensions also read
+fn to_json(cookies: &[CookieData]) -> String {
+    let cookies: Vec<serde_json::Value> = cookies
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "name": c.name,
+                "value": c.value,
+                "domain": c.domain,
+                "path": c.path,
+                "expires": c.expires.unwrap_or(-1.0),
+                "httpOnly": c.http_only,
+                "secure": c.secure,
+                "sameSite": c.same_site.as_deref().unwrap_or("Lax")
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&cookies).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn to_netscape(cookies: &[CookieData]) -> String {
+    let mut out = String::from("# Netscape HTTP Cookie File\n");
+    for cookie in cookies {
+        let flag = |b: bool| if b { "TRUE" } else { "FALSE" };
+        out.push_str(&format!(
+            "{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            if cookie.http_only { "#HttpOnly_" } else { "" },
+            cookie.domain,
+            flag(cookie.domain.starts_with('.')),
+            cookie.path,
+            flag(cookie.secure),
+            cookie.expires.map_or(0, |e| e as i64),
+            cookie.name,
+            cookie.value
+        ));
+    }
+    out
+}
+
+fn from_netscape(content: &str) -> Result<Vec<CookieData>, String> {
+    let mut cookies = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+            Some(rest) => (rest, true),
+            None => (line, false),
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, subdomains, path, secure, expires, name, value] = fields[..] else {
+            return Err(format!(
+                "line {}: expected 7 tab-separated fields",
+                number + 1
+            ));
+        };
+        let expires: f64 = expires
+            .parse()
+            .map_err(|_| format!("line {}: invalid expiry {:?}", number + 1, expires))?;
+
+        // The subdomain flag, not a leading dot, is authoritative in this format
+        let bare = domain.trim_start_matches('.');
+        cookies.push(CookieData {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: if subdomains.eq_ignore_ascii_case("TRUE") {
+                format!(".{}", bare)
+            } else {
+                bare.to_string()
+            },
+            path: path.to_string(),
+            expires: (expires > 0.0).then_some(expires),
+            http_only,
+            secure: secure.eq_ignore_ascii_case("TRUE"),
+            same_site: None,
+        });
+    }
+    Ok(cookies)
+}
+
+fn from_json(content: &str) -> Result<Vec<CookieData>, String> {
+    // Either a bare array or a storage-state object with a "cookies" array
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Cookies {
+        List(Vec<CookieData>),
+        State { cookies: Vec<CookieData> },
+    }
+
+    match serde_json::from_str(content).map_err(|e| e.to_string())? {
+        Cookies::List(cookies) | Cookies::State { cookies } => Ok(cookies
+            .into_iter()
+            .map(|c| CookieData {
+                // Exports mark session cookies with -1 or 0
+                expires: c.expires.filter(|e| *e > 0.0),
+                ..c
+            })
+            .collect()),
+    }
+}
+
+impl BrowserAgent {
+    /// List cookies, optionally only those for `domain` (and its subdomains)
+    /// or named `name`
+    pub fn get_cookies(
+        &self,
+        tab_id: Option<&str>,
+        domain: Option<&str>,
+        name: Option<&str>,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let cookies: Vec<CookieData> = match all_cookies(tab) {
+            Ok(cookies) => cookies
+                .into_iter()
+                .filter(|c| domain.is_none_or(|d| c.matches_domain(d)))
+                .filter(|c| name.is_none_or(|n| c.name == n))
+                .collect(),
+            Err(e) => return e,
+        };
+
+        ToolResult::ok(serde_json::json!({
+            "cookies": cookies,
+            "count": cookies.len()
+        }))
+    }
+
+    /// Set a cookie, for the page URL unless a URL or domain is given
+    pub fn set_cookie(&self, tab_id: Option<&str>, cookie: NewCookie) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let url = match (&cookie.url, &cookie.domain) {
+            (None, None) => {
+                let url = tab.get_url();
+                if !url.starts_with("http") {
+                    return ToolResult::err(
+                        ErrorCode::InvalidArgument,
+                        "The page has no http(s) URL to set the cookie for. Pass url or domain.",
+                    );
+                }
+                Some(url)
+            }
+            (url, _) => url.clone(),
+        };
+
+        let param = CookieParam {
+            name: cookie.name.clone(),
+            value: cookie.value,
+            url,
+            domain: cookie.domain,
+            path: cookie.path,
+            secure: Some(cookie.secure),
+            http_only: Some(cookie.http_only),
+            same_site: cookie.same_site.as_deref().and_then(same_site),
+            expires: cookie.expires,
+            priority: None,
+            same_party: None,
+            source_scheme: None,
+            source_port: None,
+            partition_key: None,
+        };
+        match set_cookies(tab, vec![param]) {
+            Ok(()) => ToolResult::ok(serde_json::json!({ "set": cookie.name })),
+            Err(e) => e,
+        }
+    }
+
+    /// Delete cookies named `name`, optionally only for `domain` and `path`
+    pub fn delete_cookie(
+        &self,
+        tab_id: Option<&str>,
+        name: &str,
+        domain: Option<&str>,
+        path: Option<&str>,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let matching: Vec<CookieData> = match all_cookies(tab) {
+            Ok(cookies) => cookies
+                .into_iter()
+                .filter(|c| c.name == name)
+                .filter(|c| domain.is_none_or(|d| c.matches_domain(d)))
+                .filter(|c| path.is_none_or(|p| c.path == p))
+                .collect(),
+            Err(e) => return e,
+        };
+
+        match delete_cookies(tab, &matching) {
+            Ok(()) => ToolResult::ok(serde_json::json!({ "deleted": matching.len() })),
+            Err(e) => e,
+        }
+    }
+
+    /// Delete every cookie, or every cookie for `domain`
+    pub fn clear_cookies(&self, tab_id: Option<&str>, domain: Option<&str>) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let cookies = match all_cookies(tab) {
+            Ok(cookies) => cookies,
+            Err(e) => return e,
+        };
+        let result = match domain {
+            Some(domain) => {
+                let matching: Vec<CookieData> = cookies
+                    .into_iter()
+                    .filter(|c| c.matches_domain(domain))
+                    .collect();
+                delete_cookies(tab, &matching).map(|_| matching.len())
+            }
+            None => tab
+                .call_method(Network::ClearBrowserCookies(None))
+                .map(|_| cookies.len())
+                .map_err(|e| {
+                    ToolResult::browser_err(ErrorCode::Internal, "Failed to clear cookies", &*e)
+                }),
+        };
+
+        match result {
+            Ok(cleared) => ToolResult::ok(serde_json::json!({ "cleared": cleared })),
+            Err(e) => e,
+        }
+    }
+
+    /// Export cookies as JSON or cookies.txt, returned inline or written to
+    /// a managed file when `save` is set
+    pub fn export_cookies(
+        &self,
+        tab_id: Option<&str>,
+        format: CookieFormat,
+        domain: Option<&str>,
+        save: bool,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let cookies: Vec<CookieData> = match all_cookies(tab) {
+            Ok(cookies) => cookies
+                .into_iter()
+                .filter(|c| domain.is_none_or(|d| c.matches_domain(d)))
+                .collect(),
+            Err(e) => return e,
+        };
+        let content = match format {
+            CookieFormat::Json => to_json(&cookies),
+            CookieFormat::Netscape => to_netscape(&cookies),
+        };
+
+        if !save {
+            return ToolResult::ok(serde_json::json!({
+                "content": content,
+                "count": cookies.len()
+            }));
+        }
+        let extension = match format {
+            CookieFormat::Json => "json",
+            CookieFormat::Netscape => "txt",
+        };
+        match self.files.write(content.as_bytes(), extension) {
+            Ok((id, path)) => ToolResult::ok(serde_json::json!({
+                "file_id": id,
+                "path": path.display().to_string(),
+                "count": cookies.len()
+            })),
+            Err(e) => ToolResult::err(
+                ErrorCode::Internal,
+                format!("Failed to write cookie file: {}", e),
+            ),
+        }
+    }
+
+    /// Import cookies from JSON or cookies.txt, given inline or as the ID of
+    /// a managed file. The format is detected when not given.
+    pub fn import_cookies(
+        &self,
+        tab_id: Option<&str>,
+        format: Option<CookieFormat>,
+        content: Option<&str>,
+        file_id: Option<&str>,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let content = match (content, file_id) {
+            (Some(content), _) => content.to_string(),
+            (None, Some(file_id)) => match self.files.read(file_id) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) => return e,
+            },
+            (None, None) => {
+                return ToolResult::err(ErrorCode::InvalidArgument, "Pass content or file_id")
+            }
+        };
+
+        let format = format.unwrap_or_else(|| {
+            if content.trim_start().starts_with(['[', '{']) {
+                CookieFormat::Json
+            } else {
+                CookieFormat::Netscape
+            }
+        });
+        let parsed = match format {
+            CookieFormat::Json => from_json(&content),
+            CookieFormat::Netscape => from_netscape(&content),
+        };
+        let cookies = match parsed {
+            Ok(cookies) => cookies,
+            Err(e) => {
+                return ToolResult::err(
+                    ErrorCode::InvalidArgument,
+                    format!("Invalid cookie file: {}", e),
+                )
+            }
+        };
+
+        match set_cookies(tab, cookies.iter().map(CookieData::to_param).collect()) {
+            Ok(()) => ToolResult::ok(serde_json::json!({ "imported": cookies.len() })),
+            Err(e) => e,
+        }
+    }
+
+    /// Read localStorage or sessionStorage for the page's origin, or one key
+    pub fn get_storage(
+        &self,
+        tab_id: Option<&str>,
+        kind: StorageKind,
+        key: Option<&str>,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let expression = format!(
+            r#"((storage, key) => {{
+                const items = {{}};
+                if (key !== null) {{
+                    items[key] = storage.getItem(key);
+                }} else {{
+                    for (let i = 0; i < storage.length; i++) items[storage.key(i)] = storage.getItem(storage.key(i));
+                }}
+                return {{ origin: location.origin, items, length: storage.length }};
+            }})(window.{}, {})"#,
+            kind.global(),
+            serde_json::json!(key)
+        );
+        match evaluate_value(tab, &expression) {
+            Ok(value) => ToolResult::ok(value),
+            Err(e) => e,
+        }
+    }
+
+    /// Write, remove or clear localStorage or sessionStorage items for the
+    /// page's origin. Clearing happens first, then removals, then writes.
+    pub fn set_storage(
+        &self,
+        tab_id: Option<&str>,
+        kind: StorageKind,
+        items: &serde_json::Map<String, serde_json::Value>,
+        remove: &[String],
+        clear: bool,
+    ) -> ToolResult {
+        let tab = match self.get_tab(tab_id) {
+            Ok(tab) => tab,
+            Err(e) => return e,
+        };
+
+        let expression = format!(
+            r#"((storage, items, remove, clear) => {{
+                if (clear) storage.clear();
+                for (const key of remove) storage.removeItem(key);
+                for (const [key, value] of Object.entries(items)) {{
+                    storage.setItem(key, typeof value === "string" ? value : JSON.stringify(value));
+                }}
+                return {{ origin: location.origin, length: storage.length }};
+            }})(window.{}, {}, {}, {})"#,
+            kind.global(),
+            serde_json::Value::Object(items.clone()),
+            serde_json::json!(remove),
+            clear
+        );
+        match evaluate_value(tab, &expression) {
+            Ok(value) => ToolResult::ok(value),
+            Err(e) => e,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn netscape_reads_http_only_lines() {
+        let cookies =
+            from_netscape("#HttpOnly_.example.com\tTRUE\t/\tTRUE\t1893456000\tsid\tabc123\n")
+                .unwrap();
+
+        assert_eq!(cookies.len(), 1);
+        let cookie = &cookies[0];
+        assert!(cookie.http_only);
+        assert!(cookie.secure);
+        assert_eq!(cookie.domain, ".example.com");
+        assert_eq!(cookie.name, "sid");
+        assert_eq!(cookie.value, "abc123");
+        assert_eq!(cookie.expires, Some(1893456000.0));
+    }
+
+    #[test]
+    fn netscape_skips_comments_and_blank_lines() {
+        let content = "# Netscape HTTP Cookie File\r\n\
+                       # https://curl.se/docs/http-cookies.html\r\n\
+                       \r\n\
+                       \t  \n\
+                       example.com\tFALSE\t/app\tFALSE\t0\ttheme\tdark\r\n";
+        let cookies = from_netscape(content).unwrap();
+
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].name, "theme");
+        assert_eq!(cookies[0].value, "dark");
+        assert_eq!(cookies[0].path, "/app");
+        assert!(!cookies[0].http_only);
+    }
+
+    #[test]
+    fn netscape_rejects_bad_column_counts() {
+        let short = "# header\nexample.com\tFALSE\t/\tFALSE\t0\tname\n";
+        assert!(from_netscape(short).unwrap_err().starts_with("line 2:"));
+
+        let long = "example.com\tFALSE\t/\tFALSE\t0\tname\tvalue\textra\n";
+        assert!(from_netscape(long).unwrap_err().starts_with("line 1:"));
+    }
+
+    #[test]
+    fn netscape_rejects_bad_expiry() {
+        let content = "example.com\tFALSE\t/\tFALSE\tnever\tname\tvalue\n";
+        assert!(from_netscape(content)
+            .unwrap_err()
+            .contains("invalid expiry"));
+    }
+
+    #[test]
+    fn netscape_zero_expiry_is_a_session_cookie() {
+        let cookies = from_netscape("example.com\tFALSE\t/\tFALSE\t0\tname\tvalue\n").unwrap();
+        assert_eq!(cookies[0].expires, None);
+    }
+
+    #[test]
+    fn netscape_subdomain_flag_sets_the_dot() {
+        let content = ".a.example\tFALSE\t/\tFALSE\t0\thost\t1\n\
+                       b.example\tTRUE\t/\tFALSE\t0\tdomain\t2\n";
+        let cookies = from_netscape(content).unwrap();
+        assert_eq!(cookies[0].domain, "a.example");
+        assert_eq!(cookies[1].domain, ".b.example");
+    }
+
+    #[test]
+    fn netscape_round_trips() {
+        let content = "#HttpOnly_.example.com\tTRUE\t/\tTRUE\t1893456000\tsid\tabc\n\
+                       example.com\tFALSE\t/app\tFALSE\t0\ttheme\tdark\n";
+        let cookies = from_netscape(content).unwrap();
+        let again = from_netscape(&to_netscape(&cookies)).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&cookies).unwrap(),
+            serde_json::to_value(&again).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_reads_export_spellings() {
+        let content = r#"[
+            {"name": "sid", "value": "abc", "domain": ".example.com",
+             "expirationDate": 1893456000, "httpOnly": true, "secure": true, "sameSite": "no_restriction"},
+            {"name": "theme", "value": "dark", "domain": "example.com", "expires": -1}
+        ]"#;
+        let cookies = from_json(content).unwrap();
+
+        assert_eq!(cookies.len(), 2);
+        assert!(cookies[0].http_only);
+        assert_eq!(cookies[0].expires, Some(1893456000.0));
+        assert_eq!(cookies[0].same_site.as_deref(), Some("no_restriction"));
+        assert_eq!(cookies[1].path, "/");
+        assert!(!cookies[1].http_only);
+    }
+
+    #[test]
+    fn json_zero_or_negative_expiry_is_a_session_cookie() {
+        let content = r#"[
+            {"name": "a", "value": "1", "domain": "example.com", "expires": 0},
+            {"name": "b", "value": "2", "domain": "example.com", "expires": -1}
+        ]"#;
+        let cookies = from_json(content).unwrap();
+        assert!(cookies.iter().all(|c| c.expires.is_none()));
+    }
+
+    #[test]
+    fn json_reads_storage_state() {
+        let content =
+            r#"{"cookies": [{"name": "a", "value": "1", "domain": "example.com"}], "origins": []}"#;
+        let cookies = from_json(content).unwrap();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].name, "a");
+    }
+
+    #[test]
+    fn json_rejects_bad_content() {
+        assert!(from_json("not json").is_err());
+        assert!(from_json(r#"[{"value": "no name"}]"#).is_err());
+    }
+}
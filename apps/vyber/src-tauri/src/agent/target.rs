@@ -0,0 +1,228 @@
+/**
+ * VybeR Agent Element Targeting
+ *
+ * Tools that act on an element accept a CSS selector, visible text, or a
+ * ref from the latest snapshot. This resolves any of them to an Element.
+ * Selectors and text also search open shadow roots, and `host >>> inner`
+ * looks for `inner` inside `host`'s shadow root. A `frame` scopes the search
+ * to an iframe's document (see `frames`).
+ */
+
+use super::frames::call_in_frame;
+use super::snapshot::RefWorld;
+use super::{ErrorCode, ToolResult};
+use headless_chrome::browser::tab::element::Element;
+use headless_chrome::protocol::cdp::DOM;
+use headless_chrome::Tab;
+use serde::Deserialize;
+use std::fmt;
+
+/// Called on a document with `{ selector }` or `{ text }`. Returns the
+/// first matching element, null when none matches, or false for an invalid
+/// selector. Text matches an element's own text nodes, as XPath's `text()`
+/// would.
+const FIND_JS: &str = r#"
+function (query) {
+  const doc = this;
+  // The root and every open shadow root inside it, in document order
+  const roots = (root) => {
+    const found = [root];
+    const walker = doc.createTreeWalker(root, NodeFilter.SHOW_ELEMENT);
+    for (let el = walker.nextNode(); el; el = walker.nextNode()) {
+      if (el.shadowRoot) found.push(...roots(el.shadowRoot));
+    }
+    return found;
+  };
+
+  if (query.selector !== undefined) {
+    const segments = query.selector.split(">>>").map((segment) => segment.trim());
+    let scopes = [doc];
+    try {
+      for (const [i, segment] of segments.entries()) {
+        const matches = scopes.flatMap((scope) =>
+          roots(scope).flatMap((root) => Array.from(root.querySelectorAll(segment))));
+        if (i === segments.length - 1) return matches[0] ?? null;
+        scopes = matches.map((el) => el.shadowRoot).filter(Boolean);
+      }
+    } catch (e) {
+      if (e.name === "SyntaxError") return false;
+      throw e;
+    }
+  }
+
+  const skip = new Set(["SCRIPT", "STYLE", "NOSCRIPT", "TEMPLATE"]);
+  for (const root of roots(doc)) {
+    const walker = doc.createTreeWalker(root, NodeFilter.SHOW_TEXT);
+    for (let node = walker.nextNode(); node; node = walker.nextNode()) {
+      if (skip.has(node.parentElement?.tagName)) continue;
+      if (node.data.includes(query.text)) return node.parentElement ?? node.parentNode.host;
+    }
+  }
+  return null;
+}
+"#;
+
+/// The element a tool acts on
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "TargetInput")]
+pub struct Target {
+    pub locator: Locator,
+    /// Frame ID, name or URL fragment of the iframe to search, or None for
+    /// the top document
+    pub frame: Option<String>,
+}
+
+/// How the element is found
+#[derive(Debug, Clone)]
+pub enum Locator {
+    Selector(String),
+    Text(String),
+    /// A ref such as `e12` handed out by the snapshot tool
+    Ref(String),
+}
+
+/// The `selector` / `text` / `ref` / `frame` fields as they appear in tool
+/// input
+#[derive(Deserialize)]
+struct TargetInput {
+    selector: Option<String>,
+    text: Option<String>,
+    #[serde(rename = "ref")]
+    element_ref: Option<String>,
+    frame: Option<String>,
+}
+
+impl TryFrom<TargetInput> for Target {
+    type Error = &'static str;
+
+    fn try_from(input: TargetInput) -> Result<Self, Self::Error> {
+        let locator = match (input.element_ref, input.selector, input.text) {
+            (Some(_), _, _) if input.frame.is_some() => {
+                return Err("a ref already identifies its element; don't combine it with frame")
+            }
+            (Some(element_ref), _, _) => Locator::Ref(element_ref),
+            (None, Some(selector), _) => Locator::Selector(selector),
+            (None, None, Some(text)) => Locator::Text(text),
+            (None, None, None) => return Err("must provide selector, text or ref"),
+        };
+        Ok(Self {
+            locator,
+            frame: input.frame,
+        })
+    }
+}
+
+/// A target for tools where the element is optional. Flattening an
+/// `Option<Target>` would turn every invalid target into None, so this
+/// reports the error instead and is only None when no field is given.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "TargetInput")]
+pub struct OptionalTarget(pub Option<Target>);
+
+impl TryFrom<TargetInput> for OptionalTarget {
+    type Error = &'static str;
+
+    fn try_from(input: TargetInput) -> Result<Self, Self::Error> {
+        match (
+            &input.selector,
+            &input.text,
+            &input.element_ref,
+            &input.frame,
+        ) {
+            (None, None, None, None) => Ok(Self(None)),
+            (None, None, None, Some(_)) => Err("frame needs a selector or text to look for"),
+            _ => Target::try_from(input).map(|target| Self(Some(target))),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.locator {
+            Locator::Selector(selector) => write!(f, "{}", selector)?,
+            Locator::Text(text) => write!(f, "text '{}'", text)?,
+            Locator::Ref(element_ref) => write!(f, "ref {}", element_ref)?,
+        }
+        if let Some(frame) = &self.frame {
+            write!(f, " in frame {}", frame)?;
+        }
+        Ok(())
+    }
+}
+
+impl Target {
+    /// A snapshot ref in the top document
+    pub fn element_ref(element_ref: String) -> Self {
+        Self {
+            locator: Locator::Ref(element_ref),
+            frame: None,
+        }
+    }
+
+    /// Find the element in `tab`, looking refs up in its ref world
+    pub fn resolve<'a>(&self, tab: &'a Tab, refs: &RefWorld) -> Result<Element<'a>, ToolResult> {
+        let query = match &self.locator {
+            Locator::Ref(element_ref) => return resolve_ref(tab, refs, element_ref),
+            Locator::Selector(selector) => serde_json::json!({ "selector": selector }),
+            Locator::Text(text) => serde_json::json!({ "text": text }),
+        };
+
+        let found = call_in_frame(tab, self.frame.as_deref(), FIND_JS, query, false)?;
+        if found.value == Some(serde_json::Value::Bool(false)) {
+            return Err(ToolResult::err(
+                ErrorCode::InvalidSelector,
+                format!("Invalid selector: {}", self),
+            ));
+        }
+        let not_found = || {
+            ToolResult::err(
+                ErrorCode::ElementNotFound,
+                format!("Element not found: {}", self),
+            )
+        };
+        let object_id = found.object_id.ok_or_else(not_found)?;
+        element_for_object(tab, object_id).map_err(|_| not_found())
+    }
+}
+
+/// Look up a snapshot ref in the ref table. The table lives in the tab's ref
+/// world, out of reach of page scripts, and goes with it on navigation.
+fn resolve_ref<'a>(
+    tab: &'a Tab,
+    refs: &RefWorld,
+    element_ref: &str,
+) -> Result<Element<'a>, ToolResult> {
+    let stale = || {
+        ToolResult::err(
+            ErrorCode::ElementNotFound,
+            format!(
+                "Unknown or stale ref: {}. Take a new snapshot to get current refs.",
+                element_ref
+            ),
+        )
+    };
+
+    let expression = format!(
+        "(() => {{ const el = window.__vyberRefs?.get({})?.deref(); return el && el.isConnected ? el : null; }})()",
+        serde_json::Value::from(element_ref)
+    );
+    let object = refs.lookup(tab, &expression, false)?.ok_or_else(stale)?;
+    let object_id = object.object_id.ok_or_else(stale)?;
+
+    element_for_object(tab, object_id).map_err(|_| stale())
+}
+
+/// Turn a handle to a page-side DOM node into an Element
+pub(super) fn element_for_object(tab: &Tab, object_id: String) -> Result<Element<'_>, ToolResult> {
+    // DOM.requestNode only hands out node IDs once the document has been requested
+    let node_id = tab
+        .get_document()
+        .and_then(|_| tab.call_method(DOM::RequestNode { object_id }))
+        .map_err(|e| {
+            ToolResult::browser_err(ErrorCode::ElementNotFound, "Failed to resolve element", &*e)
+        })?
+        .node_id;
+
+    Element::new(tab, node_id)
+        .map_err(|e| ToolResult::browser_err(ErrorCode::ElementNotFound, "Element is gone", &*e))
+}
@@ -0,0 +1,1718 @@
+/**
+ * VybeR Agent Tool Registry
+ *
+ * Every tool the native agent exposes, with the JSON Schema for its input.
+ * agent_list_tools hands these definitions to the LLM and agent_execute
+ * dispatches through them, so the tool list and the backend can't drift.
+ */
+
+use super::input::DragDestination;
+use super::intercept::RuleInput;
+use super::network::{self, NetworkFilter};
+use super::storage::{CookieFormat, NewCookie, StorageKind};
+use super::{
+    BrowserAgent, ConsoleLevel, DialogAnswer, DialogPolicy, ErrorCode, ExtractMode, LinkFilter,
+    OptionalTarget, PageRequest, PdfOptions, ScreenshotOptions, Target, ToolError, ToolResult,
+    WaitFor,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+/// Work queued on a session's worker thread
+pub type Job = Box<dyn FnOnce(&mut BrowserAgent) -> ToolResult + Send>;
+
+type Binder = Box<dyn Fn(Value) -> Result<Job, ToolError> + Send + Sync>;
+
+/// A tool definition, serialized in the shape Claude's tool_use API expects
+#[derive(Serialize)]
+pub struct Tool {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub input_schema: Value,
+    #[serde(skip)]
+    bind: Binder,
+}
+
+impl Tool {
+    fn new<I, F>(name: &'static str, description: &'static str, input_schema: Value, run: F) -> Self
+    where
+        I: DeserializeOwned + Send + 'static,
+        F: Fn(&mut BrowserAgent, I) -> ToolResult + Copy + Send + Sync + 'static,
+    {
+        Self {
+            name,
+            description,
+            input_schema,
+            bind: Box::new(move |input| {
+                // Tools without required fields may be called with no input at all
+                let input = if input.is_null() { json!({}) } else { input };
+                let input: I = serde_json::from_value(input).map_err(|e| {
+                    ToolError::new(
+                        ErrorCode::InvalidArgument,
+                        format!("Invalid input for {}: {}", name, e),
+                    )
+                })?;
+                Ok(Box::new(move |agent: &mut BrowserAgent| run(agent, input)) as Job)
+            }),
+        }
+    }
+
+    /// Validate `input` and package the call as a job for a session worker
+    pub fn bind(&self, input: Value) -> Result<Job, ToolError> {
+        (self.bind)(input)
+    }
+}
+
+/// All registered tools
+pub fn registry() -> &'static [Tool] {
+    static TOOLS: OnceLock<Vec<Tool>> = OnceLock::new();
+    TOOLS.get_or_init(build_registry)
+}
+
+/// Look up a tool by name
+pub fn find(name: &str) -> Option<&'static Tool> {
+    registry().iter().find(|tool| tool.name == name)
+}
+
+/// Build an object schema from its properties and required field names
+fn object_schema(properties: Value, required: &[&str]) -> Value {
+    if required.is_empty() {
+        json!({ "type": "object", "properties": properties })
+    } else {
+        json!({ "type": "object", "properties": properties, "required": required })
+    }
+}
+
+/// The optional `tab_id` property shared by every page tool
+fn tab_id_property() -> Value {
+    json!({
+        "type": "string",
+        "description": "Optional agent tab ID (from open_tab or list_tabs). Defaults to the active tab."
+    })
+}
+
+/// The `ref` property accepted by tools that act on an element
+fn ref_property() -> Value {
+    json!({
+        "type": "string",
+        "description": "Element ref from the latest snapshot, e.g. \"e12\" (alternative to selector). Refs stay valid until the page navigates."
+    })
+}
+
+/// The `frame` property accepted by tools that act on an element
+fn frame_property() -> Value {
+    json!({
+        "type": "string",
+        "description": "Frame ID, name or part of the URL of an iframe to look in (from list_frames), instead of the top document. Selectors and text also search open shadow roots; use \"host >>> inner\" to look inside a specific shadow host."
+    })
+}
+
+/// The `switch_to_popup` property accepted by the click tools
+fn switch_to_popup_property() -> Value {
+    json!({
+        "type": "boolean",
+        "description": "If the click opens a new tab or window, make it the active tab. Default false."
+    })
+}
+
+/// Filter properties shared by network_requests and export_har
+fn network_filter_properties() -> [(&'static str, Value); 4] {
+    [
+        (
+            "url_pattern",
+            json!({ "type": "string", "description": "Only requests whose URL matches this regular expression." }),
+        ),
+        (
+            "resource_type",
+            json!({
+                "type": "string",
+                "description": "Only requests of this type: document, stylesheet, image, media, font, script, xhr, fetch, websocket, other, ..."
+            }),
+        ),
+        (
+            "status",
+            json!({ "type": "number", "description": "Only responses with this HTTP status." }),
+        ),
+        (
+            "failed_only",
+            json!({ "type": "boolean", "description": "Only requests that failed to load or got a 4xx/5xx status." }),
+        ),
+    ]
+}
+
+/// Condition properties shared by the wait tool and `wait_for`
+fn wait_condition_properties() -> Value {
+    json!({
+        "selector": { "type": "string", "description": "Wait for the element matching this CSS selector to reach `state`." },
+        "state": {
+            "type": "string",
+            "enum": ["attached", "visible", "hidden", "detached"],
+            "description": "With selector: attached (in the page, the default), visible, hidden (invisible or gone), or detached (gone)."
+        },
+        "text": { "type": "string", "description": "Wait for this text to be visible on the page." },
+        "url": { "type": "string", "description": "Wait for the page URL to match this glob, where * matches anything." },
+        "url_regex": { "type": "string", "description": "Wait for the page URL to match this regular expression." },
+        "predicate": { "type": "string", "description": "Wait for this JavaScript expression to become truthy." },
+        "network_idle": { "type": "boolean", "description": "Wait until no requests have been loading for idle_ms." },
+        "idle_ms": { "type": "number", "description": "Quiet time that counts as network idle. Default is 500." },
+        "load_state": {
+            "type": "string",
+            "enum": ["dom_content_loaded", "load"],
+            "description": "Wait for the document to reach DOMContentLoaded or load."
+        },
+        "timeout": { "type": "number", "description": "Maximum milliseconds to wait. Default is 5000." }
+    })
+}
+
+/// The `wait_for` property accepted by tools that act on the page
+fn wait_for_property() -> Value {
+    json!({
+        "type": "object",
+        "properties": wait_condition_properties(),
+        "description": "Condition to wait for after the action, instead of a fixed short delay, e.g. {\"network_idle\": true} or {\"selector\": \".results\", \"state\": \"visible\"}. Give one condition; with only a timeout, sleeps that long."
+    })
+}
+
+#[derive(Deserialize)]
+struct TabInput {
+    tab_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NavigateInput {
+    tab_id: Option<String>,
+    url: String,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct ExtractTextInput {
+    tab_id: Option<String>,
+    selector: Option<String>,
+    #[serde(default)]
+    mode: ExtractMode,
+    frame: Option<String>,
+    #[serde(default)]
+    include_frames: bool,
+    #[serde(flatten)]
+    page: PageRequest,
+}
+
+#[derive(Deserialize)]
+struct ExtractLinksInput {
+    tab_id: Option<String>,
+    selector: Option<String>,
+    frame: Option<String>,
+    max_links: Option<usize>,
+    #[serde(flatten)]
+    filter: LinkFilter,
+}
+
+#[derive(Deserialize)]
+struct SnapshotInput {
+    tab_id: Option<String>,
+    #[serde(default)]
+    viewport_only: bool,
+    max_elements: Option<usize>,
+}
+
+/// Input for tools that act on a single element
+#[derive(Deserialize)]
+struct ElementInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    target: Target,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct ClickInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    target: Target,
+    wait_for: Option<WaitFor>,
+    #[serde(default)]
+    switch_to_popup: bool,
+}
+
+#[derive(Deserialize)]
+struct ClickAtInput {
+    tab_id: Option<String>,
+    x: f64,
+    y: f64,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct ClickMarkInput {
+    tab_id: Option<String>,
+    mark: u32,
+    wait_for: Option<WaitFor>,
+    #[serde(default)]
+    switch_to_popup: bool,
+}
+
+#[derive(Deserialize)]
+struct DragInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    target: Target,
+    to: Option<Target>,
+    offset_x: Option<f64>,
+    offset_y: Option<f64>,
+    wait_for: Option<WaitFor>,
+}
+
+impl DragInput {
+    fn destination(&self) -> Result<DragDestination, ToolResult> {
+        match (&self.to, self.offset_x, self.offset_y) {
+            (Some(to), _, _) => Ok(DragDestination::Target(to.clone())),
+            (None, None, None) => Err(ToolResult::err(
+                ErrorCode::InvalidArgument,
+                "Pass to, or offset_x and/or offset_y",
+            )),
+            (None, x, y) => Ok(DragDestination::Offset {
+                x: x.unwrap_or(0.0),
+                y: y.unwrap_or(0.0),
+            }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PressKeyInput {
+    tab_id: Option<String>,
+    key: String,
+    #[serde(flatten)]
+    target: OptionalTarget,
+    count: Option<u32>,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct SelectOptionInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    target: Target,
+    values: Vec<String>,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct SetCheckedInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    target: Target,
+    checked: Option<bool>,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct FillFormInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    target: Target,
+    value: String,
+    #[serde(default)]
+    submit: bool,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct FillFieldsInput {
+    tab_id: Option<String>,
+    form: Option<String>,
+    fields: serde_json::Map<String, Value>,
+    #[serde(default)]
+    submit: bool,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct ListFormsInput {
+    tab_id: Option<String>,
+    max_options: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct ScreenshotInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    options: ScreenshotOptions,
+}
+
+#[derive(Deserialize)]
+struct PrintPdfInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    options: PdfOptions,
+}
+
+#[derive(Deserialize)]
+struct ScrollInput {
+    tab_id: Option<String>,
+    direction: Option<String>,
+    amount: Option<i32>,
+    wait_for: Option<WaitFor>,
+}
+
+#[derive(Deserialize)]
+struct WaitInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    wait: WaitFor,
+}
+
+#[derive(Deserialize)]
+struct EvaluateJsInput {
+    tab_id: Option<String>,
+    script: String,
+    frame: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NetworkCaptureInput {
+    tab_id: Option<String>,
+    capture_bodies: Option<bool>,
+    max_body_size: Option<usize>,
+    #[serde(default)]
+    clear: bool,
+}
+
+/// Filters shared by network_requests and export_har
+#[derive(Deserialize)]
+struct NetworkFilterInput {
+    url_pattern: Option<String>,
+    resource_type: Option<String>,
+    status: Option<u32>,
+    #[serde(default)]
+    failed_only: bool,
+}
+
+impl NetworkFilterInput {
+    fn compile(self) -> Result<NetworkFilter, ToolResult> {
+        Ok(NetworkFilter {
+            url_pattern: network::url_pattern(self.url_pattern.as_deref())?,
+            resource_type: self.resource_type,
+            status: self.status,
+            failed_only: self.failed_only,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct NetworkRequestsInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    filter: NetworkFilterInput,
+    limit: Option<usize>,
+    #[serde(default)]
+    include_headers: bool,
+    #[serde(default)]
+    include_bodies: bool,
+}
+
+#[derive(Deserialize)]
+struct ExportHarInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    filter: NetworkFilterInput,
+    #[serde(default)]
+    save: bool,
+}
+
+#[derive(Deserialize)]
+struct GetConsoleInput {
+    tab_id: Option<String>,
+    level: Option<ConsoleLevel>,
+    since: Option<f64>,
+    limit: Option<usize>,
+    #[serde(default)]
+    clear: bool,
+}
+
+#[derive(Deserialize)]
+struct GetDialogsInput {
+    tab_id: Option<String>,
+    #[serde(default)]
+    open_only: bool,
+}
+
+#[derive(Deserialize)]
+struct RemoveInterceptRuleInput {
+    rule_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GetCookiesInput {
+    tab_id: Option<String>,
+    domain: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SetCookieInput {
+    tab_id: Option<String>,
+    #[serde(flatten)]
+    cookie: NewCookie,
+}
+
+#[derive(Deserialize)]
+struct DeleteCookieInput {
+    tab_id: Option<String>,
+    name: String,
+    domain: Option<String>,
+    path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ClearCookiesInput {
+    tab_id: Option<String>,
+    domain: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExportCookiesInput {
+    tab_id: Option<String>,
+    #[serde(default)]
+    format: CookieFormat,
+    domain: Option<String>,
+    #[serde(default)]
+    save: bool,
+}
+
+#[derive(Deserialize)]
+struct ImportCookiesInput {
+    tab_id: Option<String>,
+    format: Option<CookieFormat>,
+    content: Option<String>,
+    file_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GetStorageInput {
+    tab_id: Option<String>,
+    #[serde(rename = "type")]
+    kind: StorageKind,
+    key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SetStorageInput {
+    tab_id: Option<String>,
+    #[serde(rename = "type")]
+    kind: StorageKind,
+    #[serde(default)]
+    items: serde_json::Map<String, Value>,
+    #[serde(default)]
+    remove: Vec<String>,
+    #[serde(default)]
+    clear: bool,
+}
+
+#[derive(Deserialize)]
+struct SearchGoogleInput {
+    tab_id: Option<String>,
+    query: String,
+}
+
+#[derive(Deserialize)]
+struct OpenTabInput {
+    url: Option<String>,
+    activate: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct SwitchTabInput {
+    tab_id: String,
+}
+
+#[derive(Deserialize)]
+struct AdoptPopupInput {
+    target_id: Option<String>,
+    activate: Option<bool>,
+}
+
+fn build_registry() -> Vec<Tool> {
+    vec![
+        Tool::new(
+            "navigate",
+            "Navigate to a URL in the current tab. Use this to open websites or go to specific pages.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "url": { "type": "string", "description": "The URL to navigate to." }
+                }),
+                &["url"],
+            ),
+            |agent, input: NavigateInput| {
+                agent.navigate(input.tab_id.as_deref(), &input.url, input.wait_for.as_ref())
+            },
+        ),
+        Tool::new(
+            "extract_text",
+            "Extract text content from the current page. Use this to read and understand page content. Use mode \"readable\" to get just the main article as Markdown, without navigation, footers and banners.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector to extract text from a specific element. If not provided, extracts all visible text."
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["text", "readable"],
+                        "description": "\"text\" returns all page text. \"readable\" returns the main content as Markdown (headings, lists, links, code, tables) with title, byline, publish date and language. Default is text."
+                    },
+                    "frame": {
+                        "type": "string",
+                        "description": "Frame ID, name or part of the URL of an iframe to read (from list_frames), instead of the top document."
+                    },
+                    "include_frames": {
+                        "type": "boolean",
+                        "description": "Include the content of same-origin iframes where they appear in the page. Default false."
+                    },
+                    "max_length": {
+                        "type": "number",
+                        "description": "Chunk size in characters. Long pages are split into chunks; the result includes chunk, total_chunks and next_cursor. Default is 8000."
+                    },
+                    "max_tokens": {
+                        "type": "number",
+                        "description": "Chunk size as an estimated token count, instead of max_length."
+                    },
+                    "chunk": {
+                        "type": "number",
+                        "description": "Zero-based chunk to return. Default is 0."
+                    },
+                    "cursor": {
+                        "type": "string",
+                        "description": "next_cursor from a previous extract_text call, to continue reading where it left off."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: ExtractTextInput| {
+                agent.extract_text(
+                    input.tab_id.as_deref(),
+                    input.selector.as_deref(),
+                    input.mode,
+                    input.frame.as_deref(),
+                    input.include_frames,
+                    &input.page,
+                )
+            },
+        ),
+        Tool::new(
+            "extract_links",
+            "Extract links from the current page with their text and absolute URLs. Duplicates are merged. Each link says whether it is visible and whether it is internal (same origin as the page), plus any rel, target and title.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector to limit link extraction to a specific area."
+                    },
+                    "frame": {
+                        "type": "string",
+                        "description": "Frame ID, name or part of the URL of an iframe to extract links from (from list_frames), instead of the top document."
+                    },
+                    "max_links": {
+                        "type": "number",
+                        "description": "Maximum number of links to return, after filtering. Default is 50."
+                    },
+                    "same_origin": {
+                        "type": "boolean",
+                        "description": "Only return links on the same origin as the page. Default false."
+                    },
+                    "schemes": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Only return links with these URL schemes, e.g. [\"https\"] or [\"mailto\"]."
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "Only return links whose absolute URL matches this regular expression."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: ExtractLinksInput| {
+                agent.extract_links(
+                    input.tab_id.as_deref(),
+                    input.selector.as_deref(),
+                    input.frame.as_deref(),
+                    &input.filter,
+                    input.max_links.unwrap_or(50),
+                )
+            },
+        ),
+        Tool::new(
+            "snapshot",
+            "List the visible interactive elements on the page (buttons, links, inputs, ...) with headings for context. Each element has a ref like e12 that click, fill_form and the other element tools accept instead of a selector.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "viewport_only": {
+                        "type": "boolean",
+                        "description": "Only include elements currently scrolled into view. Default false."
+                    },
+                    "max_elements": {
+                        "type": "number",
+                        "description": "Maximum number of elements to list. Default is 300."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: SnapshotInput| {
+                agent.snapshot(
+                    input.tab_id.as_deref(),
+                    input.viewport_only,
+                    input.max_elements.unwrap_or(300),
+                )
+            },
+        ),
+        Tool::new(
+            "click",
+            "Click on an element on the page by snapshot ref, CSS selector or visible text. Tabs or windows the click opens become agent tabs and are listed in the result as popups.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of the element to click." },
+                    "text": {
+                        "type": "string",
+                        "description": "Visible text of the element to click (alternative to selector)."
+                    },
+                    "switch_to_popup": switch_to_popup_property()
+                }),
+                &[],
+            ),
+            |agent, input: ClickInput| {
+                agent.click(
+                    input.tab_id.as_deref(),
+                    &input.target,
+                    input.wait_for.as_ref(),
+                    input.switch_to_popup,
+                )
+            },
+        ),
+        Tool::new(
+            "hover",
+            "Move the mouse over an element, e.g. to open a hover menu or tooltip.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of the element to hover." },
+                    "text": {
+                        "type": "string",
+                        "description": "Visible text of the element to hover (alternative to selector)."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: ElementInput| {
+                agent.hover(input.tab_id.as_deref(), &input.target, input.wait_for.as_ref())
+            },
+        ),
+        Tool::new(
+            "double_click",
+            "Double-click an element, e.g. to select a word or open an item.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of the element to double-click." },
+                    "text": {
+                        "type": "string",
+                        "description": "Visible text of the element to double-click (alternative to selector)."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: ElementInput| {
+                agent.double_click(input.tab_id.as_deref(), &input.target, input.wait_for.as_ref())
+            },
+        ),
+        Tool::new(
+            "right_click",
+            "Right-click an element, e.g. to open its context menu.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of the element to right-click." },
+                    "text": {
+                        "type": "string",
+                        "description": "Visible text of the element to right-click (alternative to selector)."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: ElementInput| {
+                agent.right_click(input.tab_id.as_deref(), &input.target, input.wait_for.as_ref())
+            },
+        ),
+        Tool::new(
+            "click_at",
+            "Click a point in the viewport, e.g. one picked out of a screenshot. Coordinates are CSS pixels from the top-left of the viewport, like the boxes in a marked screenshot.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "x": { "type": "number", "description": "Distance from the left edge of the viewport." },
+                    "y": { "type": "number", "description": "Distance from the top edge of the viewport." }
+                }),
+                &["x", "y"],
+            ),
+            |agent, input: ClickAtInput| {
+                agent.click_at(input.tab_id.as_deref(), input.x, input.y, input.wait_for.as_ref())
+            },
+        ),
+        Tool::new(
+            "click_mark",
+            "Click an element by its number in the latest screenshot taken with marks. Tabs or windows the click opens are listed in the result as popups.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "mark": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "The number drawn on the element."
+                    },
+                    "switch_to_popup": switch_to_popup_property()
+                }),
+                &["mark"],
+            ),
+            |agent, input: ClickMarkInput| {
+                agent.click_mark(
+                    input.tab_id.as_deref(),
+                    input.mark,
+                    input.wait_for.as_ref(),
+                    input.switch_to_popup,
+                )
+            },
+        ),
+        Tool::new(
+            "drag",
+            "Drag an element with the mouse and drop it on another element or at an offset, e.g. to move a slider or reorder a list.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of the element to drag." },
+                    "text": {
+                        "type": "string",
+                        "description": "Visible text of the element to drag (alternative to selector)."
+                    },
+                    "to": {
+                        "type": "object",
+                        "properties": {
+                            "ref": ref_property(),
+                            "frame": frame_property(),
+                            "selector": { "type": "string" },
+                            "text": { "type": "string" }
+                        },
+                        "description": "Element to drop onto, by ref, selector or text."
+                    },
+                    "offset_x": { "type": "number", "description": "Pixels to drag right (negative for left), instead of to." },
+                    "offset_y": { "type": "number", "description": "Pixels to drag down (negative for up), instead of to." }
+                }),
+                &[],
+            ),
+            |agent, input: DragInput| match input.destination() {
+                Ok(destination) => agent.drag(
+                    input.tab_id.as_deref(),
+                    &input.target,
+                    &destination,
+                    input.wait_for.as_ref(),
+                ),
+                Err(e) => e,
+            },
+        ),
+        Tool::new(
+            "press_key",
+            "Press a key or key chord, e.g. \"Enter\", \"Escape\", \"Tab\", \"ArrowDown\" or \"Control+A\". Optionally focus an element first.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "key": {
+                        "type": "string",
+                        "description": "Key name, optionally with modifiers joined by +: Control, Shift, Alt, Meta, or ControlOrMeta for the platform's shortcut key."
+                    },
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of an element to focus before pressing." },
+                    "text": {
+                        "type": "string",
+                        "description": "Visible text of an element to focus before pressing (alternative to selector)."
+                    },
+                    "count": { "type": "number", "description": "How many times to press. Default is 1." }
+                }),
+                &["key"],
+            ),
+            |agent, input: PressKeyInput| {
+                agent.press_key(
+                    input.tab_id.as_deref(),
+                    &input.key,
+                    input.target.0.as_ref(),
+                    input.count.unwrap_or(1).min(100),
+                    input.wait_for.as_ref(),
+                )
+            },
+        ),
+        Tool::new(
+            "select_option",
+            "Choose options in a <select> dropdown by value or visible label. Options not listed are deselected.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of the <select>." },
+                    "text": {
+                        "type": "string",
+                        "description": "Visible text of the <select>'s label or one of its options (alternative to selector)."
+                    },
+                    "values": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Values or labels of the options to choose. More than one only for multi-selects."
+                    }
+                }),
+                &["values"],
+            ),
+            |agent, input: SelectOptionInput| {
+                agent.select_option(
+                    input.tab_id.as_deref(),
+                    &input.target,
+                    &input.values,
+                    input.wait_for.as_ref(),
+                )
+            },
+        ),
+        Tool::new(
+            "set_checked",
+            "Check or uncheck a checkbox, radio button or switch. Does nothing if it is already in that state.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of the checkbox or radio." },
+                    "text": {
+                        "type": "string",
+                        "description": "Visible text of the checkbox or radio (alternative to selector)."
+                    },
+                    "checked": { "type": "boolean", "description": "Whether it should be checked. Default true." }
+                }),
+                &[],
+            ),
+            |agent, input: SetCheckedInput| {
+                agent.set_checked(
+                    input.tab_id.as_deref(),
+                    &input.target,
+                    input.checked.unwrap_or(true),
+                    input.wait_for.as_ref(),
+                )
+            },
+        ),
+        Tool::new(
+            "fill_form",
+            "Fill in a form field with text, replacing what it already contains.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "selector": { "type": "string", "description": "CSS selector of the input field." },
+                    "value": { "type": "string", "description": "The text to fill in." },
+                    "submit": {
+                        "type": "boolean",
+                        "description": "Whether to submit the form after filling. Default false."
+                    }
+                }),
+                &["value"],
+            ),
+            |agent, input: FillFormInput| {
+                agent.fill_form(
+                    input.tab_id.as_deref(),
+                    &input.target,
+                    &input.value,
+                    input.submit,
+                    input.wait_for.as_ref(),
+                )
+            },
+        ),
+        Tool::new(
+            "list_forms",
+            "List the forms on the page, and fields outside any form, with each field's label, name, type, required flag, current value, options and a selector to fill it with. Password values are masked.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "max_options": {
+                        "type": "number",
+                        "description": "Maximum number of options to list per select. Default is 50."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: ListFormsInput| {
+                agent.list_forms(input.tab_id.as_deref(), input.max_options.unwrap_or(50))
+            },
+        ),
+        Tool::new(
+            "fill_fields",
+            "Fill several form fields at once, replacing any existing values, and optionally submit the form with its own submit button. Reports each field's result and any validation messages the page shows.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "fields": {
+                        "type": "object",
+                        "additionalProperties": {
+                            "type": ["string", "number", "boolean", "array"],
+                            "items": { "type": "string" }
+                        },
+                        "description": "Field to value, filled in order. Fields are matched by name or id, then label text, then placeholder, then CSS selector; prefix a key with name:, label:, placeholder: or selector: to use only that. Checkboxes take true/false, radios and selects the option's value or label (an array for multi-selects), dates YYYY-MM-DD."
+                    },
+                    "form": {
+                        "type": "string",
+                        "description": "CSS selector of the form to look for fields in. Defaults to the whole page."
+                    },
+                    "submit": {
+                        "type": "boolean",
+                        "description": "Submit the form after filling, if every field succeeded. Default false."
+                    }
+                }),
+                &["fields"],
+            ),
+            |agent, input: FillFieldsInput| {
+                agent.fill_fields(
+                    input.tab_id.as_deref(),
+                    input.form.as_deref(),
+                    &input.fields,
+                    input.submit,
+                    input.wait_for.as_ref(),
+                )
+            },
+        ),
+        Tool::new(
+            "screenshot",
+            "Take a screenshot of the current page, the full page, one element (by selector, text or ref) or a clip rectangle. Returned inline as a data URL unless save is given, which is better for large captures.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "selector": { "type": "string", "description": "CSS selector of an element to capture on its own." },
+                    "text": { "type": "string", "description": "Visible text of an element to capture (alternative to selector)." },
+                    "ref": ref_property(),
+                    "frame": frame_property(),
+                    "clip": {
+                        "type": "object",
+                        "description": "Rectangle to capture, in CSS pixels from the top-left of the page.",
+                        "properties": {
+                            "x": { "type": "number" },
+                            "y": { "type": "number" },
+                            "width": { "type": "number" },
+                            "height": { "type": "number" }
+                        },
+                        "required": ["x", "y", "width", "height"]
+                    },
+                    "full_page": {
+                        "type": "boolean",
+                        "description": "Whether to capture the full scrollable page. Default false."
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["png", "jpeg", "webp"],
+                        "description": "Image format. Default png."
+                    },
+                    "quality": {
+                        "type": "number",
+                        "description": "Compression quality from 0 to 100, for jpeg and webp only."
+                    },
+                    "marks": {
+                        "type": "boolean",
+                        "description": "Draw a numbered box on every visible interactive element and return the marks (number, ref, role, name, selector and viewport box in CSS pixels). Use click_mark to click one. Viewport screenshots only."
+                    },
+                    "scale": {
+                        "type": "number",
+                        "description": "Device scale factor, e.g. 0.5 for a smaller image or 2 for a sharper one. Default 1, at most 4."
+                    },
+                    "save": {
+                        "type": "boolean",
+                        "description": "Write the image to a managed file and return its file_id and path instead of the image. Managed files are deleted when the session stops."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: ScreenshotInput| {
+                agent.screenshot(input.tab_id.as_deref(), &input.options)
+            },
+        ),
+        Tool::new(
+            "print_pdf",
+            "Print the current page to PDF, e.g. to archive it. Returned inline as a data URL unless save is given. Only works in headless sessions. Lengths are inches, or strings with a unit such as \"2cm\", \"10mm\" or \"96px\".",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "format": {
+                        "type": "string",
+                        "enum": ["letter", "legal", "tabloid", "ledger", "a0", "a1", "a2", "a3", "a4", "a5", "a6"],
+                        "description": "Paper size. Default letter."
+                    },
+                    "width": { "type": ["number", "string"], "description": "Paper width, with height, instead of format." },
+                    "height": { "type": ["number", "string"], "description": "Paper height, with width, instead of format." },
+                    "margin": {
+                        "type": "object",
+                        "description": "Page margins. Chrome's default is about 0.4in on each side.",
+                        "properties": {
+                            "top": { "type": ["number", "string"] },
+                            "right": { "type": ["number", "string"] },
+                            "bottom": { "type": ["number", "string"] },
+                            "left": { "type": ["number", "string"] }
+                        }
+                    },
+                    "landscape": { "type": "boolean", "description": "Landscape orientation. Default false." },
+                    "print_background": { "type": "boolean", "description": "Print background colors and images. Default false." },
+                    "scale": { "type": "number", "description": "Scale of the page rendering, from 0.1 to 2. Default 1." },
+                    "header_template": {
+                        "type": "string",
+                        "description": "HTML for the page header. Elements with class date, title, url, pageNumber or totalPages get those values filled in."
+                    },
+                    "footer_template": { "type": "string", "description": "HTML for the page footer, like header_template." },
+                    "page_ranges": { "type": "string", "description": "Pages to print, e.g. \"1-5, 8, 11-13\". Default all." },
+                    "prefer_css_page_size": { "type": "boolean", "description": "Use the page's CSS @page size instead of the paper size." },
+                    "save": {
+                        "type": "boolean",
+                        "description": "Write the PDF to a managed file and return its file_id and path instead of the PDF. Managed files are deleted when the session stops."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: PrintPdfInput| agent.print_pdf(input.tab_id.as_deref(), &input.options),
+        ),
+        Tool::new(
+            "scroll",
+            "Scroll the page in a direction.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "wait_for": wait_for_property(),
+                    "direction": {
+                        "type": "string",
+                        "enum": ["up", "down", "top", "bottom"],
+                        "description": "Direction to scroll. Default is down."
+                    },
+                    "amount": { "type": "number", "description": "Pixels to scroll. Default is 500." }
+                }),
+                &[],
+            ),
+            |agent, input: ScrollInput| {
+                agent.scroll(
+                    input.tab_id.as_deref(),
+                    input.direction.as_deref().unwrap_or("down"),
+                    input.amount,
+                    input.wait_for.as_ref(),
+                )
+            },
+        ),
+        Tool::new(
+            "wait",
+            "Wait for a condition: an element reaching a state, text appearing, the URL matching a pattern, a JavaScript predicate turning true, the network going idle, or a load state. With no condition, waits for the timeout.",
+            object_schema(
+                {
+                    let mut properties = wait_condition_properties();
+                    properties["tab_id"] = tab_id_property();
+                    properties
+                },
+                &[],
+            ),
+            |agent, input: WaitInput| agent.wait(input.tab_id.as_deref(), &input.wait),
+        ),
+        Tool::new(
+            "get_page_info",
+            "Get information about the current page including URL, title, and metadata.",
+            object_schema(json!({ "tab_id": tab_id_property() }), &[]),
+            |agent, input: TabInput| agent.get_page_info(input.tab_id.as_deref()),
+        ),
+        Tool::new(
+            "list_frames",
+            "List the page's frames (the top document and its iframes) with their frame_id, name, URL and depth. Pass a frame's ID, name or part of its URL as frame to element tools, extract_text, extract_links or evaluate_js to work inside it. Cross-site iframes run in another process; they are marked unreachable and can't be scripted.",
+            object_schema(json!({ "tab_id": tab_id_property() }), &[]),
+            |agent, input: TabInput| agent.list_frames(input.tab_id.as_deref()),
+        ),
+        Tool::new(
+            "evaluate_js",
+            "Run JavaScript in the page and return the value of the last expression.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "script": { "type": "string", "description": "The JavaScript to evaluate." },
+                    "frame": {
+                        "type": "string",
+                        "description": "Frame ID, name or part of the URL of an iframe to run in (from list_frames), instead of the top document."
+                    }
+                }),
+                &["script"],
+            ),
+            |agent, input: EvaluateJsInput| {
+                agent.evaluate_js(
+                    input.tab_id.as_deref(),
+                    &input.script,
+                    input.frame.as_deref(),
+                )
+            },
+        ),
+        Tool::new(
+            "network_capture",
+            "Configure network recording for a tab. Requests and responses are always recorded; turn on capture_bodies to also keep response bodies. Use clear to empty the log before an action you want to inspect.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "capture_bodies": {
+                        "type": "boolean",
+                        "description": "Keep response bodies for requests that finish from now on."
+                    },
+                    "max_body_size": {
+                        "type": "number",
+                        "description": "Largest response body to keep, in bytes. Default is 262144."
+                    },
+                    "clear": {
+                        "type": "boolean",
+                        "description": "Discard the requests recorded so far."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: NetworkCaptureInput| {
+                agent.network_capture(
+                    input.tab_id.as_deref(),
+                    input.capture_bodies,
+                    input.max_body_size,
+                    input.clear,
+                )
+            },
+        ),
+        Tool::new(
+            "network_requests",
+            "List the network requests a tab has made, most recent last, with method, status, type, timing and size. Use this to see why a page or API call failed.",
+            object_schema(
+                {
+                    let mut properties = json!({
+                        "tab_id": tab_id_property(),
+                        "limit": {
+                            "type": "number",
+                            "description": "Maximum number of requests to return (the most recent). Default is 50."
+                        },
+                        "include_headers": {
+                            "type": "boolean",
+                            "description": "Include request and response headers."
+                        },
+                        "include_bodies": {
+                            "type": "boolean",
+                            "description": "Include request bodies and any captured response bodies."
+                        }
+                    });
+                    for (name, schema) in network_filter_properties() {
+                        properties[name] = schema;
+                    }
+                    properties
+                },
+                &[],
+            ),
+            |agent, input: NetworkRequestsInput| match input.filter.compile() {
+                Ok(filter) => agent.network_requests(
+                    input.tab_id.as_deref(),
+                    &filter,
+                    input.limit.unwrap_or(50),
+                    input.include_headers,
+                    input.include_bodies,
+                ),
+                Err(e) => e,
+            },
+        ),
+        Tool::new(
+            "export_har",
+            "Export a tab's recorded network requests as a HAR 1.2 log, either returned directly or written to a managed file.",
+            object_schema(
+                {
+                    let mut properties = json!({
+                        "tab_id": tab_id_property(),
+                        "save": {
+                            "type": "boolean",
+                            "description": "Write the HAR to a managed file and return its file_id and path instead of the HAR. Managed files are deleted when the session stops."
+                        }
+                    });
+                    for (name, schema) in network_filter_properties() {
+                        properties[name] = schema;
+                    }
+                    properties
+                },
+                &[],
+            ),
+            |agent, input: ExportHarInput| match input.filter.compile() {
+                Ok(filter) => agent.export_har(input.tab_id.as_deref(), &filter, input.save),
+                Err(e) => e,
+            },
+        ),
+        Tool::new(
+            "get_console",
+            "List a tab's console messages and uncaught exceptions, plus browser messages such as failed loads and CSP violations, most recent last. Warnings and errors logged during a failed tool call are also attached to its result as data.console.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "level": {
+                        "type": "string",
+                        "enum": ["debug", "info", "warning", "error"],
+                        "description": "Only messages at this level or above, e.g. warning for warnings and errors."
+                    },
+                    "since": {
+                        "type": "number",
+                        "description": "Only messages logged at or after this time, in milliseconds since the epoch (the timestamp of a previous message)."
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "Maximum number of messages to return (most recent). Default 100."
+                    },
+                    "clear": {
+                        "type": "boolean",
+                        "description": "Empty the tab's buffer after reading. Default false."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: GetConsoleInput| {
+                agent.get_console(
+                    input.tab_id.as_deref(),
+                    input.level,
+                    input.since,
+                    input.limit.unwrap_or(100),
+                    input.clear,
+                )
+            },
+        ),
+        Tool::new(
+            "get_dialogs",
+            "List the JavaScript dialogs (alert, confirm, prompt, beforeunload) pages have opened, with their message, whether they were accepted or dismissed and by whom, and the current dialog policy.",
+            object_schema(
+                json!({
+                    "tab_id": {
+                        "type": "string",
+                        "description": "Only dialogs opened in this agent tab. Defaults to every tab."
+                    },
+                    "open_only": {
+                        "type": "boolean",
+                        "description": "Only dialogs still waiting for an answer. Default false."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: GetDialogsInput| {
+                agent.get_dialogs(input.tab_id.as_deref(), input.open_only)
+            },
+        ),
+        Tool::new(
+            "set_dialog_policy",
+            "Set how the session answers JavaScript dialogs as they open. By default dialogs are dismissed and beforeunload dialogs are accepted, so navigations can leave the page. With surface, a dialog stays open and the call it interrupts fails with dialog_open until handle_dialog answers it.",
+            object_schema(
+                json!({
+                    "action": {
+                        "type": "string",
+                        "enum": ["accept", "dismiss", "surface"],
+                        "description": "accept presses OK, dismiss presses Cancel, surface leaves the dialog open for handle_dialog."
+                    },
+                    "prompt_text": {
+                        "type": "string",
+                        "description": "Text to enter into prompt() dialogs that are accepted. Defaults to the prompt's default value."
+                    },
+                    "beforeunload": {
+                        "type": "string",
+                        "enum": ["accept", "dismiss", "surface"],
+                        "description": "Action for beforeunload (\"leave this page?\") dialogs, if different. accept leaves the page."
+                    }
+                }),
+                &["action"],
+            ),
+            |agent, input: DialogPolicy| agent.set_dialog_policy(input),
+        ),
+        Tool::new(
+            "handle_dialog",
+            "Answer a JavaScript dialog left open by the surface policy. Works even while another call is blocked by the dialog.",
+            object_schema(
+                json!({
+                    "tab_id": {
+                        "type": "string",
+                        "description": "Agent tab the dialog is open in. Defaults to the most recent open dialog."
+                    },
+                    "accept": {
+                        "type": "boolean",
+                        "description": "true to press OK (or leave the page, for beforeunload), false to press Cancel."
+                    },
+                    "prompt_text": {
+                        "type": "string",
+                        "description": "Text to enter, when accepting a prompt() dialog."
+                    }
+                }),
+                &["accept"],
+            ),
+            |agent, input: DialogAnswer| agent.handle_dialog(&input),
+        ),
+        Tool::new(
+            "add_intercept_rule",
+            "Add a rule, for every tab in the session, that blocks matching requests, rewrites their headers, or answers them with a fixture response. Rules are checked in the order added and the first match wins.",
+            object_schema(
+                json!({
+                    "url_pattern": {
+                        "type": "string",
+                        "description": "URL glob where * matches anything and ? one character, e.g. \"*://*.doubleclick.net/*\" or \"*.png\"."
+                    },
+                    "url_regex": {
+                        "type": "string",
+                        "description": "Regular expression matched against the URL, instead of url_pattern."
+                    },
+                    "resource_types": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Only match these resource types, e.g. [\"image\", \"font\", \"media\"]."
+                    },
+                    "action": {
+                        "type": "string",
+                        "enum": ["block", "modify_headers", "fulfill"],
+                        "description": "What to do with matching requests."
+                    },
+                    "set_headers": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" },
+                        "description": "modify_headers: request headers to add or replace."
+                    },
+                    "remove_headers": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "modify_headers: request headers to remove."
+                    },
+                    "status": { "type": "number", "description": "fulfill: HTTP status. Default is 200." },
+                    "headers": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" },
+                        "description": "fulfill: response headers."
+                    },
+                    "content_type": { "type": "string", "description": "fulfill: Content-Type of the response." },
+                    "body": { "type": "string", "description": "fulfill: response body text." },
+                    "body_base64": { "type": "string", "description": "fulfill: response body as base64, for binary data." }
+                }),
+                &["action"],
+            ),
+            |agent, input: RuleInput| agent.add_intercept_rule(input),
+        ),
+        Tool::new(
+            "list_intercept_rules",
+            "List the session's interception rules with how many requests each has matched.",
+            object_schema(json!({}), &[]),
+            |agent, _: Value| agent.list_intercept_rules(),
+        ),
+        Tool::new(
+            "remove_intercept_rule",
+            "Remove an interception rule, or all rules if no rule_id is given.",
+            object_schema(
+                json!({
+                    "rule_id": { "type": "string", "description": "The rule to remove, e.g. r1." }
+                }),
+                &[],
+            ),
+            |agent, input: RemoveInterceptRuleInput| {
+                agent.remove_intercept_rule(input.rule_id.as_deref())
+            },
+        ),
+        Tool::new(
+            "get_cookies",
+            "List the browser's cookies, including HttpOnly ones, optionally only those for a domain and its subdomains or with a given name.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "domain": { "type": "string", "description": "Only cookies for this domain or its subdomains, e.g. \"example.com\"." },
+                    "name": { "type": "string", "description": "Only cookies with this name." }
+                }),
+                &[],
+            ),
+            |agent, input: GetCookiesInput| {
+                agent.get_cookies(
+                    input.tab_id.as_deref(),
+                    input.domain.as_deref(),
+                    input.name.as_deref(),
+                )
+            },
+        ),
+        Tool::new(
+            "set_cookie",
+            "Set a cookie. Without url or domain, the cookie is set for the current page's URL.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "name": { "type": "string", "description": "Cookie name." },
+                    "value": { "type": "string", "description": "Cookie value." },
+                    "url": { "type": "string", "description": "URL the cookie belongs to; sets a host-only cookie." },
+                    "domain": { "type": "string", "description": "Domain the cookie belongs to; it is also sent to subdomains." },
+                    "path": { "type": "string", "description": "Cookie path. Default is /." },
+                    "expires": { "type": "number", "description": "Expiry in seconds since the Unix epoch. Omit for a session cookie." },
+                    "http_only": { "type": "boolean", "description": "Hide the cookie from page scripts." },
+                    "secure": { "type": "boolean", "description": "Only send the cookie over HTTPS." },
+                    "same_site": { "type": "string", "enum": ["Strict", "Lax", "None"], "description": "SameSite policy." }
+                }),
+                &["name", "value"],
+            ),
+            |agent, input: SetCookieInput| agent.set_cookie(input.tab_id.as_deref(), input.cookie),
+        ),
+        Tool::new(
+            "delete_cookie",
+            "Delete cookies with a given name, optionally only for a domain and path.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "name": { "type": "string", "description": "Name of the cookie to delete." },
+                    "domain": { "type": "string", "description": "Only delete it for this domain or its subdomains." },
+                    "path": { "type": "string", "description": "Only delete it for this exact path." }
+                }),
+                &["name"],
+            ),
+            |agent, input: DeleteCookieInput| {
+                agent.delete_cookie(
+                    input.tab_id.as_deref(),
+                    &input.name,
+                    input.domain.as_deref(),
+                    input.path.as_deref(),
+                )
+            },
+        ),
+        Tool::new(
+            "clear_cookies",
+            "Delete all cookies, or all cookies for a domain and its subdomains.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "domain": { "type": "string", "description": "Only clear cookies for this domain, e.g. \"example.com\"." }
+                }),
+                &[],
+            ),
+            |agent, input: ClearCookiesInput| {
+                agent.clear_cookies(input.tab_id.as_deref(), input.domain.as_deref())
+            },
+        ),
+        Tool::new(
+            "export_cookies",
+            "Export cookies as JSON or a Netscape cookies.txt file, either returned directly or written to a managed file.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "netscape"],
+                        "description": "json (an array of cookie objects) or netscape (cookies.txt, as used by curl and wget). Default is json."
+                    },
+                    "domain": { "type": "string", "description": "Only export cookies for this domain or its subdomains." },
+                    "save": {
+                        "type": "boolean",
+                        "description": "Write the cookies to a managed file and return its file_id and path instead of the cookies. Managed files are deleted when the session stops."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: ExportCookiesInput| {
+                agent.export_cookies(
+                    input.tab_id.as_deref(),
+                    input.format,
+                    input.domain.as_deref(),
+                    input.save,
+                )
+            },
+        ),
+        Tool::new(
+            "import_cookies",
+            "Import cookies from JSON or a Netscape cookies.txt file, given as content or as a managed file from export_cookies.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "netscape"],
+                        "description": "Format of the cookies. Detected from the content if omitted."
+                    },
+                    "content": { "type": "string", "description": "The cookies to import." },
+                    "file_id": { "type": "string", "description": "ID of a managed file to read the cookies from, instead of content." }
+                }),
+                &[],
+            ),
+            |agent, input: ImportCookiesInput| {
+                agent.import_cookies(
+                    input.tab_id.as_deref(),
+                    input.format,
+                    input.content.as_deref(),
+                    input.file_id.as_deref(),
+                )
+            },
+        ),
+        Tool::new(
+            "get_storage",
+            "Read localStorage or sessionStorage for the current page's origin, either every item or one key.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "type": { "type": "string", "enum": ["local", "session"], "description": "Which storage to read." },
+                    "key": { "type": "string", "description": "Only read this key." }
+                }),
+                &["type"],
+            ),
+            |agent, input: GetStorageInput| {
+                agent.get_storage(input.tab_id.as_deref(), input.kind, input.key.as_deref())
+            },
+        ),
+        Tool::new(
+            "set_storage",
+            "Write, remove or clear localStorage or sessionStorage items for the current page's origin. Clearing happens first, then removals, then writes.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "type": { "type": "string", "enum": ["local", "session"], "description": "Which storage to change." },
+                    "items": {
+                        "type": "object",
+                        "description": "Keys and values to write. Non-string values are stored as JSON."
+                    },
+                    "remove": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Keys to remove."
+                    },
+                    "clear": { "type": "boolean", "description": "Remove every item first." }
+                }),
+                &["type"],
+            ),
+            |agent, input: SetStorageInput| {
+                agent.set_storage(
+                    input.tab_id.as_deref(),
+                    input.kind,
+                    &input.items,
+                    &input.remove,
+                    input.clear,
+                )
+            },
+        ),
+        Tool::new(
+            "search_google",
+            "Search Google for a query and open the results page.",
+            object_schema(
+                json!({
+                    "tab_id": tab_id_property(),
+                    "query": { "type": "string", "description": "The search query." }
+                }),
+                &["query"],
+            ),
+            |agent, input: SearchGoogleInput| {
+                let mut search_url = url::Url::parse("https://www.google.com/search").unwrap();
+                search_url.query_pairs_mut().append_pair("q", &input.query);
+
+                let mut result = agent.navigate(input.tab_id.as_deref(), search_url.as_str(), None);
+                if result.success {
+                    result.data = Some(json!({
+                        "searched": input.query,
+                        "url": search_url.as_str(),
+                        "navigated": result.data
+                    }));
+                }
+                result
+            },
+        ),
+        Tool::new(
+            "open_tab",
+            "Open a new browser tab. Returns the new tab's ID.",
+            object_schema(
+                json!({
+                    "url": {
+                        "type": "string",
+                        "description": "Optional URL to open in the new tab. If not provided, opens a blank tab."
+                    },
+                    "activate": {
+                        "type": "boolean",
+                        "description": "Whether the new tab becomes the active tab. Default true."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: OpenTabInput| {
+                agent.open_tab(input.url.as_deref(), input.activate.unwrap_or(true))
+            },
+        ),
+        Tool::new(
+            "list_tabs",
+            "List open tabs with their IDs, URLs, titles, and loading state.",
+            object_schema(json!({}), &[]),
+            |agent, _: Value| agent.list_tabs(),
+        ),
+        Tool::new(
+            "switch_tab",
+            "Make another tab the active tab. Tools called without a tab_id act on the active tab.",
+            object_schema(
+                json!({ "tab_id": { "type": "string", "description": "ID of the tab to switch to." } }),
+                &["tab_id"],
+            ),
+            |agent, input: SwitchTabInput| agent.switch_tab(&input.tab_id),
+        ),
+        Tool::new(
+            "close_tab",
+            "Close a tab.",
+            object_schema(
+                json!({
+                    "tab_id": {
+                        "type": "string",
+                        "description": "Optional ID of the tab to close. Defaults to the active tab."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: TabInput| agent.close_tab(input.tab_id.as_deref()),
+        ),
+        Tool::new(
+            "list_popups",
+            "List tabs and windows the page opened on its own (not through a click), such as window.open calls from scripts or timers, that aren't agent tabs yet.",
+            object_schema(json!({}), &[]),
+            |agent, _: Value| agent.list_popups(),
+        ),
+        Tool::new(
+            "adopt_popup",
+            "Make a popup from list_popups an agent tab, so other tools can use its tab_id.",
+            object_schema(
+                json!({
+                    "target_id": {
+                        "type": "string",
+                        "description": "target_id from list_popups. Defaults to the most recently opened popup."
+                    },
+                    "activate": {
+                        "type": "boolean",
+                        "description": "Whether to make it the active tab. Default true."
+                    }
+                }),
+                &[],
+            ),
+            |agent, input: AdoptPopupInput| {
+                agent.adopt_popup(input.target_id.as_deref(), input.activate.unwrap_or(true))
+            },
+        ),
+    ]
+}
@@ -0,0 +1,298 @@
+/**
+ * VybeR Agent Wait Conditions
+ *
+ * Conditions the `wait` tool and post-action waits poll for: an element
+ * reaching a state, text becoming visible, the URL matching a pattern, a
+ * JS predicate turning true, the network going idle, or the document
+ * reaching a load state. Polling goes through `pause`, so waits stay
+ * cancellable and respect the call's deadline.
+ */
+
+use super::intercept::glob_to_regex;
+use super::{evaluate_value, AgentTab, BrowserAgent, ErrorCode, ToolResult};
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How long a wait may take when no timeout is given
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(5000);
+
+/// How long the network must be quiet to count as idle by default
+const DEFAULT_IDLE: Duration = Duration::from_millis(500);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Called with the selector and state. Returns whether the element is in
+/// that state, or null for an invalid selector.
+const ELEMENT_STATE_JS: &str = r#"
+(selector, state) => {
+  let el;
+  try {
+    el = document.querySelector(selector);
+  } catch (e) {
+    return null;
+  }
+  const visible = !!el && el.getClientRects().length > 0 && getComputedStyle(el).visibility !== "hidden";
+  return { attached: !!el, visible, hidden: !visible, detached: !el }[state];
+}
+"#;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementState {
+    /// In the DOM, visible or not
+    #[default]
+    Attached,
+    Visible,
+    /// Not visible, or not in the DOM at all
+    Hidden,
+    /// Not in the DOM
+    Detached,
+}
+
+impl ElementState {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Attached => "attached",
+            Self::Visible => "visible",
+            Self::Hidden => "hidden",
+            Self::Detached => "detached",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadState {
+    #[serde(alias = "domcontentloaded")]
+    DomContentLoaded,
+    Load,
+}
+
+#[derive(Debug, Clone)]
+pub enum WaitCondition {
+    Element {
+        selector: String,
+        state: ElementState,
+    },
+    /// Text visible somewhere on the page
+    Text(String),
+    Url(Regex),
+    /// A JS expression that becomes truthy
+    Predicate(String),
+    /// No requests loading for this long
+    NetworkIdle(Duration),
+    Load(LoadState),
+}
+
+impl fmt::Display for WaitCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Element { selector, state } => write!(f, "{} to be {}", selector, state.name()),
+            Self::Text(text) => write!(f, "text '{}'", text),
+            Self::Url(pattern) => write!(f, "URL matching {}", pattern),
+            Self::Predicate(expression) => write!(f, "predicate {}", expression),
+            Self::NetworkIdle(idle) => write!(f, "network idle for {} ms", idle.as_millis()),
+            Self::Load(LoadState::DomContentLoaded) => write!(f, "DOMContentLoaded"),
+            Self::Load(LoadState::Load) => write!(f, "load"),
+        }
+    }
+}
+
+/// A condition and how long to wait for it. Without a condition, the wait
+/// just sleeps for the timeout.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "WaitInput")]
+pub struct WaitFor {
+    pub condition: Option<WaitCondition>,
+    pub timeout: Duration,
+}
+
+/// The condition fields as they appear in tool input
+#[derive(Deserialize)]
+struct WaitInput {
+    selector: Option<String>,
+    #[serde(default)]
+    state: ElementState,
+    text: Option<String>,
+    /// URL glob where `*` matches any run of characters
+    url: Option<String>,
+    url_regex: Option<String>,
+    predicate: Option<String>,
+    #[serde(default)]
+    network_idle: bool,
+    idle_ms: Option<u64>,
+    load_state: Option<LoadState>,
+    timeout: Option<u64>,
+}
+
+impl TryFrom<WaitInput> for WaitFor {
+    type Error = String;
+
+    fn try_from(input: WaitInput) -> Result<Self, Self::Error> {
+        let url = match (input.url_regex, input.url) {
+            (Some(pattern), _) => Some(pattern),
+            (None, Some(glob)) => Some(glob_to_regex(&glob)),
+            (None, None) => None,
+        }
+        .map(|pattern| Regex::new(&pattern).map_err(|e| format!("invalid URL pattern: {}", e)))
+        .transpose()?;
+
+        let mut conditions = Vec::new();
+        if let Some(selector) = input.selector {
+            conditions.push(WaitCondition::Element {
+                selector,
+                state: input.state,
+            });
+        }
+        conditions.extend(input.text.map(WaitCondition::Text));
+        conditions.extend(url.map(WaitCondition::Url));
+        conditions.extend(input.predicate.map(WaitCondition::Predicate));
+        if input.network_idle || input.idle_ms.is_some() {
+            conditions.push(WaitCondition::NetworkIdle(
+                input.idle_ms.map_or(DEFAULT_IDLE, Duration::from_millis),
+            ));
+        }
+        conditions.extend(input.load_state.map(WaitCondition::Load));
+
+        if conditions.len() > 1 {
+            return Err("give only one wait condition".to_string());
+        }
+        Ok(Self {
+            condition: conditions.pop(),
+            timeout: input.timeout.map_or(DEFAULT_TIMEOUT, Duration::from_millis),
+        })
+    }
+}
+
+impl BrowserAgent {
+    /// Wait for a condition, or for the timeout if there is none
+    pub fn wait(&self, tab_id: Option<&str>, wait: &WaitFor) -> ToolResult {
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return e,
+        };
+
+        let Some(condition) = &wait.condition else {
+            if let Some(result) = self.pause(wait.timeout) {
+                return result;
+            }
+            return ToolResult::ok(serde_json::json!({ "waited_ms": wait.timeout.as_millis() }));
+        };
+
+        match self.wait_until(agent_tab, condition, wait.timeout) {
+            Ok(elapsed) => ToolResult::ok(serde_json::json!({
+                "waited_for": condition.to_string(),
+                "elapsed_ms": elapsed.as_millis()
+            })),
+            Err(e) => e,
+        }
+    }
+
+    /// After an action, wait for `after` if given, or else sleep `default`.
+    /// Returns the result to bail out with if the wait fails.
+    pub(super) fn settle(
+        &self,
+        tab_id: Option<&str>,
+        after: Option<&WaitFor>,
+        default: Duration,
+    ) -> Option<ToolResult> {
+        let Some(WaitFor {
+            condition: Some(condition),
+            timeout,
+        }) = after
+        else {
+            return self.pause(after.map_or(default, |a| a.timeout));
+        };
+
+        let agent_tab = match self.get_agent_tab(tab_id) {
+            Ok(agent_tab) => agent_tab,
+            Err(e) => return Some(e),
+        };
+        self.wait_until(agent_tab, condition, *timeout).err()
+    }
+
+    /// Poll until `condition` holds, returning how long that took
+    fn wait_until(
+        &self,
+        agent_tab: &AgentTab,
+        condition: &WaitCondition,
+        timeout: Duration,
+    ) -> Result<Duration, ToolResult> {
+        let start = Instant::now();
+        let mut last_error = None;
+        loop {
+            match condition_met(agent_tab, condition) {
+                Ok(true) => return Ok(start.elapsed()),
+                Ok(false) => {}
+                // Scripts fail while a navigation swaps the document, so
+                // keep polling and report the error only on timeout
+                Err(e) if condition_fails_fast(&e) => return Err(e),
+                Err(e) => last_error = e.error.map(|e| e.message),
+            }
+
+            if start.elapsed() >= timeout {
+                let mut message = format!("Timeout waiting for: {}", condition);
+                if let Some(error) = last_error {
+                    message.push_str(&format!(" (last error: {})", error));
+                }
+                return Err(ToolResult::err(ErrorCode::Timeout, message));
+            }
+            if let Some(result) = self.pause(POLL_INTERVAL) {
+                return Err(result);
+            }
+        }
+    }
+}
+
+fn condition_fails_fast(result: &ToolResult) -> bool {
+    result
+        .error
+        .as_ref()
+        .is_some_and(|e| e.code == ErrorCode::InvalidSelector)
+}
+
+fn condition_met(agent_tab: &AgentTab, condition: &WaitCondition) -> Result<bool, ToolResult> {
+    let tab = &agent_tab.tab;
+    match condition {
+        WaitCondition::Element { selector, state } => {
+            let expression = format!(
+                "({})({}, {})",
+                ELEMENT_STATE_JS,
+                serde_json::Value::from(selector.as_str()),
+                serde_json::Value::from(state.name())
+            );
+            match evaluate_value(tab, &expression)? {
+                serde_json::Value::Bool(met) => Ok(met),
+                _ => Err(ToolResult::err(
+                    ErrorCode::InvalidSelector,
+                    format!("Invalid selector: {}", selector),
+                )),
+            }
+        }
+        WaitCondition::Text(text) => {
+            let expression = format!(
+                "!!document.body && document.body.innerText.includes({})",
+                serde_json::Value::from(text.as_str())
+            );
+            Ok(evaluate_value(tab, &expression)?.as_bool() == Some(true))
+        }
+        WaitCondition::Url(pattern) => Ok(pattern.is_match(&tab.get_url())),
+        WaitCondition::Predicate(expression) => {
+            let expression = format!("(async () => !!({}))()", expression);
+            Ok(evaluate_value(tab, &expression)?.as_bool() == Some(true))
+        }
+        WaitCondition::NetworkIdle(idle) => Ok(agent_tab
+            .network
+            .idle_for()
+            .is_some_and(|quiet| quiet >= *idle)),
+        WaitCondition::Load(state) => {
+            let ready = evaluate_value(tab, "document.readyState")?;
+            Ok(match state {
+                LoadState::DomContentLoaded => ready == "interactive" || ready == "complete",
+                LoadState::Load => ready == "complete",
+            })
+        }
+    }
+}
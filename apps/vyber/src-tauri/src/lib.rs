@@ -2,9 +2,12 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod agent;
+mod profiles;
+mod session;
 
-use std::collections::HashMap;
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
 use serde::{Deserialize, Serialize};
 
@@ -21,14 +24,29 @@ impl TabManager {
     }
 }
 
-// Store for browser agent instance
+// Store for browser agent sessions. Each session launches its own Chrome
+// process, either with a throwaway profile or a named persistent one that
+// only one session may use at a time. The lock is only held to look
+// sessions up; agent work runs on each session's worker thread.
 struct AgentManager {
-    agent: Option<agent::BrowserAgent>,
+    sessions: HashMap<String, Arc<session::AgentSession>>,
+    profiles_in_use: HashMap<String, String>, // profile -> session_id
+    next_session_id: usize,
+    next_request_id: usize,
+    /// IDs of calls queued or running in any session, so `agent_cancel`
+    /// can't reach into another session's call
+    request_ids: HashSet<String>,
 }
 
 impl AgentManager {
     fn new() -> Self {
-        Self { agent: None }
+        Self {
+            sessions: HashMap::new(),
+            profiles_in_use: HashMap::new(),
+            next_session_id: 1,
+            next_request_id: 1,
+            request_ids: HashSet::new(),
+        }
     }
 }
 
@@ -165,334 +183,480 @@ async fn show_tab(
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AgentToolResult {
     success: bool,
+    status: agent::ToolStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
     data: Option<serde_json::Value>,
-    error: Option<String>,
+    error: Option<agent::ToolError>,
 }
 
-/// Start the browser agent (launches Chrome)
-#[tauri::command]
-async fn agent_start(
-    state: tauri::State<'_, AppState>,
-    headless: bool,
-) -> Result<AgentToolResult, String> {
-    let mut agent_manager = state.agent_manager.lock().unwrap();
+impl From<agent::ToolResult> for AgentToolResult {
+    fn from(result: agent::ToolResult) -> Self {
+        Self {
+            success: result.success,
+            status: result.status,
+            request_id: None,
+            data: result.data,
+            error: result.error,
+        }
+    }
+}
 
-    if agent_manager.agent.is_some() {
-        return Ok(AgentToolResult {
-            success: true,
-            data: Some(serde_json::json!({ "message": "Agent already running" })),
-            error: None,
-        });
+impl AgentToolResult {
+    fn ok(data: serde_json::Value) -> Self {
+        agent::ToolResult::ok(data).into()
     }
 
-    match agent::BrowserAgent::new(headless) {
-        Ok(browser_agent) => {
-            agent_manager.agent = Some(browser_agent);
-            Ok(AgentToolResult {
-                success: true,
-                data: Some(serde_json::json!({ "message": "Agent started" })),
-                error: None,
-            })
-        }
-        Err(e) => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some(e),
-        }),
+    fn err(code: agent::ErrorCode, message: impl Into<String>) -> Self {
+        agent::ToolResult::err(code, message).into()
     }
 }
 
-/// Stop the browser agent
-#[tauri::command]
-async fn agent_stop(
-    state: tauri::State<'_, AppState>,
-) -> Result<AgentToolResult, String> {
-    let mut agent_manager = state.agent_manager.lock().unwrap();
+fn lock_agent_manager(state: &AppState) -> std::sync::MutexGuard<'_, AgentManager> {
+    // Nothing panics while holding the lock, but never let a poisoned
+    // mutex take every agent command down with it
+    state
+        .agent_manager
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
 
-    if let Some(agent) = agent_manager.agent.take() {
-        let _ = agent.close();
-    }
+/// Per-invocation options accepted by every session-bound agent command
+/// as `call: { requestId, timeoutMs }`
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallOptions {
+    request_id: Option<String>,
+    timeout_ms: Option<u64>,
+}
 
-    Ok(AgentToolResult {
-        success: true,
-        data: Some(serde_json::json!({ "message": "Agent stopped" })),
-        error: None,
-    })
+/// Queue a job on a session's worker thread and wait for the result.
+/// The call can be aborted with `agent_cancel(request_id)` or by its timeout.
+/// A request ID can only be in flight once across all sessions.
+async fn run_in_session<F>(
+    state: &AppState,
+    session_id: &str,
+    name: &str,
+    call: Option<CallOptions>,
+    job: F,
+) -> Result<AgentToolResult, String>
+where
+    F: FnOnce(&mut agent::BrowserAgent) -> agent::ToolResult + Send + 'static,
+{
+    let options = call.unwrap_or_default();
+    let (session, request_id) = {
+        let mut agent_manager = lock_agent_manager(state);
+        let request_id = match options.request_id {
+            Some(id) => id,
+            // Skip numbers a caller-supplied ID has taken
+            None => loop {
+                let id = format!("r{}", agent_manager.next_request_id);
+                agent_manager.next_request_id += 1;
+                if !agent_manager.request_ids.contains(&id) {
+                    break id;
+                }
+            },
+        };
+        if !agent_manager.request_ids.insert(request_id.clone()) {
+            let mut result = AgentToolResult::err(
+                agent::ErrorCode::InvalidArgument,
+                format!("Request ID already in use: {}", request_id),
+            );
+            result.request_id = Some(request_id);
+            return Ok(result);
+        }
+        (agent_manager.sessions.get(session_id).cloned(), request_id)
+    };
+
+    let mut result: AgentToolResult = match session {
+        Some(session) => {
+            let timeout = options.timeout_ms.map(Duration::from_millis);
+            session.run(name, &request_id, timeout, job).await.into()
+        }
+        None => AgentToolResult::err(
+            agent::ErrorCode::AgentNotStarted,
+            format!("Session not found: {}. Call agent_start first.", session_id),
+        ),
+    };
+    lock_agent_manager(state).request_ids.remove(&request_id);
+    result.request_id = Some(request_id);
+    Ok(result)
 }
 
-/// Navigate to a URL
+/// Start a browser agent session (launches its own Chrome process).
+/// Returns the session ID that every other agent command takes. With a
+/// `profile`, the browser keeps logins, cookies and storage in that named
+/// profile across sessions; otherwise (or with `ephemeral`) it gets a
+/// throwaway profile.
 #[tauri::command]
-async fn agent_navigate(
+async fn agent_start(
+    app: AppHandle,
     state: tauri::State<'_, AppState>,
-    url: String,
+    headless: bool,
+    session_id: Option<String>,
+    profile: Option<String>,
+    ephemeral: Option<bool>,
 ) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.navigate(&url);
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
+    if profile.is_some() && ephemeral == Some(true) {
+        return Ok(AgentToolResult::err(
+            agent::ErrorCode::InvalidArgument,
+            "Pass either profile or ephemeral, not both",
+        ));
+    }
+
+    let session_id = {
+        let mut agent_manager = lock_agent_manager(&state);
+        match session_id {
+            Some(id) => id,
+            None => {
+                let id = format!("s{}", agent_manager.next_session_id);
+                agent_manager.next_session_id += 1;
+                id
+            }
         }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started. Call agent_start first.".to_string()),
-        }),
+    };
+
+    let already_running = Ok(AgentToolResult::ok(serde_json::json!({
+        "message": "Session already running",
+        "session_id": session_id
+    })));
+
+    if lock_agent_manager(&state)
+        .sessions
+        .contains_key(&session_id)
+    {
+        return already_running;
     }
-}
 
-/// Extract text from the current page
-#[tauri::command]
-async fn agent_extract_text(
-    state: tauri::State<'_, AppState>,
-    selector: Option<String>,
-    max_length: Option<usize>,
-) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
+    // Reserve the profile before launching, since Chrome can't share it
+    let profile = match profile {
+        Some(name) => {
+            let opened = profiles::ProfileStore::new(&app).and_then(|store| store.open(&name));
+            let opened = match opened {
+                Ok(opened) => opened,
+                Err(e) => return Ok(agent::ToolResult::from_error(e).into()),
+            };
+            let mut agent_manager = lock_agent_manager(&state);
+            if let Some(owner) = agent_manager.profiles_in_use.get(&name) {
+                return Ok(AgentToolResult::err(
+                    agent::ErrorCode::Busy,
+                    format!("Profile {} is in use by session {}", name, owner),
+                ));
+            }
+            agent_manager
+                .profiles_in_use
+                .insert(name, session_id.clone());
+            Some(opened)
+        }
+        None => None,
+    };
+    let profile_name = profile.as_ref().map(|p| p.name.clone());
+    let release_profile = || {
+        if let Some(name) = &profile_name {
+            lock_agent_manager(&state).profiles_in_use.remove(name);
+        }
+    };
 
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.extract_text(
-                selector.as_deref(),
-                max_length.unwrap_or(8000),
-            );
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
+    // Launching Chrome is slow, so it happens on the worker without the lock
+    let browser_session = match session::AgentSession::spawn(headless, profile).await {
+        Ok(browser_session) => browser_session,
+        Err(e) => {
+            release_profile();
+            return Ok(agent::ToolResult::from_error(e).into());
+        }
+    };
+
+    let duplicate = {
+        let mut agent_manager = lock_agent_manager(&state);
+        if agent_manager.sessions.contains_key(&session_id) {
+            Some(browser_session)
+        } else {
+            agent_manager
+                .sessions
+                .insert(session_id.clone(), Arc::new(browser_session));
+            None
         }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
+    };
+
+    // Lost a race with another agent_start for the same name
+    if let Some(duplicate) = duplicate {
+        duplicate.stop().await;
+        release_profile();
+        return already_running;
     }
+
+    Ok(AgentToolResult::ok(serde_json::json!({
+        "message": "Agent started",
+        "session_id": session_id,
+        "profile": profile_name
+    })))
 }
 
-/// Extract links from the current page
+/// Stop a browser agent session
 #[tauri::command]
-async fn agent_extract_links(
+async fn agent_stop(
     state: tauri::State<'_, AppState>,
-    selector: Option<String>,
-    max_links: Option<usize>,
+    session_id: String,
 ) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.extract_links(
-                selector.as_deref(),
-                max_links.unwrap_or(50),
-            );
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
+    let session = lock_agent_manager(&state).sessions.remove(&session_id);
+
+    match session {
+        Some(session) => {
+            session.stop().await;
+            // Chrome has exited, so its profile lock is gone
+            if let Some(profile) = &session.profile {
+                lock_agent_manager(&state).profiles_in_use.remove(profile);
+            }
+            Ok(AgentToolResult::ok(serde_json::json!({
+                "message": "Agent stopped",
+                "session_id": session_id
+            })))
         }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
+        None => Ok(AgentToolResult::err(
+            agent::ErrorCode::AgentNotStarted,
+            format!("Session not found: {}", session_id),
+        )),
     }
 }
 
-/// Click an element
+/// Cancel a queued or in-flight agent call by its request ID
 #[tauri::command]
-async fn agent_click(
+async fn agent_cancel(
     state: tauri::State<'_, AppState>,
-    selector: Option<String>,
-    text: Option<String>,
+    request_id: String,
 ) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.click(selector.as_deref(), text.as_deref());
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
-        }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
+    let sessions: Vec<Arc<session::AgentSession>> = lock_agent_manager(&state)
+        .sessions
+        .values()
+        .cloned()
+        .collect();
+
+    if sessions.iter().any(|session| session.cancel(&request_id)) {
+        Ok(AgentToolResult::ok(
+            serde_json::json!({ "cancelled": request_id }),
+        ))
+    } else {
+        Ok(AgentToolResult::err(
+            agent::ErrorCode::InvalidArgument,
+            format!("No queued or running request: {}", request_id),
+        ))
     }
 }
 
-/// Fill a form field
+/// List running agent sessions and whether each is busy
 #[tauri::command]
-async fn agent_fill_form(
-    state: tauri::State<'_, AppState>,
-    selector: String,
-    value: String,
-    submit: bool,
-) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.fill_form(&selector, &value, submit);
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
+async fn agent_list_sessions(state: tauri::State<'_, AppState>) -> Result<AgentToolResult, String> {
+    let agent_manager = lock_agent_manager(&state);
+
+    let mut sessions: Vec<serde_json::Value> = agent_manager
+        .sessions
+        .iter()
+        .map(|(id, session)| {
+            serde_json::json!({
+                "session_id": id,
+                "headless": session.headless,
+                "profile": session.profile,
+                "busy": session.is_busy(),
+                "pending_jobs": session.pending_jobs(),
+                "current_job": session.current_job().map(|(name, request_id)| {
+                    serde_json::json!({ "tool": name, "request_id": request_id })
+                })
             })
-        }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
-    }
+        })
+        .collect();
+    sessions.sort_by(|a, b| a["session_id"].as_str().cmp(&b["session_id"].as_str()));
+
+    Ok(AgentToolResult::ok(serde_json::json!({
+        "sessions": sessions,
+        "count": sessions.len()
+    })))
 }
 
-/// Take a screenshot
+/// The error to return if a running session is using `profile`
+fn profile_in_use(state: &AppState, profile: &str) -> Option<AgentToolResult> {
+    lock_agent_manager(state)
+        .profiles_in_use
+        .get(profile)
+        .map(|owner| {
+            AgentToolResult::err(
+                agent::ErrorCode::Busy,
+                format!(
+                    "Profile {} is in use by session {}. Stop the session first.",
+                    profile, owner
+                ),
+            )
+        })
+}
+
+/// List persistent agent profiles with their size and the session using them
 #[tauri::command]
-async fn agent_screenshot(
+async fn agent_list_profiles(
+    app: AppHandle,
     state: tauri::State<'_, AppState>,
-    full_page: bool,
 ) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.screenshot(full_page);
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
-        }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
+    let mut profiles = match profiles::ProfileStore::new(&app).and_then(|store| store.list()) {
+        Ok(profiles) => profiles,
+        Err(e) => return Ok(agent::ToolResult::from_error(e).into()),
+    };
+
+    let agent_manager = lock_agent_manager(&state);
+    for profile in &mut profiles {
+        profile.session_id = agent_manager.profiles_in_use.get(&profile.name).cloned();
     }
+
+    Ok(AgentToolResult::ok(serde_json::json!({
+        "profiles": profiles,
+        "count": profiles.len()
+    })))
 }
 
-/// Scroll the page
+/// Copy a profile (logins, cookies, storage) to a new name
 #[tauri::command]
-async fn agent_scroll(
+async fn agent_clone_profile(
+    app: AppHandle,
     state: tauri::State<'_, AppState>,
-    direction: String,
-    amount: Option<i32>,
+    source: String,
+    target: String,
 ) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.scroll(&direction, amount);
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
-        }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
+    if let Some(in_use) = profile_in_use(&state, &source) {
+        return Ok(in_use);
     }
+
+    let result =
+        profiles::ProfileStore::new(&app).and_then(|store| store.clone_profile(&source, &target));
+    Ok(match result {
+        Ok(profile) => AgentToolResult::ok(serde_json::json!({
+            "message": "Profile cloned",
+            "source": source,
+            "profile": profile.name
+        })),
+        Err(e) => agent::ToolResult::from_error(e).into(),
+    })
 }
 
-/// Wait for element or timeout
+/// Clear everything stored in a profile, keeping the profile itself
 #[tauri::command]
-async fn agent_wait(
+async fn agent_reset_profile(
+    app: AppHandle,
     state: tauri::State<'_, AppState>,
-    selector: Option<String>,
-    timeout: Option<u64>,
+    profile: String,
 ) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.wait(selector.as_deref(), timeout.unwrap_or(5000));
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
-        }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
+    if let Some(in_use) = profile_in_use(&state, &profile) {
+        return Ok(in_use);
     }
+
+    Ok(
+        match profiles::ProfileStore::new(&app).and_then(|store| store.reset(&profile)) {
+            Ok(()) => AgentToolResult::ok(serde_json::json!({
+                "message": "Profile reset",
+                "profile": profile
+            })),
+            Err(e) => agent::ToolResult::from_error(e).into(),
+        },
+    )
 }
 
-/// Get page information
+/// Delete a profile and everything stored in it
 #[tauri::command]
-async fn agent_get_page_info(
+async fn agent_delete_profile(
+    app: AppHandle,
     state: tauri::State<'_, AppState>,
+    profile: String,
 ) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.get_page_info();
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
-        }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
+    if let Some(in_use) = profile_in_use(&state, &profile) {
+        return Ok(in_use);
     }
+
+    Ok(
+        match profiles::ProfileStore::new(&app).and_then(|store| store.delete(&profile)) {
+            Ok(()) => AgentToolResult::ok(serde_json::json!({
+                "message": "Profile deleted",
+                "profile": profile
+            })),
+            Err(e) => agent::ToolResult::from_error(e).into(),
+        },
+    )
 }
 
-/// Execute arbitrary JavaScript
+/// List the tools agent_execute accepts, with their input JSON Schemas
 #[tauri::command]
-async fn agent_evaluate_js(
+async fn agent_list_tools() -> Result<AgentToolResult, String> {
+    let tools = agent::tools::registry();
+    Ok(AgentToolResult::ok(serde_json::json!({
+        "tools": tools,
+        "count": tools.len()
+    })))
+}
+
+/// Run a registered tool in an agent session
+#[tauri::command]
+async fn agent_execute(
     state: tauri::State<'_, AppState>,
-    script: String,
+    session_id: String,
+    name: String,
+    input: Option<serde_json::Value>,
+    call: Option<CallOptions>,
 ) -> Result<AgentToolResult, String> {
-    let agent_manager = state.agent_manager.lock().unwrap();
-
-    match &agent_manager.agent {
-        Some(agent) => {
-            let result = agent.evaluate_js(&script);
-            Ok(AgentToolResult {
-                success: result.success,
-                data: result.data,
-                error: result.error,
-            })
+    let tool = match agent::tools::find(&name) {
+        Some(tool) => tool,
+        None => {
+            return Ok(AgentToolResult::err(
+                agent::ErrorCode::InvalidArgument,
+                format!("Unknown tool: {}", name),
+            ))
         }
-        None => Ok(AgentToolResult {
-            success: false,
-            data: None,
-            error: Some("Agent not started".to_string()),
-        }),
+    };
+
+    // Reject bad input before it waits in the session's queue
+    let input = input.unwrap_or_default();
+    let job = match tool.bind(input.clone()) {
+        Ok(job) => job,
+        Err(e) => return Ok(agent::ToolResult::from_error(e).into()),
+    };
+
+    // An open dialog can leave the worker blocked until it is answered, so
+    // answers skip the queue
+    if tool.name == "handle_dialog" {
+        let session = lock_agent_manager(&state)
+            .sessions
+            .get(&session_id)
+            .cloned();
+        return Ok(match (session, serde_json::from_value(input)) {
+            (Some(session), Ok(answer)) => session.answer_dialog(&answer).into(),
+            (Some(_), Err(e)) => AgentToolResult::err(
+                agent::ErrorCode::InvalidArgument,
+                format!("Invalid input for handle_dialog: {}", e),
+            ),
+            (None, _) => AgentToolResult::err(
+                agent::ErrorCode::AgentNotStarted,
+                format!("Session not found: {}. Call agent_start first.", session_id),
+            ),
+        });
     }
+
+    run_in_session(&state, &session_id, tool.name, call, job).await
 }
 
-/// Simple HTTP fetch (no browser needed)
+/// Simple HTTP fetch (no browser needed). Long pages come back in chunks;
+/// pass `chunk` or the returned `next_cursor` to read further.
 #[tauri::command]
 async fn agent_fetch_page(
     url: String,
+    mode: Option<agent::ExtractMode>,
+    max_length: Option<usize>,
+    max_tokens: Option<usize>,
+    chunk: Option<usize>,
+    cursor: Option<String>,
 ) -> Result<AgentToolResult, String> {
-    let result = agent::fetch_page(&url).await;
-    Ok(AgentToolResult {
-        success: result.success,
-        data: result.data,
-        error: result.error,
-    })
+    let page = agent::PageRequest {
+        max_length,
+        max_tokens,
+        chunk,
+        cursor,
+    };
+    Ok(agent::fetch_page(&url, mode.unwrap_or_default(), &page)
+        .await
+        .into())
 }
 
 // ============================================
@@ -527,16 +691,14 @@ pub fn run() {
             // Browser agent
             agent_start,
             agent_stop,
-            agent_navigate,
-            agent_extract_text,
-            agent_extract_links,
-            agent_click,
-            agent_fill_form,
-            agent_screenshot,
-            agent_scroll,
-            agent_wait,
-            agent_get_page_info,
-            agent_evaluate_js,
+            agent_list_sessions,
+            agent_cancel,
+            agent_list_profiles,
+            agent_clone_profile,
+            agent_reset_profile,
+            agent_delete_profile,
+            agent_list_tools,
+            agent_execute,
             agent_fetch_page,
         ])
         .run(tauri::generate_context!())
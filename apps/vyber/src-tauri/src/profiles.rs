@@ -0,0 +1,194 @@
+/**
+ * VybeR Agent Profiles
+ *
+ * Named Chrome user-data-dirs under the app data directory, so an agent
+ * session can keep logins, cookies and storage between runs. Chrome locks a
+ * user-data-dir while it runs, so a profile is used by one session at a
+ * time; the caller tracks which profiles are in use.
+ */
+
+use crate::agent::{ErrorCode, ToolError};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Files Chrome uses to lock a running profile, which must not be copied
+const LOCK_FILES: &[&str] = &[
+    "SingletonLock",
+    "SingletonSocket",
+    "SingletonCookie",
+    "lockfile",
+];
+
+/// A named persistent profile
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub size_bytes: u64,
+    /// Session currently running with this profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+/// The directory holding all profiles
+pub struct ProfileStore {
+    root: PathBuf,
+}
+
+impl ProfileStore {
+    pub fn new(app: &AppHandle) -> Result<Self, ToolError> {
+        let data_dir = app.path().app_data_dir().map_err(|e| {
+            ToolError::new(
+                ErrorCode::Internal,
+                format!("Failed to find app data directory: {}", e),
+            )
+        })?;
+        Ok(Self {
+            root: data_dir.join("profiles"),
+        })
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf, ToolError> {
+        let valid = !name.is_empty()
+            && name.len() <= 64
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(ToolError::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "Invalid profile name: {:?}. Use up to 64 letters, digits, '-' or '_'.",
+                    name
+                ),
+            ));
+        }
+        Ok(self.root.join(name))
+    }
+
+    fn existing(&self, name: &str) -> Result<PathBuf, ToolError> {
+        let path = self.path(name)?;
+        if !path.is_dir() {
+            return Err(ToolError::new(
+                ErrorCode::InvalidArgument,
+                format!("Profile not found: {}", name),
+            ));
+        }
+        Ok(path)
+    }
+
+    /// The profile's directory, created if this is its first use
+    pub fn open(&self, name: &str) -> Result<Profile, ToolError> {
+        let path = self.path(name)?;
+        fs::create_dir_all(&path).map_err(|e| io_error("create profile", name, e))?;
+        Ok(Profile {
+            name: name.to_string(),
+            path,
+        })
+    }
+
+    /// All profiles with their size on disk, sorted by name
+    pub fn list(&self) -> Result<Vec<ProfileInfo>, ToolError> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error("list", "profiles", e)),
+        };
+
+        let mut profiles: Vec<ProfileInfo> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| {
+                Some(ProfileInfo {
+                    name: entry.file_name().into_string().ok()?,
+                    size_bytes: dir_size(&entry.path()),
+                    session_id: None,
+                })
+            })
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+
+    /// Copy `source` to a new profile named `target`
+    pub fn clone_profile(&self, source: &str, target: &str) -> Result<Profile, ToolError> {
+        let source_path = self.existing(source)?;
+        let target_path = self.path(target)?;
+        if target_path.exists() {
+            return Err(ToolError::new(
+                ErrorCode::InvalidArgument,
+                format!("Profile already exists: {}", target),
+            ));
+        }
+
+        if let Err(e) = copy_dir(&source_path, &target_path) {
+            let _ = fs::remove_dir_all(&target_path);
+            return Err(io_error("clone profile", source, e));
+        }
+        Ok(Profile {
+            name: target.to_string(),
+            path: target_path,
+        })
+    }
+
+    /// Wipe a profile back to empty, keeping its name
+    pub fn reset(&self, name: &str) -> Result<(), ToolError> {
+        let path = self.existing(name)?;
+        fs::remove_dir_all(&path)
+            .and_then(|_| fs::create_dir_all(&path))
+            .map_err(|e| io_error("reset profile", name, e))
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), ToolError> {
+        let path = self.existing(name)?;
+        fs::remove_dir_all(&path).map_err(|e| io_error("delete profile", name, e))
+    }
+}
+
+fn io_error(action: &str, name: &str, e: io::Error) -> ToolError {
+    ToolError::new(
+        ErrorCode::Internal,
+        format!("Failed to {} {}: {}", action, name, e),
+    )
+}
+
+fn copy_dir(source: &Path, target: &Path) -> io::Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if LOCK_FILES.iter().any(|lock| name == *lock) {
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target.join(&name))?;
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), target.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
@@ -0,0 +1,334 @@
+/**
+ * VybeR Agent Sessions
+ *
+ * Each agent session owns a BrowserAgent on a dedicated worker thread.
+ * Tauri commands queue jobs on the worker and await the result, so blocking
+ * CDP calls and sleeps never run on (or stall) the async runtime.
+ */
+
+use crate::agent::tools::Job;
+use crate::agent::{
+    BrowserAgent, CallToken, DialogAnswer, DialogLog, ErrorCode, Interrupter, ToolError, ToolResult,
+};
+use crate::profiles::Profile;
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+
+/// Maximum number of jobs a session holds, counting the running one
+const MAX_QUEUED_JOBS: usize = 16;
+
+/// Tools that still run while a dialog is waiting for an answer
+const DIALOG_TOOLS: &[&str] = &["get_dialogs", "set_dialog_policy", "handle_dialog"];
+
+enum Message {
+    Run {
+        name: String,
+        request_id: String,
+        token: Arc<CallToken>,
+        job: Job,
+        reply: oneshot::Sender<ToolResult>,
+    },
+    Stop {
+        reply: oneshot::Sender<()>,
+    },
+}
+
+/// A queued or running job that can still be cancelled
+struct PendingCall {
+    token: Arc<CallToken>,
+    cancelled: Arc<Notify>,
+}
+
+/// The job the worker is currently running
+struct RunningJob {
+    name: String,
+    request_id: String,
+}
+
+/// Handle to a running agent session's worker thread
+pub struct AgentSession {
+    sender: mpsc::Sender<Message>,
+    pending: Arc<AtomicUsize>,
+    current: Arc<Mutex<Option<RunningJob>>>,
+    calls: Mutex<HashMap<String, PendingCall>>,
+    interrupter: Interrupter,
+    dialogs: DialogLog,
+    pub headless: bool,
+    /// Persistent profile, or None for a throwaway one
+    pub profile: Option<String>,
+}
+
+impl AgentSession {
+    /// Launch a browser on a new worker thread
+    pub async fn spawn(headless: bool, profile: Option<Profile>) -> Result<Self, ToolError> {
+        let profile_name = profile.as_ref().map(|p| p.name.clone());
+        let (sender, receiver) = mpsc::channel::<Message>();
+        let (ready_tx, ready_rx) =
+            oneshot::channel::<Result<(Interrupter, DialogLog), ToolError>>();
+        let pending = Arc::new(AtomicUsize::new(0));
+        let current = Arc::new(Mutex::new(None));
+
+        let worker_pending = pending.clone();
+        let worker_current = current.clone();
+        thread::Builder::new()
+            .name("vyber-agent".to_string())
+            .spawn(move || {
+                let mut agent = match BrowserAgent::new(headless, profile.map(|p| p.path)) {
+                    Ok(agent) => {
+                        let _ = ready_tx.send(Ok((agent.interrupter(), agent.dialogs())));
+                        agent
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+
+                let stopped = run_worker(&mut agent, receiver, &worker_pending, &worker_current);
+                let _ = agent.close();
+                if let Some(reply) = stopped {
+                    let _ = reply.send(());
+                }
+            })
+            .map_err(|e| {
+                ToolError::new(
+                    ErrorCode::LaunchFailed,
+                    format!("Failed to spawn agent worker: {}", e),
+                )
+            })?;
+
+        match ready_rx.await {
+            Ok(Ok((interrupter, dialogs))) => Ok(Self {
+                sender,
+                pending,
+                current,
+                calls: Mutex::new(HashMap::new()),
+                interrupter,
+                dialogs,
+                headless,
+                profile: profile_name,
+            }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(ToolError::new(
+                ErrorCode::LaunchFailed,
+                "Agent worker exited during startup",
+            )),
+        }
+    }
+
+    /// Queue a job on the worker and wait for its result. The call resolves
+    /// early as cancelled or timed out if `cancel` is called with its request
+    /// ID or the timeout (which includes time spent queued) elapses, and
+    /// with a dialog_open error while a dialog is left open for the user.
+    pub async fn run<F>(
+        &self,
+        name: &str,
+        request_id: &str,
+        timeout: Option<Duration>,
+        job: F,
+    ) -> ToolResult
+    where
+        F: FnOnce(&mut BrowserAgent) -> ToolResult + Send + 'static,
+    {
+        // Claim a slot up front, so concurrent calls can't both squeeze in
+        if self.pending.fetch_add(1, Ordering::SeqCst) >= MAX_QUEUED_JOBS {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return ToolResult::err(
+                ErrorCode::Busy,
+                format!("Session busy: {} jobs already queued", MAX_QUEUED_JOBS),
+            );
+        }
+
+        let token = Arc::new(CallToken::new(timeout.map(|t| Instant::now() + t)));
+        let cancelled = Arc::new(Notify::new());
+        {
+            let mut calls = self.calls.lock().unwrap_or_else(PoisonError::into_inner);
+            if calls.contains_key(request_id) {
+                self.pending.fetch_sub(1, Ordering::SeqCst);
+                return ToolResult::err(
+                    ErrorCode::InvalidArgument,
+                    format!("Request ID already in use: {}", request_id),
+                );
+            }
+            calls.insert(
+                request_id.to_string(),
+                PendingCall {
+                    token: token.clone(),
+                    cancelled: cancelled.clone(),
+                },
+            );
+        }
+
+        let (reply, response) = oneshot::channel();
+
+        let message = Message::Run {
+            name: name.to_string(),
+            request_id: request_id.to_string(),
+            token: token.clone(),
+            job: Box::new(job),
+            reply,
+        };
+
+        let result = if self.sender.send(message).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            ToolResult::err(ErrorCode::AgentNotStarted, "Agent session has stopped")
+        } else {
+            let deadline = async {
+                match token.deadline() {
+                    Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            // An open dialog blocks its page, and likely this job with it
+            let mut dialogs = self.dialogs.subscribe();
+            let dialog_open = async {
+                if DIALOG_TOOLS.contains(&name) || dialogs.wait_for(|open| *open > 0).await.is_err()
+                {
+                    std::future::pending::<()>().await;
+                }
+            };
+
+            tokio::select! {
+                result = response => result.unwrap_or_else(|_| {
+                    ToolResult::err(
+                        ErrorCode::AgentNotStarted,
+                        "Agent session stopped before the job finished",
+                    )
+                }),
+                _ = cancelled.notified() => ToolResult::cancelled(),
+                _ = deadline => {
+                    self.interrupt_if_running(request_id);
+                    ToolResult::timed_out()
+                }
+                _ = dialog_open => {
+                    // Stop the job rather than let it act once the dialog closes
+                    token.cancel();
+                    self.interrupt_if_running(request_id);
+                    self.dialogs.open_error()
+                }
+            }
+        };
+
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(request_id);
+        result
+    }
+
+    /// Cancel a queued or running job. Returns false if the request ID is
+    /// unknown (already finished, or never submitted to this session).
+    pub fn cancel(&self, request_id: &str) -> bool {
+        let calls = self.calls.lock().unwrap_or_else(PoisonError::into_inner);
+        match calls.get(request_id) {
+            Some(call) => {
+                call.token.cancel();
+                call.cancelled.notify_one();
+                self.interrupt_if_running(request_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Abort the CDP call the worker is blocked on if it belongs to `request_id`
+    fn interrupt_if_running(&self, request_id: &str) {
+        let current = self.current.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(job) = current.as_ref().filter(|job| job.request_id == request_id) {
+            // The interrupt is itself a CDP round trip, so keep it off the runtime
+            let interrupter = self.interrupter.clone();
+            let terminate_js = job.name == "evaluate_js";
+            thread::spawn(move || interrupter.interrupt(terminate_js));
+        }
+    }
+
+    /// Answer an open dialog without queueing behind the job it blocks
+    pub fn answer_dialog(&self, answer: &DialogAnswer) -> ToolResult {
+        self.dialogs.answer(answer)
+    }
+
+    /// Whether a job is running or queued
+    pub fn is_busy(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) > 0
+    }
+
+    /// Number of jobs running or waiting to run
+    pub fn pending_jobs(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// Name and request ID of the tool currently running, if any
+    pub fn current_job(&self) -> Option<(String, String)> {
+        self.current
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map(|job| (job.name.clone(), job.request_id.clone()))
+    }
+
+    /// Close the browser once queued jobs have finished
+    pub async fn stop(&self) {
+        let (reply, done) = oneshot::channel();
+        if self.sender.send(Message::Stop { reply }).is_ok() {
+            let _ = done.await;
+        }
+    }
+}
+
+/// Run jobs until a stop request arrives (returned so it can be answered
+/// once the browser is closed) or every sender is dropped
+fn run_worker(
+    agent: &mut BrowserAgent,
+    receiver: mpsc::Receiver<Message>,
+    pending: &AtomicUsize,
+    current: &Mutex<Option<RunningJob>>,
+) -> Option<oneshot::Sender<()>> {
+    while let Ok(message) = receiver.recv() {
+        match message {
+            Message::Run {
+                name,
+                request_id,
+                token,
+                job,
+                reply,
+            } => {
+                // Jobs cancelled or expired while queued never start
+                let result = match token.check() {
+                    Some(result) => result,
+                    None => {
+                        *current.lock().unwrap_or_else(PoisonError::into_inner) =
+                            Some(RunningJob {
+                                name: name.clone(),
+                                request_id,
+                            });
+                        agent.begin_call(token);
+
+                        // A panicking job must not take the whole session down with it
+                        let result =
+                            catch_unwind(AssertUnwindSafe(|| job(agent))).unwrap_or_else(|_| {
+                                ToolResult::err(
+                                    ErrorCode::Internal,
+                                    format!("Agent panicked while running {}", name),
+                                )
+                            });
+
+                        *current.lock().unwrap_or_else(PoisonError::into_inner) = None;
+                        agent.end_call(result)
+                    }
+                };
+
+                pending.fetch_sub(1, Ordering::SeqCst);
+                let _ = reply.send(result);
+            }
+            Message::Stop { reply } => return Some(reply),
+        }
+    }
+    None
+}
@@ -8,20 +8,238 @@
  * for full browser automation without iframe restrictions.
  */
 
-import type { ToolResult, ToolName } from "./tools";
+import { COMPLETE_TOOL, type Tool, type ToolResult, type ToolErrorCode } from "./tools";
 import { isTauri } from "@/lib/platform";
 
 type ToolInput = Record<string, unknown>;
 
-// Flag to track if Tauri agent is started
-let tauriAgentStarted = false;
+// Session ID of the Tauri agent started by this executor, if any
+let tauriSessionId: string | null = null;
+
+// Tool definitions fetched from the Rust registry, cached after the first call
+let tauriTools: Tool[] | null = null;
+
+/**
+ * The tools the PWA fallback below implements against the page iframe.
+ * The native registry doesn't serve PWA mode, so they're defined here.
+ */
+const IFRAME_TOOLS: Tool[] = [
+  {
+    name: "navigate",
+    description: "Navigate to a URL in the current tab. Use this to open websites, search Google, or go to specific pages.",
+    input_schema: {
+      type: "object",
+      properties: {
+        url: {
+          type: "string",
+          description: "The URL to navigate to. Can be a full URL or a search query (will search Google)."
+        }
+      },
+      required: ["url"]
+    }
+  },
+  {
+    name: "extract_text",
+    description: "Extract all visible text content from the current page. Use this to read and understand page content.",
+    input_schema: {
+      type: "object",
+      properties: {
+        selector: {
+          type: "string",
+          description: "Optional CSS selector to extract text from a specific element. If not provided, extracts all visible text."
+        },
+        max_length: {
+          type: "number",
+          description: "Maximum characters to return. Default is 8000."
+        }
+      }
+    }
+  },
+  {
+    name: "extract_links",
+    description: "Extract all links from the current page with their text and URLs.",
+    input_schema: {
+      type: "object",
+      properties: {
+        selector: {
+          type: "string",
+          description: "Optional CSS selector to limit link extraction to a specific area."
+        },
+        max_links: {
+          type: "number",
+          description: "Maximum number of links to return. Default is 50."
+        }
+      }
+    }
+  },
+  {
+    name: "click",
+    description: "Click on an element on the page by CSS selector or visible text.",
+    input_schema: {
+      type: "object",
+      properties: {
+        selector: {
+          type: "string",
+          description: "CSS selector of the element to click."
+        },
+        text: {
+          type: "string",
+          description: "Visible text of the element to click (alternative to selector)."
+        }
+      }
+    }
+  },
+  {
+    name: "fill_form",
+    description: "Fill in a form field with text.",
+    input_schema: {
+      type: "object",
+      properties: {
+        selector: {
+          type: "string",
+          description: "CSS selector of the input field."
+        },
+        value: {
+          type: "string",
+          description: "The text to fill in."
+        },
+        submit: {
+          type: "boolean",
+          description: "Whether to submit the form after filling. Default false."
+        }
+      },
+      required: ["selector", "value"]
+    }
+  },
+  {
+    name: "screenshot",
+    description: "Take a screenshot of the current page or a specific element.",
+    input_schema: {
+      type: "object",
+      properties: {
+        selector: {
+          type: "string",
+          description: "Optional CSS selector to screenshot only a specific element."
+        },
+        full_page: {
+          type: "boolean",
+          description: "Whether to capture the full scrollable page. Default false."
+        }
+      }
+    }
+  },
+  {
+    name: "scroll",
+    description: "Scroll the page in a direction or to a specific element.",
+    input_schema: {
+      type: "object",
+      properties: {
+        direction: {
+          type: "string",
+          enum: ["up", "down", "top", "bottom"],
+          description: "Direction to scroll."
+        },
+        selector: {
+          type: "string",
+          description: "CSS selector of element to scroll into view (alternative to direction)."
+        },
+        amount: {
+          type: "number",
+          description: "Pixels to scroll. Default is one viewport height."
+        }
+      }
+    }
+  },
+  {
+    name: "wait",
+    description: "Wait for a condition before proceeding.",
+    input_schema: {
+      type: "object",
+      properties: {
+        selector: {
+          type: "string",
+          description: "Wait for this element to appear on the page."
+        },
+        timeout: {
+          type: "number",
+          description: "Maximum milliseconds to wait. Default is 5000."
+        }
+      }
+    }
+  },
+  {
+    name: "get_page_info",
+    description: "Get information about the current page including URL, title, and metadata.",
+    input_schema: {
+      type: "object",
+      properties: {}
+    }
+  },
+  {
+    name: "search_google",
+    description: "Search Google for a query and return the results.",
+    input_schema: {
+      type: "object",
+      properties: {
+        query: {
+          type: "string",
+          description: "The search query."
+        }
+      },
+      required: ["query"]
+    }
+  },
+  {
+    name: "open_tab",
+    description: "Open a new browser tab. Returns the new tab's ID.",
+    input_schema: {
+      type: "object",
+      properties: {
+        url: {
+          type: "string",
+          description: "Optional URL to open in the new tab. If not provided, opens a blank tab."
+        },
+        activate: {
+          type: "boolean",
+          description: "Whether the new tab becomes the active tab. Default true."
+        }
+      }
+    }
+  },
+  {
+    name: "close_tab",
+    description: "Close the active tab.",
+    input_schema: {
+      type: "object",
+      properties: {}
+    }
+  },
+  COMPLETE_TOOL
+];
+
+/**
+ * Tool definitions to offer the model. In Tauri mode these come from the
+ * Rust tool registry so they always match what the backend accepts.
+ */
+export async function getAgentTools(): Promise<Tool[]> {
+  if (!isTauri) {
+    return IFRAME_TOOLS;
+  }
+
+  if (!tauriTools) {
+    const { invoke } = await import("@tauri-apps/api/core");
+    const result = await invoke<{ success: boolean; data?: { tools: Tool[] } }>("agent_list_tools");
+    tauriTools = [...(result.data?.tools ?? []), COMPLETE_TOOL];
+  }
+  return tauriTools;
+}
 
 /**
  * Execute a tool call from the agent
  * Automatically routes to Tauri backend when available
  */
 export async function executeTool(
-  name: ToolName,
+  name: string,
   input: ToolInput,
   context: {
     activeTabId: string | null;
@@ -343,7 +561,7 @@ export async function executeTool(
  * Uses Chrome DevTools Protocol for full browser automation
  */
 async function executeTauriTool(
-  name: ToolName,
+  name: string,
   input: ToolInput,
   _context: {
     activeTabId: string | null;
@@ -359,130 +577,54 @@ async function executeTauriTool(
   interface TauriResult {
     success: boolean;
     data?: unknown;
-    error?: string;
+    error?: { code: ToolErrorCode; message: string; retryable: boolean; status?: number };
+  }
+
+  const fromTauri = (result: TauriResult): ToolResult => ({
+    success: result.success,
+    data: result.data,
+    error: result.error?.message,
+    errorCode: result.error?.code,
+    httpStatus: result.error?.status,
+    retryable: result.error?.retryable,
+  });
+
+  // "complete" ends the agent loop and never reaches the browser
+  if (name === "complete") {
+    return {
+      success: true,
+      data: {
+        summary: input.summary,
+        result: input.data || null,
+        completed: true,
+      },
+    };
   }
 
   try {
+    const tools = await getAgentTools();
+    if (!tools.some(tool => tool.name === name)) {
+      return { success: false, error: `Unknown tool: ${name}`, errorCode: "invalid_argument" };
+    }
+
     // Auto-start the browser agent if not already running
-    if (!tauriAgentStarted && name !== "complete") {
+    if (!tauriSessionId) {
       const startResult = await invoke<TauriResult>("agent_start", { headless: false });
-      if (startResult.success) {
-        tauriAgentStarted = true;
+      const started = startResult.data as { session_id?: string } | undefined;
+      if (startResult.success && started?.session_id) {
+        tauriSessionId = started.session_id;
       } else {
-        return { success: false, error: startResult.error || "Failed to start agent" };
+        return startResult.error ? fromTauri(startResult) : { success: false, error: "Failed to start agent" };
       }
     }
 
-    switch (name) {
-      case "navigate": {
-        const url = input.url as string;
-        const result = await invoke<TauriResult>("agent_navigate", { url });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "extract_text": {
-        const result = await invoke<TauriResult>("agent_extract_text", {
-          selector: input.selector as string | undefined,
-          maxLength: (input.max_length as number) || 8000,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "extract_links": {
-        const result = await invoke<TauriResult>("agent_extract_links", {
-          selector: input.selector as string | undefined,
-          maxLinks: (input.max_links as number) || 50,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "click": {
-        const result = await invoke<TauriResult>("agent_click", {
-          selector: input.selector as string | undefined,
-          text: input.text as string | undefined,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "fill_form": {
-        const result = await invoke<TauriResult>("agent_fill_form", {
-          selector: input.selector as string,
-          value: input.value as string,
-          submit: (input.submit as boolean) || false,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "screenshot": {
-        const result = await invoke<TauriResult>("agent_screenshot", {
-          fullPage: (input.full_page as boolean) || false,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "scroll": {
-        const result = await invoke<TauriResult>("agent_scroll", {
-          direction: (input.direction as string) || "down",
-          amount: input.amount as number | undefined,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "wait": {
-        const result = await invoke<TauriResult>("agent_wait", {
-          selector: input.selector as string | undefined,
-          timeout: input.timeout as number | undefined,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "get_page_info": {
-        const result = await invoke<TauriResult>("agent_get_page_info");
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "search_google": {
-        const query = input.query as string;
-        const searchUrl = `https://www.google.com/search?q=${encodeURIComponent(query)}`;
-        const result = await invoke<TauriResult>("agent_navigate", { url: searchUrl });
-        return {
-          success: result.success,
-          data: { searched: query, url: searchUrl, navigated: result.data },
-          error: result.error,
-        };
-      }
-
-      case "open_tab": {
-        // For Tauri, we can open a URL in the current browser instance
-        const url = input.url as string | undefined;
-        if (url) {
-          const result = await invoke<TauriResult>("agent_navigate", { url });
-          return { success: result.success, data: { url, navigated: result.data }, error: result.error };
-        }
-        return { success: true, data: { note: "New tab opened" } };
-      }
-
-      case "close_tab": {
-        // For Tauri, this stops the browser agent
-        const result = await invoke<TauriResult>("agent_stop");
-        tauriAgentStarted = false;
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "complete": {
-        return {
-          success: true,
-          data: {
-            summary: input.summary,
-            result: input.data || null,
-            completed: true,
-          },
-        };
-      }
-
-      default:
-        return { success: false, error: `Unknown tool: ${name}` };
-    }
+    // Every browser tool goes through the Rust tool registry
+    const result = await invoke<TauriResult>("agent_execute", {
+      sessionId: tauriSessionId,
+      name,
+      input,
+    });
+    return fromTauri(result);
   } catch (error) {
     return { success: false, error: String(error) };
   }
@@ -492,11 +634,11 @@ async function executeTauriTool(
  * Stop the Tauri agent (cleanup)
  */
 export async function stopTauriAgent(): Promise<void> {
-  if (isTauri && tauriAgentStarted) {
+  if (isTauri && tauriSessionId) {
     try {
       const { invoke } = await import("@tauri-apps/api/core");
-      await invoke("agent_stop");
-      tauriAgentStarted = false;
+      await invoke("agent_stop", { sessionId: tauriSessionId });
+      tauriSessionId = null;
     } catch {
       // Ignore errors during cleanup
     }
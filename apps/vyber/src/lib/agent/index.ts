@@ -23,8 +23,8 @@
  * ```
  */
 
-export { AGENT_TOOLS, type Tool, type ToolResult, type ToolName } from "./tools";
-export { executeTool, stopTauriAgent } from "./executor";
+export { COMPLETE_TOOL, type Tool, type ToolResult, type ToolErrorCode } from "./tools";
+export { executeTool, getAgentTools, stopTauriAgent } from "./executor";
 export {
   runAgent,
   queryAgent,
@@ -5,8 +5,8 @@
  * executes them, and continues until the task is complete.
  */
 
-import { AGENT_TOOLS, type Tool, type ToolResult } from "./tools";
-import { executeTool } from "./executor";
+import { type Tool, type ToolResult } from "./tools";
+import { executeTool, getAgentTools } from "./executor";
 
 export interface AgentMessage {
   role: "user" | "assistant";
@@ -84,6 +84,7 @@ export async function runAgent(
   ];
 
   let iterations = 0;
+  const tools = await getAgentTools();
 
   while (iterations < maxIterations) {
     iterations++;
@@ -94,7 +95,7 @@ export async function runAgent(
         apiKey,
         proxyUrl,
         model,
-        tools: AGENT_TOOLS,
+        tools,
         system: AGENT_SYSTEM_PROMPT,
       });
 
@@ -157,7 +158,7 @@ export async function runAgent(
       for (const block of assistantContent) {
         if (block.type === "tool_use" && block.name && block.id) {
           const result = await executeTool(
-            block.name as Parameters<typeof executeTool>[0],
+            block.name,
             block.input || {},
             context
           );
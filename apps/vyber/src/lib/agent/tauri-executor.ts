@@ -6,202 +6,251 @@
  */
 
 import { invoke } from "@tauri-apps/api/core";
-import type { ToolResult, ToolName } from "./tools";
+import type { Tool, ToolResult, ToolErrorCode } from "./tools";
+
+interface TauriAgentError {
+  code: ToolErrorCode;
+  message: string;
+  retryable: boolean;
+  status?: number; // HTTP status for "http_status"
+}
 
 interface TauriAgentResult {
   success: boolean;
+  status: "ok" | "error" | "cancelled" | "timed_out";
+  request_id?: string;
   data?: unknown;
-  error?: string;
+  error?: TauriAgentError;
 }
 
 /**
- * Start the browser agent (launches Chrome/Chromium)
+ * Per-call options for session tools. Pass a `requestId` to be able to
+ * cancel the call with `cancelRequest`.
+ */
+export interface CallOptions {
+  requestId?: string;
+  timeoutMs?: number;
+}
+
+function toToolResult(result: TauriAgentResult): ToolResult {
+  return {
+    success: result.success,
+    status: result.status,
+    data: result.data,
+    error: result.error?.message,
+    errorCode: result.error?.code,
+    httpStatus: result.error?.status,
+    retryable: result.error?.retryable,
+  };
+}
+
+export interface StartOptions {
+  /** Persistent profile to keep logins, cookies and storage in. Created on first use. */
+  profile?: string;
+  /** Use a throwaway profile (the default when no profile is given) */
+  ephemeral?: boolean;
+}
+
+/**
+ * Start a browser agent session (launches its own Chrome/Chromium)
  * @param headless - Run browser in headless mode (no visible window)
+ * @param sessionId - Optional session name; one is generated if omitted.
+ *   The session ID is returned in `data.session_id`.
+ * @param options - Which profile the browser uses. A profile can only be
+ *   used by one session at a time.
  */
-export async function startAgent(headless = true): Promise<ToolResult> {
+export async function startAgent(
+  headless = true,
+  sessionId?: string,
+  options: StartOptions = {}
+): Promise<ToolResult> {
   try {
-    const result = await invoke<TauriAgentResult>("agent_start", { headless });
-    return {
-      success: result.success,
-      data: result.data,
-      error: result.error,
-    };
+    const result = await invoke<TauriAgentResult>("agent_start", { headless, sessionId, ...options });
+    return toToolResult(result);
   } catch (error) {
     return { success: false, error: String(error) };
   }
 }
 
 /**
- * Stop the browser agent
+ * Stop a browser agent session
  */
-export async function stopAgent(): Promise<ToolResult> {
+export async function stopAgent(sessionId: string): Promise<ToolResult> {
   try {
-    const result = await invoke<TauriAgentResult>("agent_stop");
-    return {
-      success: result.success,
-      data: result.data,
-      error: result.error,
-    };
+    const result = await invoke<TauriAgentResult>("agent_stop", { sessionId });
+    return toToolResult(result);
+  } catch (error) {
+    return { success: false, error: String(error) };
+  }
+}
+
+/**
+ * List running browser agent sessions
+ */
+export async function listSessions(): Promise<ToolResult> {
+  try {
+    const result = await invoke<TauriAgentResult>("agent_list_sessions");
+    return toToolResult(result);
+  } catch (error) {
+    return { success: false, error: String(error) };
+  }
+}
+
+/**
+ * List persistent agent profiles, with their size and the session using each
+ */
+export async function listProfiles(): Promise<ToolResult> {
+  try {
+    const result = await invoke<TauriAgentResult>("agent_list_profiles");
+    return toToolResult(result);
+  } catch (error) {
+    return { success: false, error: String(error) };
+  }
+}
+
+/**
+ * Copy a profile to a new name
+ */
+export async function cloneProfile(source: string, target: string): Promise<ToolResult> {
+  try {
+    const result = await invoke<TauriAgentResult>("agent_clone_profile", { source, target });
+    return toToolResult(result);
+  } catch (error) {
+    return { success: false, error: String(error) };
+  }
+}
+
+/**
+ * Clear a profile's logins, cookies and storage, keeping the profile
+ */
+export async function resetProfile(profile: string): Promise<ToolResult> {
+  try {
+    const result = await invoke<TauriAgentResult>("agent_reset_profile", { profile });
+    return toToolResult(result);
   } catch (error) {
     return { success: false, error: String(error) };
   }
 }
 
 /**
- * Execute a tool using the Tauri backend
+ * Delete a profile
+ */
+export async function deleteProfile(profile: string): Promise<ToolResult> {
+  try {
+    const result = await invoke<TauriAgentResult>("agent_delete_profile", { profile });
+    return toToolResult(result);
+  } catch (error) {
+    return { success: false, error: String(error) };
+  }
+}
+
+// Names in the Rust tool registry, cached after the first lookup
+let toolNames: Set<string> | null = null;
+
+/**
+ * List the tools the native agent supports, with their input schemas
+ */
+export async function listTools(): Promise<Tool[]> {
+  const result = await invoke<TauriAgentResult>("agent_list_tools");
+  const tools = (result.data as { tools: Tool[] }).tools;
+  toolNames = new Set(tools.map(tool => tool.name));
+  return tools;
+}
+
+/**
+ * Execute a tool in a Tauri agent session
  */
 export async function executeTauriTool(
-  name: ToolName,
-  input: Record<string, unknown>
+  sessionId: string,
+  name: string,
+  input: Record<string, unknown>,
+  call?: CallOptions
 ): Promise<ToolResult> {
+  // "complete" ends the agent loop and never reaches the browser
+  if (name === "complete") {
+    return {
+      success: true,
+      data: {
+        summary: input.summary,
+        result: input.data || null,
+        completed: true,
+      },
+    };
+  }
+
   try {
-    switch (name) {
-      case "navigate": {
-        const url = input.url as string;
-        const result = await invoke<TauriAgentResult>("agent_navigate", { url });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "extract_text": {
-        const selector = input.selector as string | undefined;
-        const maxLength = input.max_length as number | undefined;
-        const result = await invoke<TauriAgentResult>("agent_extract_text", {
-          selector,
-          maxLength,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "extract_links": {
-        const selector = input.selector as string | undefined;
-        const maxLinks = input.max_links as number | undefined;
-        const result = await invoke<TauriAgentResult>("agent_extract_links", {
-          selector,
-          maxLinks,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "click": {
-        const selector = input.selector as string | undefined;
-        const text = input.text as string | undefined;
-        const result = await invoke<TauriAgentResult>("agent_click", {
-          selector,
-          text,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "fill_form": {
-        const selector = input.selector as string;
-        const value = input.value as string;
-        const submit = (input.submit as boolean) || false;
-        const result = await invoke<TauriAgentResult>("agent_fill_form", {
-          selector,
-          value,
-          submit,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "screenshot": {
-        const fullPage = (input.full_page as boolean) || false;
-        const result = await invoke<TauriAgentResult>("agent_screenshot", {
-          fullPage,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "scroll": {
-        const direction = (input.direction as string) || "down";
-        const amount = input.amount as number | undefined;
-        const result = await invoke<TauriAgentResult>("agent_scroll", {
-          direction,
-          amount,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "wait": {
-        const selector = input.selector as string | undefined;
-        const timeout = input.timeout as number | undefined;
-        const result = await invoke<TauriAgentResult>("agent_wait", {
-          selector,
-          timeout,
-        });
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "get_page_info": {
-        const result = await invoke<TauriAgentResult>("agent_get_page_info");
-        return { success: result.success, data: result.data, error: result.error };
-      }
-
-      case "search_google": {
-        const query = input.query as string;
-        const searchUrl = `https://www.google.com/search?q=${encodeURIComponent(query)}`;
-        const result = await invoke<TauriAgentResult>("agent_navigate", {
-          url: searchUrl,
-        });
-        return {
-          success: result.success,
-          data: { searched: query, url: searchUrl, navigated: result.data },
-          error: result.error,
-        };
-      }
-
-      case "open_tab":
-      case "close_tab":
-        // These are handled at the orchestrator level for Tauri
-        return { success: true, data: { note: "Tab operations managed by orchestrator" } };
-
-      case "complete":
-        return {
-          success: true,
-          data: {
-            summary: input.summary,
-            result: input.data || null,
-            completed: true,
-          },
-        };
-
-      default:
-        return { success: false, error: `Unknown tool: ${name}` };
+    if (!toolNames) {
+      await listTools();
+    }
+    if (!toolNames?.has(name)) {
+      return { success: false, error: `Unknown tool: ${name}`, errorCode: "invalid_argument" };
     }
+
+    const result = await invoke<TauriAgentResult>("agent_execute", {
+      sessionId,
+      name,
+      input,
+      call,
+    });
+    return toToolResult(result);
   } catch (error) {
     return { success: false, error: String(error) };
   }
 }
 
 /**
- * Simple HTTP fetch without launching a full browser
- * (Uses reqwest in Rust, no Chrome needed)
+ * Cancel a queued or in-flight call started with `call.requestId`.
+ * The cancelled call resolves with `status: "cancelled"`.
  */
-export async function fetchPage(url: string): Promise<ToolResult> {
+export async function cancelRequest(requestId: string): Promise<ToolResult> {
   try {
-    const result = await invoke<TauriAgentResult>("agent_fetch_page", { url });
-    return {
-      success: result.success,
-      data: result.data,
-      error: result.error,
-    };
+    const result = await invoke<TauriAgentResult>("agent_cancel", { requestId });
+    return toToolResult(result);
   } catch (error) {
     return { success: false, error: String(error) };
   }
 }
 
+export interface PageOptions {
+  /** Chunk size in characters (default 8000) */
+  maxLength?: number;
+  /** Chunk size as an estimated token count */
+  maxTokens?: number;
+  /** Zero-based chunk index */
+  chunk?: number;
+  /** `next_cursor` from a previous result */
+  cursor?: string;
+}
+
 /**
- * Execute arbitrary JavaScript on the current page
+ * Simple HTTP fetch without launching a full browser
+ * (Uses reqwest in Rust, no Chrome needed)
+ * @param mode - "readable" returns the main content as Markdown with
+ *   title, byline, publish date and language instead of all body text
+ * @param page - Which chunk of a long page to return. Results include
+ *   `chunk`, `total_chunks` and `next_cursor`; pass the cursor back to continue.
  */
-export async function evaluateJs(script: string): Promise<ToolResult> {
+export async function fetchPage(
+  url: string,
+  mode: "text" | "readable" = "text",
+  page: PageOptions = {}
+): Promise<ToolResult> {
   try {
-    const result = await invoke<TauriAgentResult>("agent_evaluate_js", { script });
-    return {
-      success: result.success,
-      data: result.data,
-      error: result.error,
-    };
+    const result = await invoke<TauriAgentResult>("agent_fetch_page", { url, mode, ...page });
+    return toToolResult(result);
   } catch (error) {
     return { success: false, error: String(error) };
   }
 }
+
+/**
+ * Execute arbitrary JavaScript on the current page
+ */
+export async function evaluateJs(
+  sessionId: string,
+  script: string,
+  tabId?: string,
+  call?: CallOptions
+): Promise<ToolResult> {
+  return executeTauriTool(sessionId, "evaluate_js", { script, tab_id: tabId }, call);
+}